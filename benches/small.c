@@ -0,0 +1,36 @@
+/* A small translation unit with the usual kinds of declarations */
+
+enum mode { MODE_OFF, MODE_ON, MODE_AUTO = 0x10 };
+
+struct point_t {
+  int x;
+  int y;
+};
+
+struct limits_t {
+  unsigned short low;
+  unsigned short high;
+  struct point_t origin;
+};
+
+typedef struct {
+  const char *name;
+  enum mode mode;
+  struct limits_t limits;
+  unsigned char flags[4];
+} channel_t;
+
+const struct point_t corner = { .x = 10, .y = -10 };
+const struct limits_t defaults = { 1, 100, { 0, 0 } };
+const int scale = 4 * 1024;
+const char *const title = "benchmark";
+
+const channel_t channels[] = {
+  { "left", MODE_ON, { 0, 255, { 1, 2 } }, { 1, 2, 3, 4 } },
+  { "right", MODE_AUTO, { .high = 1023 }, { 0 } },
+  { .name = "center", .limits.origin.y = 5, .flags = { [2] = 9 } },
+};
+
+void f(void) {
+  static const int local = 3;
+}
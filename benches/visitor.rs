@@ -0,0 +1,139 @@
+//! Measure the parse and the visit of translation units, run with `cargo bench`
+//!
+//! A plain `main` instead of a benchmark framework, so it builds without further
+//! dependencies. The parse (preprocessing included) and the extraction by the visitor are
+//! timed separately, each as the best of several runs, and the allocations of a visit are
+//! counted. The large fixture is generated into the target directory if it doesn't exist yet.
+
+use c_ast::{Extraction, MyVisitor};
+use lang_c::driver::Config;
+use lang_c::visit::Visit;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+/// The system allocator, counting the allocations
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+/// The number of elements of the tables in the large fixture
+const ROWS: usize = 20_000;
+
+fn main() {
+    let config = Config::default();
+    let small = Path::new(env!("CARGO_MANIFEST_DIR")).join("benches/small.c");
+    let large = large_fixture();
+    for (path, runs) in [(small, 200), (large, 5)] {
+        bench(&config, &path, runs);
+    }
+}
+
+fn bench(config: &Config, path: &Path, runs: usize) {
+    let (parse_time, parse) = best(runs, || c_ast::parse_file(config, path, false).unwrap());
+    let visit = || {
+        let mut ex = Extraction::default();
+        MyVisitor::new(Some(path), &mut ex)
+            .with_enum_bases(&parse.source)
+            .visit_translation_unit(&parse.unit);
+        ex
+    };
+    let (visit_time, ex) = best(runs, visit);
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    drop(visit());
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+    let mb = parse.source.len() as f64 / 1e6;
+    println!(
+        "{}: {:.1} kB, {} values\n  parse {:>10.3?} {:>8.1} MB/s\n  visit {:>10.3?} {:>8.1} MB/s, \
+         {} allocations",
+        path.file_name().unwrap_or_default().to_string_lossy(),
+        mb * 1000.0,
+        ex.values.len(),
+        parse_time,
+        mb / parse_time.as_secs_f64(),
+        visit_time,
+        mb / visit_time.as_secs_f64(),
+        allocations,
+    );
+}
+
+/// The shortest time of `runs` runs of `f`, and the result of the last run
+fn best<T>(runs: usize, mut f: impl FnMut() -> T) -> (Duration, T) {
+    let mut fastest = Duration::MAX;
+    let mut result = None;
+    for _ in 0..runs {
+        let start = Instant::now();
+        result = Some(f());
+        fastest = fastest.min(start.elapsed());
+    }
+    (fastest, result.unwrap())
+}
+
+/// A translation unit with large tables of structs and many scalar constants, like the
+/// generated configuration tables c-ast is used on
+fn large_fixture() -> PathBuf {
+    let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join(format!("large_{}.c", ROWS));
+    if path.exists() {
+        return path;
+    }
+    let mut c = String::from(
+        "enum kind { KIND_A, KIND_B, KIND_C };\n\
+         struct range_t { int low; int high; };\n\
+         struct entry_t {\n  \
+           unsigned id;\n  \
+           enum kind kind;\n  \
+           const char *label;\n  \
+           struct range_t range;\n  \
+           unsigned char data[4];\n\
+         };\n",
+    );
+    c.push_str("const struct entry_t entries[] = {\n");
+    for i in 0..ROWS {
+        writeln!(
+            c,
+            "  {{ {}, KIND_{}, \"entry {}\", {{ -{}, {} }}, {{ {}, 0x{:02x}, 2, 3 }} }},",
+            i,
+            ["A", "B", "C"][i % 3],
+            i,
+            i % 100,
+            i * 2,
+            i % 256,
+            i % 256
+        )
+        .unwrap();
+    }
+    c.push_str("};\n");
+    for i in 0..ROWS / 10 {
+        writeln!(
+            c,
+            "const struct range_t range_{} = {{ .high = {} << 2, .low = {} }};",
+            i, i, i
+        )
+        .unwrap();
+        writeln!(c, "const unsigned long limit_{} = {}UL * 1024;", i, i).unwrap();
+    }
+    fs::write(&path, c).unwrap();
+    path
+}
@@ -0,0 +1,149 @@
+//! `_Alignas` in struct fields like `struct s { _Alignas(16) char buf[64]; };`
+//!
+//! The parser only takes `_Alignas` in declarations, so in the bodies of structs and unions
+//! it is replaced by the attribute `aligned` before parsing, see [`rewrite`]. The type form
+//! `_Alignas(double)` becomes `aligned(_Alignof(double))`.
+
+use std::borrow::Cow;
+
+const KEYWORD: &str = "_Alignas";
+
+/// The words a type name may start with, see [`is_type`]
+const TYPE_WORDS: &[&str] = &[
+    "void",
+    "char",
+    "short",
+    "int",
+    "long",
+    "float",
+    "double",
+    "signed",
+    "unsigned",
+    "_Bool",
+    "_Complex",
+    "struct",
+    "union",
+    "enum",
+    "const",
+    "volatile",
+    "_Atomic",
+    "__typeof__",
+];
+
+/// Replace the `_Alignas` specifiers in the struct and union bodies of the preprocessed `text`
+pub fn rewrite(text: &str) -> Cow<'_, str> {
+    if !text.contains(KEYWORD) {
+        return Cow::Borrowed(text);
+    }
+    let bytes = text.as_bytes();
+    let mut out = String::with_capacity(text.len());
+    // Whether each open brace is the body of a struct or union
+    let mut bodies: Vec<bool> = Vec::new();
+    let mut record = false;
+    let mut quote = None;
+    let mut copied = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        let c = bytes[i];
+        match quote {
+            Some(_) if c == b'\\' => i += 1,
+            Some(q) if c == q || c == b'\n' => quote = None,
+            Some(_) => (),
+            None if c == b'"' || c == b'\'' => quote = Some(c),
+            None if c == b'{' => {
+                bodies.push(record);
+                record = false;
+            }
+            None if c == b'}' => {
+                bodies.pop();
+            }
+            // A tag or declarator ends the struct specifier without a body
+            None if c == b';' || c == b'=' || c == b'(' || c == b')' => record = false,
+            None if is_word(c) => {
+                let len = bytes[i..].iter().take_while(|c| is_word(**c)).count();
+                let word = &text[i..i + len];
+                if word == "struct" || word == "union" {
+                    record = true;
+                } else if word == KEYWORD && bodies.last() == Some(&true) {
+                    if let Some((arg, end)) = argument(text, i + len) {
+                        out.push_str(&text[copied..i]);
+                        if is_type(arg) {
+                            out.push_str(&format!("__attribute__((aligned(_Alignof({}))))", arg));
+                        } else {
+                            out.push_str(&format!("__attribute__((aligned({})))", arg));
+                        }
+                        copied = end;
+                        i = end;
+                        continue;
+                    }
+                }
+                i += len;
+                continue;
+            }
+            None => (),
+        }
+        i += 1;
+    }
+    if copied == 0 {
+        return Cow::Borrowed(text);
+    }
+    out.push_str(&text[copied..]);
+    Cow::Owned(out)
+}
+
+/// The argument in the parentheses following `at` and the offset after them
+fn argument(text: &str, at: usize) -> Option<(&str, usize)> {
+    let start = at + text[at..].find(|c: char| !c.is_whitespace())?;
+    if !text[start..].starts_with('(') {
+        return None;
+    }
+    let mut depth = 0;
+    for (i, c) in text[start..].char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 1 => return Some((text[start + 1..start + i].trim(), start + i + 1)),
+            ')' => depth -= 1,
+            _ => (),
+        }
+    }
+    None
+}
+
+/// Whether the argument of `_Alignas` is a type name rather than an expression
+///
+/// The parser can't tell here, so it is a type if it starts with a type keyword, or if it is
+/// a single identifier ending in `_t` like `max_align_t`.
+fn is_type(arg: &str) -> bool {
+    let first = arg
+        .split(|c: char| !is_word(c as u8))
+        .next()
+        .unwrap_or_default();
+    TYPE_WORDS.contains(&first)
+        || (first.len() == arg.len()
+            && first.ends_with("_t")
+            && !first.starts_with(char::is_numeric))
+}
+
+fn is_word(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || c == b'_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rewritten_in_struct_bodies() {
+        assert_eq!(
+            rewrite("struct s { _Alignas(16) char buf[64]; _Alignas(double) char c; };"),
+            "struct s { __attribute__((aligned(16))) char buf[64]; \
+             __attribute__((aligned(_Alignof(double)))) char c; };"
+        );
+    }
+
+    #[test]
+    fn kept_in_declarations() {
+        let text = "_Alignas(32) struct s rx; void f(void) { _Alignas(8) int x; }";
+        assert_eq!(rewrite(text), text);
+    }
+}
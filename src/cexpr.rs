@@ -0,0 +1,233 @@
+//! Render expressions back into C source text
+
+use crate::ctype::MyType;
+use lang_c::ast::*;
+use lang_c::span::Node;
+
+pub fn render(expr: &Expression) -> String {
+    match expr {
+        Expression::Identifier(id) => id.node.name.clone(),
+        Expression::Constant(c) => constant(&c.node),
+        Expression::StringLiteral(s) => s.node.join(" "),
+        Expression::GenericSelection(g) => {
+            let mut parts = vec![render(&g.node.expression.node)];
+            for a in &g.node.associations {
+                parts.push(match &a.node {
+                    GenericAssociation::Type(t) => format!(
+                        "{}: {}",
+                        MyType::from_type_name(&t.node.type_name.node),
+                        render(&t.node.expression.node)
+                    ),
+                    GenericAssociation::Default(e) => format!("default: {}", render(&e.node)),
+                });
+            }
+            format!("_Generic({})", parts.join(", "))
+        }
+        Expression::Member(m) => {
+            let op = match m.node.operator.node {
+                MemberOperator::Direct => ".",
+                MemberOperator::Indirect => "->",
+            };
+            format!(
+                "{}{}{}",
+                operand(&m.node.expression.node),
+                op,
+                m.node.identifier.node.name
+            )
+        }
+        Expression::Call(c) => {
+            let args: Vec<String> = c.node.arguments.iter().map(|a| render(&a.node)).collect();
+            format!("{}({})", operand(&c.node.callee.node), args.join(", "))
+        }
+        Expression::CompoundLiteral(c) => format!(
+            "({}){}",
+            MyType::from_type_name(&c.node.type_name.node),
+            initializer_list(&c.node.initializer_list)
+        ),
+        Expression::SizeOf(t) => format!("sizeof({})", MyType::from_type_name(&t.node)),
+        Expression::AlignOf(t) => format!("_Alignof({})", MyType::from_type_name(&t.node)),
+        Expression::UnaryOperator(u) => {
+            let e = operand(&u.node.operand.node);
+            match u.node.operator.node {
+                UnaryOperator::PostIncrement => format!("{}++", e),
+                UnaryOperator::PostDecrement => format!("{}--", e),
+                UnaryOperator::PreIncrement => format!("++{}", e),
+                UnaryOperator::PreDecrement => format!("--{}", e),
+                UnaryOperator::Address => format!("&{}", e),
+                UnaryOperator::Indirection => format!("*{}", e),
+                UnaryOperator::Plus => format!("+{}", e),
+                UnaryOperator::Minus => format!("-{}", e),
+                UnaryOperator::Complement => format!("~{}", e),
+                UnaryOperator::Negate => format!("!{}", e),
+                UnaryOperator::SizeOf => format!("sizeof {}", e),
+            }
+        }
+        Expression::Cast(c) => format!(
+            "({}){}",
+            MyType::from_type_name(&c.node.type_name.node),
+            operand(&c.node.expression.node)
+        ),
+        Expression::BinaryOperator(b) => {
+            let lhs = operand(&b.node.lhs.node);
+            let rhs = operand(&b.node.rhs.node);
+            match b.node.operator.node {
+                BinaryOperator::Index => format!("{}[{}]", lhs, render(&b.node.rhs.node)),
+                ref op => format!("{} {} {}", lhs, binary_operator(op), rhs),
+            }
+        }
+        Expression::Conditional(c) => format!(
+            "{} ? {} : {}",
+            operand(&c.node.condition.node),
+            operand(&c.node.then_expression.node),
+            operand(&c.node.else_expression.node)
+        ),
+        Expression::Comma(es) => {
+            let es: Vec<String> = es.iter().map(|e| render(&e.node)).collect();
+            es.join(", ")
+        }
+        Expression::OffsetOf(o) => {
+            let mut path = o.node.designator.node.base.node.name.clone();
+            for m in &o.node.designator.node.members {
+                match &m.node {
+                    OffsetMember::Member(id) => path = format!("{}.{}", path, id.node.name),
+                    OffsetMember::IndirectMember(id) => {
+                        path = format!("{}->{}", path, id.node.name)
+                    }
+                    OffsetMember::Index(e) => path = format!("{}[{}]", path, render(&e.node)),
+                }
+            }
+            format!(
+                "offsetof({}, {})",
+                MyType::from_type_name(&o.node.type_name.node),
+                path
+            )
+        }
+        Expression::VaArg(v) => format!(
+            "va_arg({}, {})",
+            render(&v.node.va_list.node),
+            MyType::from_type_name(&v.node.type_name.node)
+        ),
+        Expression::Statement(_) => String::from("({ ... })"),
+    }
+}
+
+/// Render `expr` as operand of another operator, parenthesized where needed
+fn operand(expr: &Expression) -> String {
+    match expr {
+        Expression::BinaryOperator(b) if b.node.operator.node != BinaryOperator::Index => {
+            format!("({})", render(expr))
+        }
+        Expression::Conditional(_) | Expression::Comma(_) | Expression::Cast(_) => {
+            format!("({})", render(expr))
+        }
+        _ => render(expr),
+    }
+}
+
+pub fn constant(c: &Constant) -> String {
+    match c {
+        Constant::Integer(i) => integer(i),
+        Constant::Float(f) => {
+            let prefix = match f.base {
+                FloatBase::Decimal => "",
+                FloatBase::Hexadecimal => "0x",
+            };
+            let suffix = match &f.suffix.format {
+                FloatFormat::Float => String::from("f"),
+                FloatFormat::Double => String::new(),
+                FloatFormat::LongDouble => String::from("L"),
+                FloatFormat::TS18661Format(t) => format!("f{}", t.width),
+            };
+            let i = if f.suffix.imaginary { "i" } else { "" };
+            format!("{}{}{}{}", prefix, f.number, i, suffix)
+        }
+        Constant::Character(c) => c.clone(),
+    }
+}
+
+pub fn integer(i: &Integer) -> String {
+    let prefix = match i.base {
+        IntegerBase::Decimal => "",
+        IntegerBase::Octal => "0",
+        IntegerBase::Hexadecimal => "0x",
+        IntegerBase::Binary => "0b",
+    };
+    let suffix = match (i.suffix.unsigned, &i.suffix.size) {
+        (false, IntegerSize::Int) => "",
+        (false, IntegerSize::Long) => "L",
+        (false, IntegerSize::LongLong) => "LL",
+        (true, IntegerSize::Int) => "U",
+        (true, IntegerSize::Long) => "UL",
+        (true, IntegerSize::LongLong) => "ULL",
+    };
+    let imaginary = if i.suffix.imaginary { "i" } else { "" };
+    format!("{}{}{}{}", prefix, i.number, imaginary, suffix)
+}
+
+fn initializer_list(items: &[Node<InitializerListItem>]) -> String {
+    let items: Vec<String> = items
+        .iter()
+        .map(|i| {
+            let mut s = String::new();
+            for d in &i.node.designation {
+                match &d.node {
+                    Designator::Index(e) => s.push_str(&format!("[{}]", render(&e.node))),
+                    Designator::Member(id) => s.push_str(&format!(".{}", id.node.name)),
+                    Designator::Range(r) => s.push_str(&format!(
+                        "[{} ... {}]",
+                        render(&r.node.from.node),
+                        render(&r.node.to.node)
+                    )),
+                }
+            }
+            if !s.is_empty() {
+                s.push_str(" = ");
+            }
+            s.push_str(&initializer(&i.node.initializer.node));
+            s
+        })
+        .collect();
+    format!("{{{}}}", items.join(", "))
+}
+
+pub fn initializer(ini: &Initializer) -> String {
+    match ini {
+        Initializer::Expression(e) => render(&e.node),
+        Initializer::List(items) => initializer_list(items),
+    }
+}
+
+fn binary_operator(op: &BinaryOperator) -> &'static str {
+    match op {
+        BinaryOperator::Index => "[]",
+        BinaryOperator::Multiply => "*",
+        BinaryOperator::Divide => "/",
+        BinaryOperator::Modulo => "%",
+        BinaryOperator::Plus => "+",
+        BinaryOperator::Minus => "-",
+        BinaryOperator::ShiftLeft => "<<",
+        BinaryOperator::ShiftRight => ">>",
+        BinaryOperator::Less => "<",
+        BinaryOperator::Greater => ">",
+        BinaryOperator::LessOrEqual => "<=",
+        BinaryOperator::GreaterOrEqual => ">=",
+        BinaryOperator::Equals => "==",
+        BinaryOperator::NotEquals => "!=",
+        BinaryOperator::BitwiseAnd => "&",
+        BinaryOperator::BitwiseXor => "^",
+        BinaryOperator::BitwiseOr => "|",
+        BinaryOperator::LogicalAnd => "&&",
+        BinaryOperator::LogicalOr => "||",
+        BinaryOperator::Assign => "=",
+        BinaryOperator::AssignMultiply => "*=",
+        BinaryOperator::AssignDivide => "/=",
+        BinaryOperator::AssignModulo => "%=",
+        BinaryOperator::AssignPlus => "+=",
+        BinaryOperator::AssignMinus => "-=",
+        BinaryOperator::AssignShiftLeft => "<<=",
+        BinaryOperator::AssignShiftRight => ">>=",
+        BinaryOperator::AssignBitwiseAnd => "&=",
+        BinaryOperator::AssignBitwiseXor => "^=",
+        BinaryOperator::AssignBitwiseOr => "|=",
+    }
+}
@@ -0,0 +1,197 @@
+//! Find the comments documenting a declaration in the original source files
+//!
+//! The preprocessor drops the comments, but its line markers (`# 12 "foo.h"`) tell where a
+//! position of the preprocessed text comes from. The comment on the same line or the
+//! comment lines directly above are taken from there. The markers also tell whether the
+//! text comes from a system header.
+
+use crate::Location;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The preprocessed text of a translation unit and the original files it refers to
+pub struct Source<'s> {
+    text: &'s str,
+    markers: Vec<Marker>,
+    files: RefCell<HashMap<PathBuf, Option<Vec<String>>>>,
+}
+
+struct Marker {
+    /// The offset of the line following the marker
+    offset: usize,
+    file: PathBuf,
+    line: usize,
+    system: bool,
+}
+
+/// The directory of the system headers if the preprocessor output doesn't tell
+const SYSTEM_INCLUDE: &str = "/usr/include";
+
+impl<'s> Source<'s> {
+    pub fn new(text: &'s str) -> Source<'s> {
+        let mut markers = Vec::new();
+        let mut offset = 0;
+        for line in text.split_inclusive('\n') {
+            offset += line.len();
+            if let Some(mut m) = marker(line) {
+                m.offset = offset;
+                markers.push(m);
+            }
+        }
+        Source {
+            text,
+            markers,
+            files: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// The original file and line (starting at 1) of `offset` in the preprocessed text
+    pub fn location(&self, offset: usize) -> Option<(PathBuf, usize)> {
+        let m = self.marker_before(offset)?;
+        let skipped = self.text.get(m.offset..offset)?.matches('\n').count();
+        Some((m.file.clone(), m.line + skipped))
+    }
+
+    /// Whether `offset` in the preprocessed text comes from a system header
+    ///
+    /// These are marked as such by the preprocessor, e.g. the headers found in the `-isystem`
+    /// directories, or else are below `/usr/include`.
+    pub fn is_system(&self, offset: usize) -> bool {
+        match self.marker_before(offset) {
+            Some(m) => m.system || m.file.starts_with(SYSTEM_INCLUDE),
+            None => false,
+        }
+    }
+
+    /// The `#pragma` lines as they are written, with their location, except those of system
+    /// headers
+    ///
+    /// The preprocessor keeps these lines and turns `_Pragma("...")` operators into them.
+    /// Without line markers the lines of the preprocessed text in `path` are taken, the files
+    /// the markers name are shown as `shown` maps them.
+    pub fn pragmas(
+        &self,
+        path: &Path,
+        shown: &dyn Fn(&Path) -> PathBuf,
+    ) -> Vec<(String, Location)> {
+        let mut pragmas = Vec::new();
+        let mut offset = 0;
+        for (i, line) in self.text.split_inclusive('\n').enumerate() {
+            let start = offset;
+            offset += line.len();
+            let text = line.trim();
+            match text.strip_prefix('#') {
+                Some(rest) if rest.trim_start().starts_with("pragma") => (),
+                _ => continue,
+            }
+            if self.is_system(start) {
+                continue;
+            }
+            let (file, line) = match self.location(start) {
+                Some((file, line)) => (shown(&file), line),
+                None => (path.to_path_buf(), i + 1),
+            };
+            pragmas.push((String::from(text), Location { file, line }));
+        }
+        pragmas
+    }
+
+    fn marker_before(&self, offset: usize) -> Option<&Marker> {
+        let i = self
+            .markers
+            .partition_point(|m| m.offset <= offset)
+            .checked_sub(1)?;
+        Some(&self.markers[i])
+    }
+
+    /// The comment on the line of `offset` or else the comment lines directly above it
+    pub fn comment(&self, offset: usize) -> Option<String> {
+        let (file, line) = self.location(offset)?;
+        let mut files = self.files.borrow_mut();
+        let lines = files
+            .entry(file.clone())
+            .or_insert_with(|| {
+                fs::read_to_string(&file)
+                    .map(|t| t.lines().map(String::from).collect())
+                    .ok()
+            })
+            .as_ref()?;
+        let current = lines.get(line.checked_sub(1)?)?;
+        if let Some(c) = trailing(current) {
+            return Some(c);
+        }
+        let mut above = Vec::new();
+        let mut in_block = false;
+        for l in lines[..line - 1].iter().rev() {
+            let t = l.trim();
+            if in_block {
+                above.push(t);
+                in_block = !t.starts_with("/*");
+            } else if t.starts_with("//") {
+                above.push(t);
+            } else if t.ends_with("*/") {
+                above.push(t);
+                in_block = !t.starts_with("/*");
+            } else {
+                break;
+            }
+        }
+        above.reverse();
+        let text = clean(&above);
+        if text.is_empty() {
+            None
+        } else {
+            Some(text)
+        }
+    }
+}
+
+/// A line marker like `# 12 "foo.h" 1 3`, the flag 3 marks system headers
+fn marker(line: &str) -> Option<Marker> {
+    let rest = line.strip_prefix('#')?.trim_start();
+    let rest = rest.strip_prefix("line").unwrap_or(rest).trim_start();
+    let (number, rest) = rest.split_once(' ')?;
+    let line = number.parse().ok()?;
+    let (file, flags) = rest.trim_start().strip_prefix('"')?.split_once('"')?;
+    Some(Marker {
+        offset: 0,
+        file: PathBuf::from(file),
+        line,
+        system: flags.split_whitespace().any(|f| f == "3"),
+    })
+}
+
+/// The comment at the end of a line of code, e.g. `int port; // the TCP port`
+fn trailing(line: &str) -> Option<String> {
+    let start = match (line.find("//"), line.find("/*")) {
+        (Some(a), Some(b)) => a.min(b),
+        (a, b) => a.or(b)?,
+    };
+    if line[..start].trim().is_empty() {
+        // A comment on a line of its own belongs to the following lines
+        return None;
+    }
+    let text = clean(&[&line[start..]]);
+    if text.is_empty() {
+        None
+    } else {
+        Some(text)
+    }
+}
+
+/// The text of comment lines without the comment markers, joined by spaces
+fn clean(lines: &[&str]) -> String {
+    let mut words = Vec::new();
+    for l in lines {
+        let l = l.trim();
+        let l = l.strip_suffix("*/").unwrap_or(l);
+        let l = l.trim_start_matches('/').trim_start_matches('*');
+        let l = l.trim_start_matches('<').trim();
+        if !l.is_empty() {
+            words.push(l);
+        }
+    }
+    words.join(" ")
+}
@@ -0,0 +1,532 @@
+//! C types reconstructed from declaration specifiers and declarators
+
+use crate::cexpr;
+use lang_c::ast::*;
+use lang_c::span::Node;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+
+/// The keywords of the base types in the order they are rendered, e.g. `const unsigned long
+/// int` for `int long const unsigned`
+const ORDER: &[&str] = &[
+    "const",
+    "volatile",
+    "restrict",
+    "_Atomic",
+    "signed",
+    "unsigned",
+    "short",
+    "long",
+    "void",
+    "_Bool",
+    "char",
+    "int",
+    "float",
+    "double",
+    "_Complex",
+    "_Imaginary",
+];
+
+/// A C type as a base type plus the derived declarators applied to it
+///
+/// `char *names[4]` is the base `char` with a pointer and then an array applied,
+/// i.e. an array of four pointers to `char`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MyType {
+    /// Type qualifiers of the base type, e.g. `const`
+    pub qualifiers: Vec<String>,
+    /// Type specifiers of the base type, e.g. `unsigned int`, `struct a_t` or a typedef name
+    pub specifiers: Vec<String>,
+    /// Derived declarators in the order they apply to the base type
+    pub derived: Vec<Derived>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Derived {
+    /// Pointer with its qualifiers
+    Pointer(Vec<String>),
+    /// Array with its length, if one was given
+    ///
+    /// In function parameters also with `static` and the qualifiers given before the length,
+    /// like `static const 4` for `int a[static const 4]`, or `*` for a variable length array
+    /// of unspecified length. These aren't lengths that [`MyType::array_dims`] knows.
+    Array(Option<String>),
+    Function {
+        params: Vec<(Option<String>, MyType)>,
+        variadic: bool,
+    },
+}
+
+impl MyType {
+    pub fn from_declaration(specs: &[Node<DeclarationSpecifier>]) -> MyType {
+        let mut t = MyType::default();
+        for s in specs {
+            match &s.node {
+                DeclarationSpecifier::TypeSpecifier(ts) => t.specifiers.push(specifier(&ts.node)),
+                DeclarationSpecifier::TypeQualifier(q) => t.qualifiers.push(qualifier(&q.node)),
+                _ => (),
+            }
+        }
+        t
+    }
+
+    pub fn from_specifier_qualifiers(specs: &[Node<SpecifierQualifier>]) -> MyType {
+        let mut t = MyType::default();
+        for s in specs {
+            match &s.node {
+                SpecifierQualifier::TypeSpecifier(ts) => t.specifiers.push(specifier(&ts.node)),
+                SpecifierQualifier::TypeQualifier(q) => t.qualifiers.push(qualifier(&q.node)),
+                SpecifierQualifier::Extension(_) => (),
+            }
+        }
+        t
+    }
+
+    pub fn from_type_name(tn: &TypeName) -> MyType {
+        let t = MyType::from_specifier_qualifiers(&tn.specifiers);
+        match &tn.declarator {
+            Some(d) => t.with_declarator(&d.node),
+            None => t,
+        }
+    }
+
+    /// The lengths of the array dimensions this type starts with, outermost first
+    ///
+    /// A length is `None` if it isn't given or not a plain number.
+    pub fn array_dims(&self) -> Vec<Option<usize>> {
+        self.derived
+            .iter()
+            .rev()
+            .map_while(|d| match d {
+                Derived::Array(len) => Some(len.as_ref().and_then(|l| l.parse().ok())),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Apply the derived declarators of `d` (pointers, arrays, functions) to this type
+    pub fn with_declarator(self, d: &Declarator) -> MyType {
+        self.with_declarator_sized(d, &|_| None)
+    }
+
+    /// Apply the derived declarators of `d` like [`MyType::with_declarator`], with the
+    /// array lengths that `len` evaluates written as number, e.g. `sizeof(struct header) + 4`
+    pub fn with_declarator_sized(
+        mut self,
+        d: &Declarator,
+        len: &dyn Fn(&Expression) -> Option<u64>,
+    ) -> MyType {
+        // Within one declarator level the pointers bind tighter than the array and function
+        // suffixes, and the suffixes bind from right to left (`a[2][3]` is an array of two
+        // arrays of three elements).
+        for dd in &d.derived {
+            if let DerivedDeclarator::Pointer(qs) = &dd.node {
+                let qs = qs
+                    .iter()
+                    .filter_map(|q| match &q.node {
+                        PointerQualifier::TypeQualifier(q) => Some(qualifier(&q.node)),
+                        PointerQualifier::Extension(_) => None,
+                    })
+                    .collect();
+                self.derived.push(Derived::Pointer(qs));
+            }
+        }
+        for dd in d.derived.iter().rev() {
+            match &dd.node {
+                DerivedDeclarator::Pointer(_) => (),
+                DerivedDeclarator::Array(a) => {
+                    let mut words: Vec<String> = Vec::new();
+                    if let ArraySize::StaticExpression(_) = &a.node.size {
+                        words.push(String::from("static"));
+                    }
+                    words.extend(a.node.qualifiers.iter().map(|q| qualifier(&q.node)));
+                    match &a.node.size {
+                        ArraySize::Unknown => (),
+                        ArraySize::VariableUnknown => words.push(String::from("*")),
+                        ArraySize::VariableExpression(e) | ArraySize::StaticExpression(e) => words
+                            .push(
+                                len(&e.node)
+                                    .map_or_else(|| cexpr::render(&e.node), |l| l.to_string()),
+                            ),
+                    }
+                    self.derived.push(Derived::Array(match words.is_empty() {
+                        true => None,
+                        false => Some(words.join(" ")),
+                    }))
+                }
+                DerivedDeclarator::Function(f) => self.derived.push(Derived::Function {
+                    params: f
+                        .node
+                        .parameters
+                        .iter()
+                        .map(|p| {
+                            let t = MyType::from_declaration(&p.node.specifiers);
+                            match &p.node.declarator {
+                                Some(d) => (
+                                    crate::declarator_name(&d.node).map(String::from),
+                                    t.with_declarator(&d.node),
+                                ),
+                                None => (None, t),
+                            }
+                        })
+                        .collect(),
+                    variadic: f.node.ellipsis == Ellipsis::Some,
+                }),
+                DerivedDeclarator::KRFunction(ids) => self.derived.push(Derived::Function {
+                    params: ids
+                        .iter()
+                        .map(|id| (Some(id.node.name.clone()), MyType::default()))
+                        .collect(),
+                    variadic: false,
+                }),
+            }
+        }
+        if let DeclaratorKind::Declarator(inner) = &d.kind.node {
+            self = self.with_declarator_sized(&inner.node, len);
+        }
+        self
+    }
+
+    /// The referenced typedef name, if the base type is one
+    fn typedef_name(&self) -> Option<&str> {
+        match self.specifiers.as_slice() {
+            [name] if !name.contains(' ') => Some(name),
+            _ => None,
+        }
+    }
+
+    /// The struct or union name, if the base type is one
+    pub fn struct_name(&self) -> Option<&str> {
+        match self.specifiers.as_slice() {
+            [s] => s
+                .strip_prefix("struct ")
+                .or_else(|| s.strip_prefix("union ")),
+            _ => None,
+        }
+    }
+
+    /// Whether this is a pointer to a function, like the elements of a dispatch table
+    ///
+    /// Typedefs have to be resolved beforehand.
+    pub fn is_function_pointer(&self) -> bool {
+        matches!(
+            self.derived.as_slice(),
+            [.., Derived::Function { .. }, Derived::Pointer(_)]
+        )
+    }
+
+    /// Whether this is `_Bool`, also written `bool` with `<stdbool.h>`
+    ///
+    /// Typedefs have to be resolved beforehand.
+    pub fn is_bool(&self) -> bool {
+        self.derived.is_empty() && self.specifiers.iter().any(|s| s == "_Bool")
+    }
+
+    /// The smallest and largest value of an integer type, assuming the LP64 data model of
+    /// GCC on 64 bit Linux (plain `char` is signed)
+    ///
+    /// Typedefs have to be resolved beforehand. `None` for non-integer types.
+    pub fn int_range(&self) -> Option<(i128, i128)> {
+        if !self.derived.is_empty() {
+            return None;
+        }
+        let count = |w: &str| self.specifiers.iter().filter(|s| *s == w).count();
+        let unsigned = count("unsigned") > 0;
+        let bits = if count("_Bool") > 0 {
+            return Some((0, 1));
+        } else if count("char") > 0 {
+            8
+        } else if count("short") > 0 {
+            16
+        } else if count("long") > 0 {
+            64
+        } else if count("int") > 0
+            || count("signed") > 0
+            || unsigned
+            || self.specifiers.iter().any(|s| s.starts_with("enum "))
+        {
+            32
+        } else {
+            return None;
+        };
+        if unsigned {
+            Some((0, (1 << bits) - 1))
+        } else {
+            Some((-(1 << (bits - 1)), (1 << (bits - 1)) - 1))
+        }
+    }
+
+    /// Whether this is the same type as `other` regardless of how the specifiers are written,
+    /// e.g. `unsigned` and `int unsigned`, ignoring the qualifiers of the base type
+    pub fn same_as(&self, other: &MyType) -> bool {
+        self.derived == other.derived && self.canonical() == other.canonical()
+    }
+
+    fn canonical(&self) -> Vec<&str> {
+        let mut words: Vec<&str> = self.specifiers.iter().map(|s| s.as_str()).collect();
+        words.sort_unstable();
+        let integer = self.derived.is_empty() && self.int_range().is_some();
+        if integer && !words.iter().any(|w| *w == "char" || w.starts_with("enum ")) {
+            // `int` and `signed` are implied by the other integer specifiers
+            words.retain(|w| *w != "int" && *w != "signed");
+        }
+        words
+    }
+
+    /// Replace typedef names by the types they stand for, recursively
+    ///
+    /// Borrowed if there is no typedef name to replace, which is the usual case.
+    pub fn resolve(&self, typedefs: &HashMap<String, MyType>) -> Cow<'_, MyType> {
+        self.resolve_depth(typedefs, 0)
+    }
+
+    fn resolve_depth(&self, typedefs: &HashMap<String, MyType>, depth: usize) -> Cow<'_, MyType> {
+        let target = match self.typedef_name().and_then(|n| typedefs.get(n)) {
+            Some(t) if depth < 64 => t.resolve_depth(typedefs, depth + 1),
+            _ => return Cow::Borrowed(self),
+        };
+        let mut t = target.into_owned();
+        // Qualifiers of the use apply to the outermost type of the typedef
+        match t.derived.last_mut() {
+            Some(Derived::Pointer(qs)) => qs.extend(self.qualifiers.iter().cloned()),
+            _ => t.qualifiers.extend(self.qualifiers.iter().cloned()),
+        }
+        t.derived.extend(self.derived.iter().cloned());
+        Cow::Owned(t)
+    }
+
+    /// Render a declaration of `name` with this type, e.g. `int (*name)(void)`
+    pub fn declare(&self, name: &str) -> String {
+        let mut decl = String::from(name);
+        let mut after_pointer = false;
+        for d in self.derived.iter().rev() {
+            match d {
+                Derived::Pointer(qs) => {
+                    let qs: String = ordered(qs).iter().map(|q| format!("{} ", q)).collect();
+                    decl = format!("*{}{}", qs, decl);
+                    after_pointer = true;
+                }
+                Derived::Array(len) => {
+                    if after_pointer {
+                        decl = format!("({})", decl);
+                    }
+                    decl = format!("{}[{}]", decl, len.as_deref().unwrap_or(""));
+                    after_pointer = false;
+                }
+                Derived::Function { params, variadic } => {
+                    if after_pointer {
+                        decl = format!("({})", decl);
+                    }
+                    let mut ps: Vec<String> = params
+                        .iter()
+                        .map(|(n, t)| t.declare(n.as_deref().unwrap_or("")))
+                        .collect();
+                    if *variadic {
+                        ps.push(String::from("..."));
+                    }
+                    decl = format!("{}({})", decl, ps.join(", "));
+                    after_pointer = false;
+                }
+            }
+        }
+        let mut base = ordered(&self.qualifiers);
+        base.extend(ordered(&self.specifiers));
+        let base = base.join(" ");
+        let decl = decl.trim_end();
+        if decl.is_empty() {
+            base
+        } else {
+            format!("{} {}", base, decl)
+        }
+    }
+}
+
+/// `words` in the order of [`ORDER`], other words like typedef names after the keywords
+fn ordered<S: AsRef<str>>(words: &[S]) -> Vec<&str> {
+    let mut words: Vec<&str> = words.iter().map(|w| w.as_ref()).collect();
+    words.sort_by_key(|w| ORDER.iter().position(|k| k == w).unwrap_or(ORDER.len()));
+    words
+}
+
+/// The type written as `c_type` like it is rendered, e.g. `const int *` for `int const*`
+///
+/// The keywords of the base type are reordered and the whitespace is normalized, so the same
+/// types are written the same. Only the base type up to the first `*`, `(` or `[` is
+/// reordered, the rest is kept as it is written apart from the spaces.
+pub fn normalize(c_type: &str) -> String {
+    let mut tokens = Vec::new();
+    for word in c_type.split_whitespace() {
+        let mut start = 0;
+        for (i, c) in word.char_indices() {
+            if "*()[],".contains(c) {
+                if start < i {
+                    tokens.push(&word[start..i]);
+                }
+                tokens.push(&word[i..i + 1]);
+                start = i + 1;
+            }
+        }
+        if start < word.len() {
+            tokens.push(&word[start..]);
+        }
+    }
+    let split = tokens
+        .iter()
+        .position(|t| "*()[],".contains(*t))
+        .unwrap_or(tokens.len());
+    let mut out = ordered(&tokens[..split]).join(" ");
+    for t in &tokens[split..] {
+        let space = !matches!(
+            (out.chars().last(), *t),
+            (None, _)
+                | (Some('('), _)
+                | (Some('['), _)
+                | (Some('*'), _)
+                | (Some(')'), "(")
+                | (_, ")")
+                | (_, "]")
+                | (_, ",")
+                | (_, "[")
+        );
+        if space {
+            out.push(' ');
+        }
+        out.push_str(t);
+    }
+    out
+}
+
+impl fmt::Display for MyType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.declare(""))
+    }
+}
+
+/// The type of `expr` if it follows from the expression alone, e.g. for literals and casts
+pub fn type_of(expr: &Expression) -> Option<MyType> {
+    let words: &[&str] = match expr {
+        Expression::Constant(c) => match &c.node {
+            Constant::Integer(i) if i.suffix.imaginary => return None,
+            Constant::Integer(i) => match (i.suffix.size, i.suffix.unsigned) {
+                (IntegerSize::Int, false) => &["int"],
+                (IntegerSize::Int, true) => &["unsigned"],
+                (IntegerSize::Long, false) => &["long"],
+                (IntegerSize::Long, true) => &["unsigned", "long"],
+                (IntegerSize::LongLong, false) => &["long", "long"],
+                (IntegerSize::LongLong, true) => &["unsigned", "long", "long"],
+            },
+            Constant::Float(f) if f.suffix.imaginary => return None,
+            Constant::Float(f) => match f.suffix.format {
+                FloatFormat::Float => &["float"],
+                FloatFormat::Double => &["double"],
+                FloatFormat::LongDouble => &["long", "double"],
+                FloatFormat::TS18661Format(_) => return None,
+            },
+            // Character constants are of type `int` in C
+            Constant::Character(_) => &["int"],
+        },
+        Expression::Cast(c) => return Some(MyType::from_type_name(&c.node.type_name.node)),
+        _ => return None,
+    };
+    Some(MyType {
+        specifiers: words.iter().map(|w| String::from(*w)).collect(),
+        ..MyType::default()
+    })
+}
+
+pub fn specifier(ts: &TypeSpecifier) -> String {
+    let s = match ts {
+        TypeSpecifier::Void => "void",
+        TypeSpecifier::Char => "char",
+        TypeSpecifier::Short => "short",
+        TypeSpecifier::Int => "int",
+        TypeSpecifier::Long => "long",
+        TypeSpecifier::Float => "float",
+        TypeSpecifier::Double => "double",
+        TypeSpecifier::Signed => "signed",
+        TypeSpecifier::Unsigned => "unsigned",
+        TypeSpecifier::Bool => "_Bool",
+        TypeSpecifier::Complex => "_Complex",
+        TypeSpecifier::Atomic(tn) => {
+            return format!("_Atomic({})", MyType::from_type_name(&tn.node))
+        }
+        TypeSpecifier::Struct(st) => {
+            let kind = match st.node.kind.node {
+                StructKind::Struct => "struct",
+                StructKind::Union => "union",
+            };
+            return match &st.node.identifier {
+                Some(id) => format!("{} {}", kind, id.node.name),
+                None => format!("{} <anonymous>", kind),
+            };
+        }
+        TypeSpecifier::Enum(et) => {
+            return match &et.node.identifier {
+                Some(id) => format!("enum {}", id.node.name),
+                None => String::from("enum <anonymous>"),
+            }
+        }
+        TypeSpecifier::TypedefName(id) => return id.node.name.clone(),
+        TypeSpecifier::TypeOf(t) => {
+            return match &t.node {
+                TypeOf::Expression(e) => format!("typeof({})", cexpr::render(&e.node)),
+                TypeOf::Type(tn) => format!("typeof({})", MyType::from_type_name(&tn.node)),
+            }
+        }
+        TypeSpecifier::TS18661Float(f) => {
+            let x = match f.format {
+                TS18661FloatFormat::BinaryExtended | TS18661FloatFormat::DecimalExtended => "x",
+                _ => "",
+            };
+            let kind = match f.format {
+                TS18661FloatFormat::BinaryInterchange | TS18661FloatFormat::BinaryExtended => {
+                    "_Float"
+                }
+                _ => "_Decimal",
+            };
+            return format!("{}{}{}", kind, f.width, x);
+        }
+    };
+    String::from(s)
+}
+
+/// The storage class and function specifiers like `static` and `inline`, as written
+///
+/// `typedef` isn't included, typedefs are no declarations of objects or functions.
+pub fn storage_specifiers(specs: &[Node<DeclarationSpecifier>]) -> Vec<String> {
+    specs
+        .iter()
+        .filter_map(|s| {
+            let s = match &s.node {
+                DeclarationSpecifier::StorageClass(sc) => match sc.node {
+                    StorageClassSpecifier::Typedef => return None,
+                    StorageClassSpecifier::Extern => "extern",
+                    StorageClassSpecifier::Static => "static",
+                    StorageClassSpecifier::ThreadLocal => "_Thread_local",
+                    StorageClassSpecifier::Auto => "auto",
+                    StorageClassSpecifier::Register => "register",
+                },
+                DeclarationSpecifier::Function(fs) => match fs.node {
+                    FunctionSpecifier::Inline => "inline",
+                    FunctionSpecifier::Noreturn => "_Noreturn",
+                },
+                _ => return None,
+            };
+            Some(String::from(s))
+        })
+        .collect()
+}
+
+pub fn qualifier(q: &TypeQualifier) -> String {
+    let s = match q {
+        TypeQualifier::Const => "const",
+        TypeQualifier::Restrict => "restrict",
+        TypeQualifier::Volatile => "volatile",
+        TypeQualifier::Nonnull => "_Nonnull",
+        TypeQualifier::NullUnspecified => "_Null_unspecified",
+        TypeQualifier::Nullable => "_Nullable",
+        TypeQualifier::Atomic => "_Atomic",
+    };
+    String::from(s)
+}
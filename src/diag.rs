@@ -0,0 +1,199 @@
+//! Count the warnings by category while logging them
+//!
+//! The warnings are located at the declaration being visited, see [`locate`], and can be
+//! printed as annotations for CI instead, see [`Annotations`].
+
+use crate::Location;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use simple_logger::SimpleLogger;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+/// The categories of the warnings, their log targets are these prefixed with `c_ast::`
+pub const CATEGORIES: &[&str] = &[
+    "conflict",
+    "cycle",
+    "designator",
+    "gitignore",
+    "no_struct",
+    "octal",
+    "range",
+    "redefinition",
+    "shadowing",
+    "shift",
+    "skipped",
+    "strip_prefix",
+    "type_map",
+    "unknown_struct",
+    "unnamed",
+    "unresolved",
+    "unused",
+];
+
+static WARNINGS: Mutex<BTreeMap<String, usize>> = Mutex::new(BTreeMap::new());
+
+thread_local! {
+    static LOCATION: RefCell<Option<Location>> = const { RefCell::new(None) };
+}
+
+/// How to print the warnings for a CI service instead of logging them
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Annotations {
+    /// Workflow commands of GitHub Actions like `::warning file=a.c,line=3::message`, shown
+    /// inline in pull requests
+    Github,
+}
+
+/// Wraps the actual logger, warnings are counted even if they aren't printed
+struct Counting {
+    inner: SimpleLogger,
+    annotations: Option<Annotations>,
+}
+
+impl Log for Counting {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= Level::Warn || self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if record.level() != Level::Warn {
+            return self.inner.log(record);
+        }
+        *WARNINGS
+            .lock()
+            .unwrap()
+            .entry(String::from(record.target()))
+            .or_default() += 1;
+        let at = LOCATION.with(|l| l.borrow().clone());
+        match (self.annotations, at) {
+            (Some(Annotations::Github), at) => {
+                let message = record.args().to_string();
+                println!("{}", github(record.target(), &message, at.as_ref()))
+            }
+            (None, Some(at)) => self.inner.log(
+                &Record::builder()
+                    .args(format_args!("{}: {}", at, record.args()))
+                    .level(record.level())
+                    .target(record.target())
+                    .module_path(record.module_path())
+                    .file(record.file())
+                    .line(record.line())
+                    .build(),
+            ),
+            (None, None) => self.inner.log(record),
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush()
+    }
+}
+
+/// Install the logger printing messages up to `level`, the warnings as `annotations` if given
+pub fn init(
+    level: LevelFilter,
+    annotations: Option<Annotations>,
+) -> Result<(), log::SetLoggerError> {
+    log::set_boxed_logger(Box::new(Counting {
+        inner: SimpleLogger::new().with_level(level),
+        annotations,
+    }))?;
+    log::set_max_level(level.max(LevelFilter::Warn));
+    Ok(())
+}
+
+/// The number of warnings so far, by category (the log target)
+pub fn warnings() -> BTreeMap<String, usize> {
+    WARNINGS.lock().unwrap().clone()
+}
+
+/// Forget the warnings so far, e.g. before extracting again
+pub fn reset() {
+    WARNINGS.lock().unwrap().clear();
+}
+
+/// Locate the following warnings at `at` until the returned guard is dropped
+pub fn locate(at: Option<Location>) -> Located {
+    Located {
+        outer: LOCATION.with(|l| l.replace(at)),
+    }
+}
+
+/// Restores the location before [`locate`] when dropped
+#[must_use]
+pub struct Located {
+    outer: Option<Location>,
+}
+
+impl Drop for Located {
+    fn drop(&mut self) {
+        LOCATION.with(|l| *l.borrow_mut() = self.outer.take());
+    }
+}
+
+/// The warning of `category` (the log target) as GitHub Actions workflow command
+///
+/// The category is the title of the annotation. The message may have several lines, these and
+/// the characters separating the properties are escaped as GitHub expects.
+pub fn github(category: &str, message: &str, at: Option<&Location>) -> String {
+    let title = category.strip_prefix("c_ast::").unwrap_or(category);
+    let mut properties = Vec::new();
+    if let Some(at) = at {
+        properties.push(format!("file={}", property(&at.file.display().to_string())));
+        properties.push(format!("line={}", at.line));
+    }
+    properties.push(format!("title={}", property(title)));
+    format!("::warning {}::{}", properties.join(","), data(message))
+}
+
+fn data(s: &str) -> String {
+    s.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+fn property(s: &str) -> String {
+    data(s).replace(':', "%3A").replace(',', "%2C")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn github_annotations() {
+        let at = Location {
+            file: PathBuf::from("src/a,b.c"),
+            line: 7,
+        };
+        assert_eq!(
+            github("c_ast::range", "100% of\nthe range", Some(&at)),
+            "::warning file=src/a%2Cb.c,line=7,title=range::100%25 of%0Athe range"
+        );
+        assert_eq!(github("other", "a: b", None), "::warning title=other::a: b");
+    }
+
+    /// Every warning of the crate is logged with the target of one of the categories
+    #[test]
+    fn warnings_have_categories() {
+        let src = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+        for entry in std::fs::read_dir(src).unwrap() {
+            let path = entry.unwrap().path();
+            let text = std::fs::read_to_string(&path).unwrap();
+            for call in text.split(concat!("log::", "warn!(")).skip(1) {
+                let call = call.trim_start();
+                let category = call
+                    .strip_prefix("target: \"c_ast::")
+                    .and_then(|t| t.split('"').next());
+                assert!(
+                    category.is_some_and(|c| CATEGORIES.contains(&c)),
+                    "{}: {}",
+                    path.display(),
+                    call.lines().take(2).collect::<Vec<_>>().join("\n")
+                );
+            }
+        }
+    }
+}
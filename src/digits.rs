@@ -0,0 +1,117 @@
+//! Integers with C23 digit separators like `1'000'000`
+//!
+//! The parser would take the separators for the start of character constants, so they are
+//! removed before parsing and the numbers padded with spaces, see [`strip`]. The offsets of
+//! the text stay the same, so the numbers as they are written can be looked up by the offsets
+//! of the constants, see [`separated`].
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+/// Remove the digit separators of the numbers in the preprocessed `text`
+pub fn strip(text: &str) -> Cow<'_, str> {
+    let found = numbers(text);
+    if found.is_empty() {
+        return Cow::Borrowed(text);
+    }
+    let mut out = String::from(text);
+    for (start, number) in found {
+        let digits = number.replace('\'', "");
+        let padded = format!("{:width$}", digits, width = number.len());
+        out.replace_range(start..start + number.len(), &padded);
+    }
+    Cow::Owned(out)
+}
+
+/// The numbers with digit separators of `text` as they are written, by their offset
+pub fn separated(text: &str) -> HashMap<usize, String> {
+    numbers(text)
+        .into_iter()
+        .map(|(start, number)| (start, String::from(number)))
+        .collect()
+}
+
+/// The numbers with digit separators outside of string and character literals
+fn numbers(text: &str) -> Vec<(usize, &str)> {
+    let mut found = Vec::new();
+    if !text.contains('\'') {
+        return found;
+    }
+    let bytes = text.as_bytes();
+    let mut quote = None;
+    let mut i = 0;
+    while i < bytes.len() {
+        let c = bytes[i];
+        match quote {
+            Some(_) if c == b'\\' => i += 1,
+            Some(q) if c == q || c == b'\n' => quote = None,
+            Some(_) => (),
+            None if c == b'"' || c == b'\'' => quote = Some(c),
+            None if c.is_ascii_digit() => {
+                let len = number_len(&bytes[i..]);
+                let number = &text[i..i + len];
+                if number.contains('\'') {
+                    found.push((i, number));
+                }
+                i += len;
+                continue;
+            }
+            // Words like `x1` aren't numbers
+            None if is_word(c) => {
+                i += bytes[i..].iter().take_while(|c| is_word(**c)).count();
+                continue;
+            }
+            None => (),
+        }
+        i += 1;
+    }
+    found
+}
+
+/// The length of the preprocessing number at the start of `bytes`, with the signs of the
+/// exponents and the separators between digits
+fn number_len(bytes: &[u8]) -> usize {
+    let mut len = 1;
+    while let Some(&c) = bytes.get(len) {
+        let next = bytes.get(len + 1).copied().unwrap_or_default();
+        let exponent = matches!(bytes[len - 1], b'e' | b'E' | b'p' | b'P');
+        if is_word(c) || c == b'.' || ((c == b'+' || c == b'-') && exponent) {
+            len += 1;
+        } else if c == b'\'' && is_word(next) {
+            len += 2;
+        } else {
+            break;
+        }
+    }
+    len
+}
+
+fn is_word(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || c == b'_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offsets_kept() {
+        let text = "int big = 1'000'000; char c = 'a'; int h = 0xff'ff;";
+        let stripped = strip(text);
+        assert_eq!(stripped.len(), text.len());
+        assert_eq!(
+            stripped,
+            "int big = 1000000  ; char c = 'a'; int h = 0xffff ;"
+        );
+        let found = separated(text);
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[&10], "1'000'000");
+        assert_eq!(found[&43], "0xff'ff");
+    }
+
+    #[test]
+    fn literals_untouched() {
+        let text = "char *s = \"1'2\"; int c = '1';";
+        assert!(matches!(strip(text), Cow::Borrowed(_)));
+    }
+}
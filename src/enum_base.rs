@@ -0,0 +1,135 @@
+//! Enums with a fixed underlying type like `enum color : unsigned char { RED, GREEN };`
+//!
+//! The parser doesn't know the C23 syntax, so the underlying types are replaced by spaces
+//! before parsing, see [`blank`]. The offsets of the text stay the same, so the underlying
+//! types can be looked up in the text by the offsets of the enum specifiers, see [`bases`].
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+/// Replace the `: type` of the enum specifiers of the preprocessed `text` by spaces
+pub fn blank(text: &str) -> Cow<'_, str> {
+    let found = specifiers(text);
+    if found.is_empty() {
+        return Cow::Borrowed(text);
+    }
+    let mut out = String::from(text);
+    for (_, colon, end, _) in found {
+        let spaces: String = text[colon..end]
+            .chars()
+            .map(|c| if c == '\n' { c } else { ' ' })
+            .collect();
+        out.replace_range(colon..end, &spaces);
+    }
+    Cow::Owned(out)
+}
+
+/// The underlying types of the enum specifiers of `text`, by the offset of their `enum`
+pub fn bases(text: &str) -> HashMap<usize, String> {
+    specifiers(text)
+        .into_iter()
+        .map(|(start, _, _, base)| (start, base))
+        .collect()
+}
+
+/// The enum specifiers with an underlying type outside of string and character literals, as
+/// the offsets of `enum`, of the colon and after the type, and the type
+fn specifiers(text: &str) -> Vec<(usize, usize, usize, String)> {
+    let mut found = Vec::new();
+    if !text.contains(':') {
+        return found;
+    }
+    let bytes = text.as_bytes();
+    let mut quote = None;
+    let mut i = 0;
+    while i < bytes.len() {
+        let c = bytes[i];
+        match quote {
+            Some(_) if c == b'\\' => i += 1,
+            Some(q) if c == q || c == b'\n' => quote = None,
+            Some(_) => (),
+            None if c == b'"' || c == b'\'' => quote = Some(c),
+            None if is_word(c) => {
+                let start = i;
+                let word = word_at(bytes, i);
+                i += word.len();
+                if word == b"enum" {
+                    if let Some((colon, end, base)) = base(text, i) {
+                        found.push((start, colon, end, base));
+                        i = end;
+                    }
+                }
+                continue;
+            }
+            None => (),
+        }
+        i += 1;
+    }
+    found
+}
+
+/// The underlying type of the enum specifier continuing at `at` after `enum`, if any
+///
+/// That is an optional tag, a colon and the words of the type up to the `{` or `;`. An enum
+/// bit-field like `enum e : 3;` has a width instead of a type and isn't taken.
+fn base(text: &str, at: usize) -> Option<(usize, usize, String)> {
+    let bytes = text.as_bytes();
+    let skip = |mut i: usize| {
+        while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        i
+    };
+    let mut i = skip(at);
+    if i < bytes.len() && is_word(bytes[i]) {
+        i = skip(i + word_at(bytes, i).len());
+    }
+    if bytes.get(i) != Some(&b':') || bytes.get(i + 1) == Some(&b':') {
+        return None;
+    }
+    let colon = i;
+    let mut words = Vec::new();
+    i = skip(i + 1);
+    while i < bytes.len() && is_word(bytes[i]) && !bytes[i].is_ascii_digit() {
+        let word = word_at(bytes, i);
+        words.push(&text[i..i + word.len()]);
+        i = skip(i + word.len());
+    }
+    match bytes.get(i) {
+        Some(b'{') | Some(b';') if !words.is_empty() => Some((colon, i, words.join(" "))),
+        _ => None,
+    }
+}
+
+fn is_word(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || c == b'_'
+}
+
+fn word_at(bytes: &[u8], at: usize) -> &[u8] {
+    let len = bytes[at..].iter().take_while(|c| is_word(**c)).count();
+    &bytes[at..at + len]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blanked() {
+        let text = "enum color : unsigned char { RED };\nstruct s { int bits : 3; };";
+        let blanked = blank(text);
+        assert_eq!(
+            blanked,
+            "enum color                 { RED };\nstruct s { int bits : 3; };"
+        );
+        assert_eq!(bases(text)[&0], "unsigned char");
+        assert_eq!(bases(text).len(), 1);
+    }
+
+    #[test]
+    fn declarations_without_body() {
+        let text = "enum big : long long;";
+        assert_eq!(bases(text)[&0], "long long");
+        assert_eq!(blank("enum e x = A ? 1 : 2;"), "enum e x = A ? 1 : 2;");
+    }
+}
@@ -0,0 +1,66 @@
+//! The errors of the library functions
+
+use lang_c::driver::SyntaxError;
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+#[derive(Debug)]
+pub enum CAstError {
+    /// A file couldn't be read
+    Io { path: PathBuf, source: io::Error },
+    /// The preprocessor failed, e.g. because of a missing include
+    Preprocess { path: PathBuf, source: io::Error },
+    /// The (preprocessed) file isn't valid C
+    Parse { path: PathBuf, source: SyntaxError },
+    /// An invalid glob pattern
+    Pattern(glob::PatternError),
+    /// A directory couldn't be walked
+    Walk(walkdir::Error),
+    /// An invalid line in an expectation file, see [`crate::verify::parse_spec`]
+    Spec { line: usize, message: String },
+    /// An invalid command of the repl, see [`crate::repl::Session::dispatch`]
+    Query(String),
+}
+
+impl fmt::Display for CAstError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CAstError::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+            CAstError::Preprocess { path, source } => {
+                write!(f, "{}: preprocessor error: {}", path.display(), source)
+            }
+            CAstError::Parse { path, source } => {
+                write!(f, "{}: syntax error: {}", path.display(), source)
+            }
+            CAstError::Pattern(e) => write!(f, "invalid pattern: {}", e),
+            CAstError::Walk(e) => write!(f, "{}", e),
+            CAstError::Spec { line, message } => write!(f, "line {}: {}", line, message),
+            CAstError::Query(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl Error for CAstError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CAstError::Io { source, .. } | CAstError::Preprocess { source, .. } => Some(source),
+            CAstError::Pattern(e) => Some(e),
+            CAstError::Walk(e) => Some(e),
+            CAstError::Parse { .. } | CAstError::Spec { .. } | CAstError::Query(_) => None,
+        }
+    }
+}
+
+impl From<glob::PatternError> for CAstError {
+    fn from(e: glob::PatternError) -> CAstError {
+        CAstError::Pattern(e)
+    }
+}
+
+impl From<walkdir::Error> for CAstError {
+    fn from(e: walkdir::Error) -> CAstError {
+        CAstError::Walk(e)
+    }
+}
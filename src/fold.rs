@@ -0,0 +1,539 @@
+//! Evaluation of integer constant expressions
+
+use crate::ctype::MyType;
+use crate::MyExpression;
+use lang_c::ast::*;
+
+/// Looks up the value of an identifier, e.g. of a `const` variable declared before, or of a
+/// member access written like `table[1].x`
+pub type Env<'e> = dyn Fn(&str) -> Option<MyExpression> + 'e;
+
+/// An environment without any known identifiers
+pub fn no_env(_: &str) -> Option<MyExpression> {
+    None
+}
+
+/// Looks up the size and the alignment of a type in bytes, e.g. [`layout::size_align`] with
+/// the types extracted so far
+///
+/// [`layout::size_align`]: crate::layout::size_align
+pub type Layouts<'l> = dyn Fn(&MyType) -> Option<(u64, u64)> + 'l;
+
+/// No type has a known layout
+pub fn no_layouts(_: &MyType) -> Option<(u64, u64)> {
+    None
+}
+
+/// Evaluate `expr` if it is an integer constant expression
+///
+/// Identifiers and member accesses like `cfg.base.value` are looked up in `env`. Returns `None` if the expression isn't constant,
+/// refers to unknown identifiers, or would overflow or divide by zero.
+pub fn eval(expr: &Expression, env: &Env) -> Option<i128> {
+    eval_sized(expr, env, &no_layouts)
+}
+
+/// Evaluate `expr` like [`eval`], with `sizeof` and `_Alignof` of the types whose layout is
+/// in `layouts`
+///
+/// The type of an expression is only known for constants and casts, like for `_Generic`.
+pub fn eval_sized(expr: &Expression, env: &Env, layouts: &Layouts) -> Option<i128> {
+    typed(expr, env, layouts).map(|i| i.value)
+}
+
+/// An integer type by its width and signedness, assuming the LP64 data model like
+/// [`MyType::int_range`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct IntType {
+    bits: u32,
+    signed: bool,
+}
+
+const INT: IntType = IntType {
+    bits: 32,
+    signed: true,
+};
+const UNSIGNED: IntType = IntType {
+    bits: 32,
+    signed: false,
+};
+const LONG: IntType = IntType {
+    bits: 64,
+    signed: true,
+};
+const UNSIGNED_LONG: IntType = IntType {
+    bits: 64,
+    signed: false,
+};
+
+impl IntType {
+    /// The integer type `typ`, `None` for other types and typedef names
+    fn of(typ: &MyType) -> Option<IntType> {
+        let (min, max) = typ.int_range()?;
+        let bits = 128 - max.leading_zeros() + u32::from(min < 0);
+        Some(IntType {
+            bits,
+            signed: min < 0,
+        })
+    }
+
+    /// The type of `value` if it isn't known, the first of `int`, `unsigned int`, `long` and
+    /// `unsigned long` it fits into, like for a hexadecimal constant
+    fn inferred(value: i128) -> Option<IntType> {
+        [INT, UNSIGNED, LONG, UNSIGNED_LONG]
+            .iter()
+            .copied()
+            .find(|t| t.fits(value))
+    }
+
+    fn fits(self, value: i128) -> bool {
+        if self.signed {
+            let max = (1i128 << (self.bits - 1)) - 1;
+            (-max - 1..=max).contains(&value)
+        } else {
+            (0..1i128 << self.bits).contains(&value)
+        }
+    }
+
+    /// `value` converted to this type, modulo 2^bits like GCC does for signed types too
+    fn wrap(self, value: i128) -> i128 {
+        let low = value & ((1i128 << self.bits) - 1);
+        if self.signed && low >> (self.bits - 1) != 0 {
+            low - (1i128 << self.bits)
+        } else {
+            low
+        }
+    }
+
+    /// The type after the integer promotions, `int` for the types smaller than an `int`
+    fn promoted(self) -> IntType {
+        if self.bits < 32 {
+            INT
+        } else {
+            self
+        }
+    }
+
+    /// The common type of the operands of a binary operator after the usual arithmetic
+    /// conversions
+    fn common(self, other: IntType) -> IntType {
+        let (a, b) = (self.promoted(), other.promoted());
+        if a.signed == b.signed {
+            return if a.bits >= b.bits { a } else { b };
+        }
+        let (signed, unsigned) = if a.signed { (a, b) } else { (b, a) };
+        if unsigned.bits >= signed.bits {
+            unsigned
+        } else {
+            signed
+        }
+    }
+}
+
+/// An integer value of a type
+#[derive(Debug, Clone, Copy)]
+struct Int {
+    value: i128,
+    typ: IntType,
+}
+
+impl Int {
+    /// `value` of the type `typ`, wrapped around for unsigned types and `None` if a signed
+    /// type can't represent it
+    fn checked(value: i128, typ: IntType) -> Option<Int> {
+        if !typ.signed {
+            Some(Int {
+                value: typ.wrap(value),
+                typ,
+            })
+        } else if typ.fits(value) {
+            Some(Int { value, typ })
+        } else {
+            None
+        }
+    }
+
+    /// A value of an unknown type, see [`IntType::inferred`]
+    fn inferred(value: i128) -> Option<Int> {
+        Some(Int {
+            value,
+            typ: IntType::inferred(value)?,
+        })
+    }
+
+    fn converted(self, typ: IntType) -> Int {
+        Int {
+            value: typ.wrap(self.value),
+            typ,
+        }
+    }
+}
+
+/// `value` as an `int`, the type of comparisons and character constants
+fn int(value: i128) -> Int {
+    Int { value, typ: INT }
+}
+
+fn typed(expr: &Expression, env: &Env, layouts: &Layouts) -> Option<Int> {
+    let eval = |e: &Expression| typed(e, env, layouts);
+    match expr {
+        Expression::Identifier(id) => match env(&id.node.name)? {
+            MyExpression::Integer { value, .. } => Int::inferred(value),
+            _ => None,
+        },
+        // Only the members and elements of `const` structs and arrays, the environment tells
+        Expression::Member(_) => match env(&path(expr, env)?)? {
+            MyExpression::Integer { value, .. } => Int::inferred(value),
+            _ => None,
+        },
+        Expression::BinaryOperator(b) if b.node.operator.node == BinaryOperator::Index => {
+            match env(&path(expr, env)?)? {
+                MyExpression::Integer { value, .. } => Int::inferred(value),
+                _ => None,
+            }
+        }
+        Expression::Constant(c) => match &c.node {
+            Constant::Integer(i) => constant(i),
+            Constant::Character(c) => character(c).map(int),
+            Constant::Float(_) => None,
+        },
+        Expression::SizeOf(t) => size_of(&MyType::from_type_name(&t.node), layouts),
+        Expression::AlignOf(t) => {
+            let (_, align) = layouts(&MyType::from_type_name(&t.node))?;
+            Int::checked(i128::from(align), UNSIGNED_LONG)
+        }
+        Expression::UnaryOperator(u) if u.node.operator.node == UnaryOperator::SizeOf => {
+            size_of(&crate::ctype::type_of(&u.node.operand.node)?, layouts)
+        }
+        Expression::UnaryOperator(u) => {
+            let v = eval(&u.node.operand.node)?;
+            let typ = v.typ.promoted();
+            match u.node.operator.node {
+                UnaryOperator::Plus => Some(v.converted(typ)),
+                UnaryOperator::Minus => Int::checked(v.value.checked_neg()?, typ),
+                UnaryOperator::Negate => Some(int((v.value == 0) as i128)),
+                UnaryOperator::Complement => Int::checked(!v.value, typ),
+                _ => None,
+            }
+        }
+        Expression::BinaryOperator(b) => {
+            let lhs = eval(&b.node.lhs.node)?;
+            // The right-hand side of `&&` and `||` is only evaluated if needed
+            match b.node.operator.node {
+                BinaryOperator::LogicalAnd if lhs.value == 0 => return Some(int(0)),
+                BinaryOperator::LogicalOr if lhs.value != 0 => return Some(int(1)),
+                _ => (),
+            }
+            let rhs = eval(&b.node.rhs.node)?;
+            binary(b.node.operator.node.clone(), lhs, rhs)
+        }
+        Expression::Conditional(c) => {
+            let (taken, other) = if eval(&c.node.condition.node)?.value != 0 {
+                (&c.node.then_expression, &c.node.else_expression)
+            } else {
+                (&c.node.else_expression, &c.node.then_expression)
+            };
+            let v = eval(&taken.node)?;
+            // The result has the common type of both branches, if the other one is constant
+            match eval(&other.node) {
+                Some(o) => Some(v.converted(v.typ.common(o.typ))),
+                None => Some(v),
+            }
+        }
+        Expression::Cast(c) => {
+            let v = eval(&c.node.expression.node)?;
+            let typ = MyType::from_type_name(&c.node.type_name.node);
+            if typ.is_bool() {
+                return Some(Int {
+                    value: (v.value != 0) as i128,
+                    typ: IntType::of(&typ)?,
+                });
+            }
+            // Typedef names aren't known here, the value stays as it is
+            match IntType::of(&typ) {
+                Some(t) => Some(v.converted(t)),
+                None => Some(v),
+            }
+        }
+        Expression::GenericSelection(g) => eval(select(&g.node)?),
+        Expression::Call(c) => builtin(&c.node, env, layouts),
+        _ => None,
+    }
+}
+
+/// The access of `expr` like `table[1].x` with the indices evaluated, to look it up in the
+/// environment, e.g. of `table[i].x` with `const int i = 1;`
+///
+/// `None` if `expr` isn't an identifier with member accesses and indices, or an index isn't
+/// constant.
+pub fn path(expr: &Expression, env: &Env) -> Option<String> {
+    match expr {
+        Expression::Identifier(id) => Some(id.node.name.clone()),
+        Expression::Member(m) if m.node.operator.node == MemberOperator::Direct => Some(format!(
+            "{}.{}",
+            path(&m.node.expression.node, env)?,
+            m.node.identifier.node.name
+        )),
+        Expression::BinaryOperator(b) if b.node.operator.node == BinaryOperator::Index => {
+            let index = eval(&b.node.rhs.node, env).filter(|i| *i >= 0)?;
+            Some(format!("{}[{}]", path(&b.node.lhs.node, env)?, index))
+        }
+        _ => None,
+    }
+}
+
+/// The value of the binary operator `op` except `&&` and `||` short-circuiting, of the
+/// operands converted to their common type
+fn binary(op: BinaryOperator, lhs: Int, rhs: Int) -> Option<Int> {
+    if let BinaryOperator::ShiftLeft | BinaryOperator::ShiftRight = op {
+        // The result has the type of the promoted left operand
+        let lhs = lhs.converted(lhs.typ.promoted());
+        let n = shift_amount(lhs, rhs.value)?;
+        // Bits shifted out of a signed type wrap around as in GCC
+        let value = match op {
+            BinaryOperator::ShiftLeft => lhs.typ.wrap(lhs.value << n),
+            _ => lhs.value >> n,
+        };
+        return Some(Int {
+            value,
+            typ: lhs.typ,
+        });
+    }
+    let typ = lhs.typ.common(rhs.typ);
+    let (l, r) = (lhs.converted(typ).value, rhs.converted(typ).value);
+    let truth = |b: bool| Some(int(b as i128));
+    match op {
+        BinaryOperator::Multiply if typ.signed => Int::checked(l.checked_mul(r)?, typ),
+        BinaryOperator::Multiply => Int::checked(l.wrapping_mul(r), typ),
+        BinaryOperator::Divide => Int::checked(l.checked_div(r)?, typ),
+        BinaryOperator::Modulo => Int::checked(l.checked_rem(r)?, typ),
+        BinaryOperator::Plus => Int::checked(l + r, typ),
+        BinaryOperator::Minus => Int::checked(l - r, typ),
+        BinaryOperator::Less => truth(l < r),
+        BinaryOperator::Greater => truth(l > r),
+        BinaryOperator::LessOrEqual => truth(l <= r),
+        BinaryOperator::GreaterOrEqual => truth(l >= r),
+        BinaryOperator::Equals => truth(l == r),
+        BinaryOperator::NotEquals => truth(l != r),
+        BinaryOperator::LogicalAnd | BinaryOperator::LogicalOr => truth(r != 0),
+        BinaryOperator::BitwiseAnd => Int::checked(l & r, typ),
+        BinaryOperator::BitwiseOr => Int::checked(l | r, typ),
+        BinaryOperator::BitwiseXor => Int::checked(l ^ r, typ),
+        _ => None,
+    }
+}
+
+/// The value of a call of a GNU builtin that is constant for constant arguments, e.g.
+/// `__builtin_constant_p(4)`
+///
+/// `__builtin_offsetof` is an [`Expression::OffsetOf`] already.
+fn builtin(call: &CallExpression, env: &Env, layouts: &Layouts) -> Option<Int> {
+    let eval = |e: &Expression| typed(e, env, layouts);
+    let name = match &call.callee.node {
+        Expression::Identifier(id) => id.node.name.as_str(),
+        _ => return None,
+    };
+    let args: Vec<&Expression> = call.arguments.iter().map(|a| &a.node).collect();
+    let count = |v: u32| Some(int(i128::from(v)));
+    match (name, args.as_slice()) {
+        // Only known constants are 1, GCC may still find other arguments to be constant
+        ("__builtin_constant_p", [e]) => eval(e).map(|_| int(1)),
+        ("__builtin_expect", [e, _]) => Some(eval(e)?.converted(LONG)),
+        ("__builtin_choose_expr", [c, then, other]) => {
+            eval(if eval(c)?.value != 0 { then } else { other })
+        }
+        ("__builtin_popcount", [e]) => count((eval(e)?.value as u32).count_ones()),
+        ("__builtin_popcountl", [e]) | ("__builtin_popcountll", [e]) => {
+            count((eval(e)?.value as u64).count_ones())
+        }
+        ("__builtin_bswap16", [e]) => count(u32::from((eval(e)?.value as u16).swap_bytes())),
+        ("__builtin_bswap32", [e]) => {
+            Int::checked(i128::from((eval(e)?.value as u32).swap_bytes()), UNSIGNED)
+        }
+        ("__builtin_bswap64", [e]) => Int::checked(
+            i128::from((eval(e)?.value as u64).swap_bytes()),
+            UNSIGNED_LONG,
+        ),
+        _ => None,
+    }
+}
+
+/// `sizeof` is a `size_t`, an `unsigned long`
+fn size_of(typ: &MyType, layouts: &Layouts) -> Option<Int> {
+    let (size, _) = layouts(typ)?;
+    Int::checked(i128::from(size), UNSIGNED_LONG)
+}
+
+/// The expression a `_Generic` selection chooses, if the type of its controlling expression
+/// is known
+///
+/// Types are compared as written, typedef names aren't resolved.
+pub fn select(g: &GenericSelection) -> Option<&Expression> {
+    let typ = crate::ctype::type_of(&g.expression.node)?;
+    let mut default = None;
+    for a in &g.associations {
+        match &a.node {
+            GenericAssociation::Type(t) => {
+                let candidate = MyType::from_type_name(&t.node.type_name.node);
+                if candidate.same_as(&typ) {
+                    return Some(&t.node.expression.node);
+                }
+            }
+            GenericAssociation::Default(e) => default = Some(&e.node),
+        }
+    }
+    default
+}
+
+/// `rhs` if it is a defined amount to shift `lhs` by, else warn about it
+fn shift_amount(lhs: Int, rhs: i128) -> Option<u32> {
+    let width = lhs.typ.bits;
+    if (0..i128::from(width)).contains(&rhs) {
+        Some(rhs as u32)
+    } else {
+        log::warn!(
+            target: "c_ast::shift",
+            "Shifting {} by {} bits is undefined for a {} bit type",
+            lhs.value,
+            rhs,
+            width
+        );
+        None
+    }
+}
+
+/// The value of an integer constant, `None` for imaginary constants like `2i`
+pub fn integer(i: &Integer) -> Option<i128> {
+    if i.suffix.imaginary {
+        return None;
+    }
+    let radix = match i.base {
+        IntegerBase::Decimal => 10,
+        IntegerBase::Octal => 8,
+        IntegerBase::Hexadecimal => 16,
+        IntegerBase::Binary => 2,
+    };
+    let digits = if i.number.is_empty() { "0" } else { &i.number };
+    i128::from_str_radix(digits, radix).ok()
+}
+
+/// The value of an integer constant with its type, the first type of its suffix it fits into
+///
+/// Octal and hexadecimal constants may be unsigned without the suffix `u`, decimal ones only
+/// if they don't fit a `long long`, as GCC assumes then.
+fn constant(i: &Integer) -> Option<Int> {
+    let value = integer(i)?;
+    let smallest = match i.suffix.size {
+        IntegerSize::Int => 32,
+        IntegerSize::Long | IntegerSize::LongLong => 64,
+    };
+    let unsigned = i.suffix.unsigned;
+    let decimal = i.base == IntegerBase::Decimal;
+    let candidates = [INT, UNSIGNED, LONG, UNSIGNED_LONG];
+    let typ = candidates
+        .iter()
+        .copied()
+        .filter(|t| t.bits >= smallest && (t.signed || unsigned || !decimal))
+        .filter(|t| !(t.signed && unsigned))
+        .find(|t| t.fits(value))
+        .or_else(|| Some(UNSIGNED_LONG).filter(|t| t.fits(value)))?;
+    Some(Int { value, typ })
+}
+
+fn character(raw: &str) -> Option<i128> {
+    if let Some(v) = crate::literal::multi_char(raw) {
+        return Some(v);
+    }
+    let body = raw.strip_prefix('\'')?.strip_suffix('\'')?;
+    match crate::literal::unescape(body)?.as_slice() {
+        [crate::literal::Unit::Char(c)] => Some(*c as i128),
+        [crate::literal::Unit::Escape(e)] => Some(*e as i128),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lang_c::driver::{parse_preprocessed, Config};
+
+    /// The initializer of `int x = <expr>;`
+    fn parsed(expr: &str) -> Expression {
+        let source = format!("int x = {};", expr);
+        let parsed = parse_preprocessed(&Config::default(), source).unwrap();
+        let decl = match &parsed.unit.0[0].node {
+            ExternalDeclaration::Declaration(d) => &d.node,
+            _ => unreachable!(),
+        };
+        match &decl.declarators[0].node.initializer.as_ref().unwrap().node {
+            Initializer::Expression(e) => e.node.clone(),
+            Initializer::List(_) => unreachable!(),
+        }
+    }
+
+    /// The folded initializer of `int x = <expr>;`
+    fn fold(expr: &str) -> Option<i128> {
+        eval(&parsed(expr), &no_env)
+    }
+
+    #[test]
+    fn access_paths() {
+        let env = |n: &str| match n {
+            "i" => Some(MyExpression::Integer {
+                value: 1,
+                raw: String::from("1"),
+            }),
+            _ => None,
+        };
+        let path_of = |expr: &str| path(&parsed(expr), &env);
+        assert_eq!(path_of("a[1][2]").as_deref(), Some("a[1][2]"));
+        assert_eq!(path_of("s.arr[i + 1].x").as_deref(), Some("s.arr[2].x"));
+        assert_eq!(path_of("s.arr[n]"), None);
+        assert_eq!(path_of("p->x"), None);
+        assert_eq!(path_of("a[-1]"), None);
+    }
+
+    #[test]
+    fn shifts_by_the_width_of_the_type() {
+        assert_eq!(fold("1UL << 40"), Some(1 << 40));
+        assert_eq!(fold("1LL << 63"), Some(i128::from(i64::MIN)));
+        assert_eq!(fold("1U << 31"), Some(1 << 31));
+        assert_eq!(fold("1 << 31"), Some(i128::from(i32::MIN)));
+        assert_eq!(fold("1 << 32"), None);
+        assert_eq!(fold("0x100000000 << 32"), Some(0));
+        assert_eq!(fold("(char)1 << 20"), Some(1 << 20));
+        assert_eq!(fold("1 << -1"), None);
+        assert_eq!(fold("-16 >> 2"), Some(-4));
+    }
+
+    #[test]
+    fn unsigned_arithmetic_wraps() {
+        assert_eq!(fold("~0u"), Some(4_294_967_295));
+        assert_eq!(fold("~0"), Some(-1));
+        assert_eq!(fold("~0ULL"), Some(i128::from(u64::MAX)));
+        assert_eq!(fold("-1u"), Some(4_294_967_295));
+        assert_eq!(fold("0u - 1"), Some(4_294_967_295));
+        assert_eq!(fold("0xFFFFFFFF + 1"), Some(0));
+        assert_eq!(fold("4294967295 + 1"), Some(4_294_967_296));
+        assert_eq!(fold("(unsigned char)300"), Some(44));
+        assert_eq!(fold("(signed char)200"), Some(-56));
+        assert_eq!(fold("(_Bool)7"), Some(1));
+    }
+
+    #[test]
+    fn usual_arithmetic_conversions() {
+        assert_eq!(fold("-1 < 0u"), Some(0));
+        assert_eq!(fold("-1 < 0L"), Some(1));
+        assert_eq!(fold("-1L < 0u"), Some(1));
+        assert_eq!(fold("1 ? -1 : 0u"), Some(4_294_967_295));
+        assert_eq!(fold("-1 / 2u"), Some(2_147_483_647));
+    }
+
+    #[test]
+    fn signed_overflow_is_not_folded() {
+        assert_eq!(fold("2147483647 + 1"), None);
+        assert_eq!(fold("2147483647L + 1"), Some(2_147_483_648));
+        assert_eq!(fold("-2147483647 - 1 / -1"), Some(-2_147_483_646));
+        assert_eq!(fold("(-2147483647 - 1) / -1"), None);
+        assert_eq!(fold("1 / 0"), None);
+    }
+}
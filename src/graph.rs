@@ -0,0 +1,114 @@
+//! Relationships between the struct types, e.g. for DOT export
+
+use crate::ctype::Derived;
+use crate::Extraction;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// A field of struct `from` referring to struct `to`
+#[derive(Debug)]
+pub struct Edge<'e> {
+    pub from: &'e str,
+    pub to: &'e str,
+    pub field: &'e str,
+    /// The field refers to `to` through a pointer
+    pub indirect: bool,
+    /// The edge closes a cycle, following it again would recurse forever
+    pub back: bool,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum State {
+    Unvisited,
+    InProgress,
+    Done,
+}
+
+/// All edges between struct types, with the back-edges of cycles marked
+///
+/// The graph is walked depth first from the struct types in name order, so the result
+/// is stable between runs. Self-referential structs get a back-edge to themselves.
+pub fn edges(ex: &Extraction) -> Vec<Edge<'_>> {
+    let mut adjacent: HashMap<&str, Vec<Edge>> = HashMap::new();
+    for st in ex.struct_types.values() {
+        let out = adjacent.entry(&st.name).or_default();
+        for f in &st.fields {
+            let typ = f.typ.resolve(&ex.typedefs);
+            if let Some(to) = typ.struct_name() {
+                if ex.struct_types.contains_key(to) {
+                    let to = ex.struct_types[to].name.as_str();
+                    out.push(Edge {
+                        from: &st.name,
+                        to,
+                        field: &f.name,
+                        indirect: typ.derived.iter().any(|d| matches!(d, Derived::Pointer(_))),
+                        back: false,
+                    });
+                }
+            }
+        }
+    }
+    let mut names: Vec<&str> = adjacent.keys().copied().collect();
+    names.sort_unstable();
+    let mut state: HashMap<&str, State> = names.iter().map(|n| (*n, State::Unvisited)).collect();
+    let mut result = Vec::new();
+    for n in &names {
+        if state[n] == State::Unvisited {
+            walk(n, &mut adjacent, &mut state, &mut result);
+        }
+    }
+    result
+}
+
+fn walk<'e>(
+    node: &'e str,
+    adjacent: &mut HashMap<&'e str, Vec<Edge<'e>>>,
+    state: &mut HashMap<&'e str, State>,
+    result: &mut Vec<Edge<'e>>,
+) {
+    state.insert(node, State::InProgress);
+    let out = adjacent.remove(node).unwrap_or_default();
+    for mut e in out {
+        match state[e.to] {
+            State::InProgress => {
+                e.back = true;
+                if !e.indirect {
+                    log::warn!(
+                        target: "c_ast::cycle",
+                        "struct {} contains itself through field '{}' of struct {}",
+                        e.to,
+                        e.field,
+                        e.from
+                    );
+                }
+                result.push(e);
+            }
+            State::Done => result.push(e),
+            State::Unvisited => {
+                let to = e.to;
+                result.push(e);
+                walk(to, adjacent, state, result);
+            }
+        }
+    }
+    state.insert(node, State::Done);
+}
+
+/// Render the struct relationships as a graphviz digraph, back-edges are dashed
+pub fn dot(ex: &Extraction) -> String {
+    let mut names: Vec<&str> = ex.struct_types.values().map(|s| s.name.as_str()).collect();
+    names.sort_unstable();
+    let mut out = String::from("digraph structs {\n");
+    for n in names {
+        writeln!(out, "  \"{}\";", n).unwrap();
+    }
+    for e in edges(ex) {
+        let mut attrs = format!("label=\"{}{}\"", if e.indirect { "*" } else { "" }, e.field);
+        if e.back {
+            attrs.push_str(", style=dashed, constraint=false");
+        }
+        writeln!(out, "  \"{}\" -> \"{}\" [{}];", e.from, e.to, attrs).unwrap();
+    }
+    out.push_str("}\n");
+    out
+}
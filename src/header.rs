@@ -0,0 +1,91 @@
+//! Render the collected types as C declarations again, e.g. as a header stub
+
+use crate::ctype::Derived;
+use crate::radix::{self, EnumRadix};
+use crate::{Extraction, MyStructType};
+use std::collections::HashSet;
+use std::fmt::Write;
+
+/// The enum and struct types of `ex` as C declarations
+///
+/// Typedef names are resolved in the field types so the header doesn't depend on other
+/// headers. Struct types come after the struct types they contain. Fields whose type can't be
+/// written, e.g. of anonymous struct type, are only kept as comment. Of the attributes only
+/// `deprecated` is kept. The enum constants are written in hex or decimal as told by `radix`.
+pub fn render(ex: &Extraction, radix: EnumRadix) -> String {
+    let mut out = String::new();
+    let mut enum_types: Vec<_> = ex.enum_types.values().collect();
+    enum_types.sort_by_key(|e| &e.name);
+    for et in enum_types {
+        let hex = radix::is_hex(et, radix);
+        match &et.underlying {
+            Some(u) => writeln!(out, "enum {} : {} {{", et.name, u).unwrap(),
+            None => writeln!(out, "enum {} {{", et.name).unwrap(),
+        }
+        for (name, value) in &et.constants {
+            match value {
+                Some(v) => writeln!(out, "  {} = {},", name, radix::format(*v, hex)).unwrap(),
+                None => writeln!(out, "  {},", name).unwrap(),
+            }
+        }
+        writeln!(out, "}};\n").unwrap();
+    }
+    let mut names: Vec<&str> = ex.struct_types.keys().map(|n| n.as_str()).collect();
+    names.sort_unstable();
+    let mut done = HashSet::new();
+    for name in names {
+        write_struct(&mut out, ex, name, &mut done);
+    }
+    out
+}
+
+/// Write the struct `name` after the struct types it contains, unless it is `done` already
+fn write_struct<'e>(
+    out: &mut String,
+    ex: &'e Extraction,
+    name: &'e str,
+    done: &mut HashSet<&'e str>,
+) {
+    let st = match ex.struct_types.get(name) {
+        Some(st) => st,
+        None => return,
+    };
+    if !done.insert(name) {
+        return;
+    }
+    for f in &st.fields {
+        let typ = f.typ.resolve(&ex.typedefs);
+        let by_value = typ.derived.iter().all(|d| matches!(d, Derived::Array(_)));
+        if let (true, Some(inner)) = (by_value, typ.struct_name()) {
+            if let Some((inner, _)) = ex.struct_types.get_key_value(inner) {
+                write_struct(out, ex, inner, done);
+            }
+        }
+    }
+    declare(out, ex, st);
+}
+
+fn declare(out: &mut String, ex: &Extraction, st: &MyStructType) {
+    writeln!(out, "{} {} {{", st.keyword(), st.name).unwrap();
+    for f in &st.fields {
+        let mut decl = f.typ.resolve(&ex.typedefs).declare(&f.name);
+        if let Some(w) = &f.bit_width {
+            write!(decl, " : {}", w).unwrap();
+        }
+        match (f.attrs.deprecated, &f.attrs.deprecated_message) {
+            (true, Some(m)) => write!(decl, " __attribute__((deprecated({:?})))", m).unwrap(),
+            (true, None) => decl.push_str(" __attribute__((deprecated))"),
+            _ => (),
+        }
+        // Keeps the layout of the struct
+        if let Some(a) = f.attrs.align {
+            decl.insert_str(0, &format!("_Alignas({}) ", a));
+        }
+        if decl.contains("<anonymous>") {
+            writeln!(out, "  /* {}; */", decl).unwrap();
+        } else {
+            writeln!(out, "  {};", decl).unwrap();
+        }
+    }
+    writeln!(out, "}};\n").unwrap();
+}
@@ -0,0 +1,142 @@
+//! JSON rendering of an extraction
+
+use crate::{sorted_keys, Extraction, MyValue, ValueKey};
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// The whole extraction as one JSON object
+///
+/// Types are rendered as C declarations, the values are sorted by file, scope and name so
+/// the output is stable between runs.
+pub fn extraction(ex: &Extraction) -> Value {
+    // Objects keep their keys sorted
+    let mut typedefs = Map::new();
+    for (n, t) in &ex.typedefs {
+        typedefs.insert(n.clone(), json!(t.to_string()));
+    }
+
+    let mut struct_types = Map::new();
+    for st in ex.struct_types.values() {
+        let fields: Vec<Value> = st
+            .fields
+            .iter()
+            .map(|f| {
+                json!({
+                    "name": f.name,
+                    "type": f.typ.to_string(),
+                    "attrs": f.attrs,
+                    "bit_width": f.bit_width,
+                })
+            })
+            .collect();
+        struct_types.insert(
+            st.name.clone(),
+            json!({ "union": st.union, "anonymous": st.anonymous, "fields": fields }),
+        );
+    }
+
+    let values = values(ex);
+
+    let mut enum_types = Map::new();
+    for et in ex.enum_types.values() {
+        let constants: Vec<Value> = et
+            .constants
+            .iter()
+            .map(|(n, v)| json!({"name": n, "value": v}))
+            .collect();
+        let mut json = json!({ "anonymous": et.anonymous, "constants": constants });
+        if let Some(u) = &et.underlying {
+            json["underlying"] = json!(u);
+        }
+        enum_types.insert(et.name.clone(), json);
+    }
+
+    let mut json = json!({
+        "typedefs": typedefs,
+        "struct_types": struct_types,
+        "enum_types": enum_types,
+        "values": values,
+        "functions": ex.functions,
+    });
+    if !ex.pragmas.is_empty() {
+        let pragmas: Vec<Value> = ex
+            .pragmas
+            .iter()
+            .map(|(p, at)| json!({"pragma": p, "file": at.file, "line": at.line}))
+            .collect();
+        json["pragmas"] = json!(pragmas);
+    }
+    json
+}
+
+/// The values of `ex` sorted by file, scope and name, each as an object like [`value`]
+pub fn values(ex: &Extraction) -> Vec<Value> {
+    sorted_keys(ex)
+        .into_iter()
+        .map(|k| value(k, &ex.values[k]))
+        .collect()
+}
+
+/// One top level value with its key, e.g. a line of `--format jsonl`
+pub fn value(key: &ValueKey, value: &MyValue) -> Value {
+    json!({
+        "file": key.file,
+        "scope": key.scope,
+        "name": key.name,
+        "value": value,
+    })
+}
+
+/// A hash of [`extraction`] as 16 hex digits, the same for the same extraction on any machine
+///
+/// The JSON objects keep their keys sorted, so the hash doesn't depend on the order the maps
+/// of `ex` are iterated in. It is a 64 bit FNV-1a hash, good to detect changes but not against
+/// tampering. The parts added later (the `underlying` type of enums, the `align` of
+/// [`Attributes`](crate::Attributes) and the `pragmas`) are only hashed when present, so
+/// extractions without them keep their earlier fingerprints.
+pub fn fingerprint(ex: &Extraction) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for b in extraction(ex).to_string().bytes() {
+        hash ^= u64::from(b);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    format!("{:016x}", hash)
+}
+
+/// A compact report of a run: the number of extracted items (and of the declarations skipped
+/// with `--first-only`), the warnings by category, the files and the total parse time
+///
+/// With profiles the counts are reported per profile, under `per` like `profiles`. The runs
+/// can also be labeled otherwise, e.g. with their file.
+pub fn summary(
+    files: &[PathBuf],
+    runs: &[(Option<&str>, Extraction)],
+    per: &str,
+    parse_time: Duration,
+    warnings: &BTreeMap<String, usize>,
+) -> Value {
+    let counts = |ex: &Extraction| {
+        json!({
+            "struct_types": ex.struct_types.len(),
+            "enum_types": ex.enum_types.len(),
+            "values": ex.values.len(),
+            "skipped_declarations": ex.skipped,
+        })
+    };
+    let mut summary = match runs {
+        [(None, ex)] => counts(ex),
+        _ => {
+            let profiles: Map<String, Value> = runs
+                .iter()
+                .map(|(p, ex)| (String::from(p.unwrap_or_default()), counts(ex)))
+                .collect();
+            json!({ per: profiles })
+        }
+    };
+    summary["warnings"] = json!(warnings);
+    summary["files"] = json!(files);
+    summary["parse_ms"] = json!(parse_time.as_secs_f64() * 1000.0);
+    summary
+}
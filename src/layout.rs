@@ -0,0 +1,142 @@
+//! Sizes, alignments and member offsets of the extracted types
+//!
+//! The layout follows the LP64 data model of GCC on x86-64 Linux. The alignments of fields
+//! given with `_Alignas` or the attribute `aligned` are taken into account, attributes like
+//! `packed` aren't known to the extraction. The layout of structs with bit-fields isn't
+//! computed.
+
+use crate::ctype::{Derived, MyType};
+use crate::{Extraction, MyField};
+
+/// One step of an `offsetof` member designator
+#[derive(Debug, Clone, PartialEq)]
+pub enum Member {
+    Field(String),
+    Index(u64),
+}
+
+/// The size and the alignment of `typ` in bytes, `None` if it is incomplete or unknown
+pub fn size_align(typ: &MyType, ex: &Extraction) -> Option<(u64, u64)> {
+    let typ = typ.resolve(&ex.typedefs);
+    let mut element = typ.clone().into_owned();
+    match element.derived.pop() {
+        Some(Derived::Pointer(_)) => return Some((8, 8)),
+        Some(Derived::Array(len)) => {
+            let len: u64 = len?.parse().ok()?;
+            let (size, align) = size_align(&element, ex)?;
+            return Some((size.checked_mul(len)?, align));
+        }
+        Some(Derived::Function { .. }) => return None,
+        None => (),
+    }
+    if let Some(name) = typ.struct_name() {
+        return record(&typ, name, ex);
+    }
+    let count = |w: &str| typ.specifiers.iter().filter(|s| *s == w).count();
+    let size = if count("_Bool") > 0 || count("char") > 0 {
+        1
+    } else if count("short") > 0 {
+        2
+    } else if count("double") > 0 && count("long") > 0 {
+        16
+    } else if count("double") > 0 || count("long") > 0 {
+        8
+    } else if count("float") > 0
+        || count("int") > 0
+        || count("signed") > 0
+        || count("unsigned") > 0
+        || typ.specifiers.iter().any(|s| s.starts_with("enum "))
+    {
+        4
+    } else {
+        return None;
+    };
+    if count("_Complex") > 0 {
+        Some((2 * size, size))
+    } else {
+        Some((size, size))
+    }
+}
+
+/// The offset of `members` within `typ` in bytes, like `offsetof(typ, a.b[2])`
+pub fn offset_of(typ: &MyType, members: &[Member], ex: &Extraction) -> Option<u64> {
+    let mut typ = typ.resolve(&ex.typedefs).into_owned();
+    let mut offset = 0u64;
+    for m in members {
+        match m {
+            Member::Field(name) => {
+                let (field_offset, field_type) = field(&typ, name, ex)?;
+                offset = offset.checked_add(field_offset)?;
+                typ = field_type.resolve(&ex.typedefs).into_owned();
+            }
+            Member::Index(i) => {
+                match typ.derived.pop() {
+                    Some(Derived::Array(_)) => (),
+                    _ => return None,
+                }
+                let (size, _) = size_align(&typ, ex)?;
+                offset = offset.checked_add(size.checked_mul(*i)?)?;
+            }
+        }
+    }
+    Some(offset)
+}
+
+/// The offset and type of the field `name` of the struct or union `typ`
+fn field(typ: &MyType, name: &str, ex: &Extraction) -> Option<(u64, MyType)> {
+    if !typ.derived.is_empty() {
+        return None;
+    }
+    let st = ex.struct_types.get(typ.struct_name()?)?;
+    let union = is_union(typ);
+    let mut offset = 0;
+    for f in &st.fields {
+        if f.bit_width.is_some() {
+            return None;
+        }
+        let (size, align) = field_size_align(f, ex)?;
+        if !union {
+            offset = align_to(offset, align);
+        }
+        if f.name == name {
+            return Some((offset, f.typ.clone()));
+        }
+        if !union {
+            offset += size;
+        }
+    }
+    None
+}
+
+fn record(typ: &MyType, name: &str, ex: &Extraction) -> Option<(u64, u64)> {
+    let st = ex.struct_types.get(name)?;
+    let union = is_union(typ);
+    let (mut size, mut max_align) = (0, 1);
+    for f in &st.fields {
+        if f.bit_width.is_some() {
+            return None;
+        }
+        let (fsize, falign) = field_size_align(f, ex)?;
+        max_align = max_align.max(falign);
+        size = if union {
+            size.max(fsize)
+        } else {
+            align_to(size, falign) + fsize
+        };
+    }
+    Some((align_to(size, max_align), max_align))
+}
+
+/// The size and the alignment of the type of `f`, aligned further as its attributes request
+fn field_size_align(f: &MyField, ex: &Extraction) -> Option<(u64, u64)> {
+    let (size, align) = size_align(&f.typ, ex)?;
+    Some((size, f.attrs.align.map_or(align, |a| a.max(align))))
+}
+
+fn is_union(typ: &MyType) -> bool {
+    typ.specifiers.iter().any(|s| s.starts_with("union "))
+}
+
+fn align_to(offset: u64, align: u64) -> u64 {
+    offset.div_ceil(align) * align
+}
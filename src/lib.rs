@@ -0,0 +1,3126 @@
+//! Extract struct types, typedefs and the values of initialized variables from C sources
+
+mod alignas;
+pub mod cexpr;
+pub mod comments;
+pub mod ctype;
+pub mod diag;
+mod digits;
+mod enum_base;
+mod error;
+pub mod fold;
+pub mod graph;
+pub mod header;
+pub mod json;
+pub mod layout;
+pub mod lint;
+mod literal;
+pub mod markdown;
+pub mod progress;
+pub mod proto;
+pub mod radix;
+pub mod rename;
+pub mod repl;
+pub mod rust;
+pub mod sql;
+pub mod stats;
+pub mod tree;
+pub mod typemap;
+mod ucn;
+pub mod verify;
+pub mod walk;
+pub mod watch;
+
+use ctype::{Derived, MyType};
+pub use error::CAstError;
+use lang_c::ast::*;
+use lang_c::driver::{Config, Parse};
+use lang_c::span::{Node, Span};
+use lang_c::visit;
+use lang_c::visit::Visit;
+use serde::Serialize;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::convert::TryFrom;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Preprocess and parse the file `path`, or only parse it if it is `preprocessed` already
+///
+/// The universal character names of identifiers are replaced by placeholders for the parser,
+/// see [`rename::decode_identifiers`] to get them back. The `_Alignas` of struct fields are
+/// turned into `aligned` attributes. The underlying types of enums like
+/// `enum e : short` and the digit separators of numbers like `1'000` are hidden from the
+/// parser, the source of the result still has them for [`MyVisitor::with_enum_bases`] and
+/// [`MyVisitor::with_digit_separators`].
+pub fn parse_file(config: &Config, path: &Path, preprocessed: bool) -> Result<Parse, CAstError> {
+    let source = if preprocessed {
+        fs::read_to_string(path).map_err(|source| CAstError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?
+    } else {
+        preprocess(config, path).map_err(|source| CAstError::Preprocess {
+            path: path.to_path_buf(),
+            source,
+        })?
+    };
+    let source = match ucn::mangle(&source) {
+        Cow::Borrowed(_) => source,
+        Cow::Owned(mangled) => mangled,
+    };
+    let source = match alignas::rewrite(&source) {
+        Cow::Borrowed(_) => source,
+        Cow::Owned(rewritten) => rewritten,
+    };
+    let stripped = match digits::strip(&source) {
+        Cow::Borrowed(_) => None,
+        Cow::Owned(stripped) => Some(stripped),
+    };
+    let blanked = match enum_base::blank(stripped.as_deref().unwrap_or(&source)) {
+        Cow::Borrowed(_) => None,
+        Cow::Owned(blanked) => Some(blanked),
+    };
+    let (text, original) = match blanked.or(stripped) {
+        Some(text) => (text, Some(source)),
+        None => (source, None),
+    };
+    let mut parse =
+        lang_c::driver::parse_preprocessed(config, text).map_err(|source| CAstError::Parse {
+            path: path.to_path_buf(),
+            source,
+        })?;
+    if let Some(original) = original {
+        parse.source = original;
+    }
+    Ok(parse)
+}
+
+/// Run the preprocessor of `config` on `path`, like [`lang_c::driver::parse`] does
+fn preprocess(config: &Config, path: &Path) -> io::Result<String> {
+    let output = Command::new(&config.cpp_command)
+        .args(&config.cpp_options)
+        .arg(path)
+        .output()?;
+    let text = |bytes| String::from_utf8(bytes).map_err(io::Error::other);
+    if output.status.success() {
+        text(output.stdout)
+    } else if output.stderr.is_empty() {
+        Err(io::Error::other(format!(
+            "{} exited with {}",
+            config.cpp_command, output.status
+        )))
+    } else {
+        Err(io::Error::other(text(output.stderr)?))
+    }
+}
+
+/// Everything collected from a translation unit
+#[derive(Debug, Default)]
+pub struct Extraction {
+    pub struct_types: HashMap<String, MyStructType>,
+    pub enum_types: HashMap<String, MyEnumType>,
+    /// The underlying types of the enums declared with one, also of those only declared
+    /// forward like `enum color : unsigned char;`, for the definitions without one
+    pub enum_bases: HashMap<String, String>,
+    pub typedefs: HashMap<String, MyType>,
+    pub values: HashMap<ValueKey, MyValue>,
+    /// The declared and defined functions, only collected if asked for
+    pub functions: Vec<FunctionDecl>,
+    /// The `#pragma` lines, only collected if asked for, see [`comments::Source::pragmas`]
+    pub pragmas: Vec<(String, Location)>,
+    /// The external declarations not visited once the target of [`MyVisitor::stop_after`]
+    /// was collected
+    pub skipped: usize,
+}
+
+/// A line of an original source file
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Location {
+    pub file: PathBuf,
+    /// Starting at 1
+    pub line: usize,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.file.display(), self.line)
+    }
+}
+
+/// The signature of a function, with the types rendered as C
+#[derive(Debug, Clone, Serialize)]
+pub struct FunctionDecl {
+    pub name: String,
+    pub return_type: String,
+    pub params: Vec<(Option<String>, String)>,
+    pub variadic: bool,
+    /// The storage class and function specifiers, e.g. `static` and `inline`
+    pub specifiers: Vec<String>,
+    /// The whole function type, to render the signature properly
+    #[serde(skip)]
+    typ: MyType,
+}
+
+impl FunctionDecl {
+    /// The signature of `name` if `typ` is a function type
+    fn new(name: &str, typ: MyType, specifiers: Vec<String>) -> Option<FunctionDecl> {
+        let mut ret = typ.clone();
+        let (params, variadic) = match ret.derived.pop()? {
+            Derived::Function { params, variadic } => (params, variadic),
+            _ => return None,
+        };
+        let void = MyType {
+            specifiers: vec![String::from("void")],
+            ..MyType::default()
+        };
+        let params = match params.as_slice() {
+            // `f(void)` has no parameters
+            [(None, t)] if *t == void => Vec::new(),
+            _ => params
+                .into_iter()
+                .map(|(n, t)| (n, t.to_string()))
+                .collect(),
+        };
+        Some(FunctionDecl {
+            name: String::from(name),
+            return_type: ret.to_string(),
+            params,
+            variadic,
+            specifiers,
+            typ,
+        })
+    }
+}
+
+impl fmt::Display for FunctionDecl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for s in &self.specifiers {
+            write!(f, "{} ", s)?;
+        }
+        write!(f, "{}", self.typ.declare(&self.name))
+    }
+}
+
+#[derive(Debug)]
+pub struct MyStructType {
+    pub name: String,
+    /// A `union` rather than a `struct`
+    pub union: bool,
+    /// Declared without a tag, the name is synthesized, see [`MyVisitor`]
+    pub anonymous: bool,
+    pub fields: Vec<MyField>,
+}
+
+#[derive(Debug)]
+pub struct MyField {
+    pub name: String,
+    pub typ: MyType,
+    pub attrs: Attributes,
+    /// The width of a bit-field as written, e.g. `3` for `int mode : 3;`
+    pub bit_width: Option<String>,
+    /// The comment documenting the field in the source, see [`MyVisitor::with_source`]
+    pub doc: Option<String>,
+    /// An anonymous struct or union member like `union { int i; float f; };`, the name is
+    /// synthesized as `anonymous_1`, `anonymous_2` and so on
+    pub anonymous: bool,
+}
+
+/// The GNU attributes of a field or variable that are worth reporting
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Attributes {
+    pub deprecated: bool,
+    pub deprecated_message: Option<String>,
+    /// The symbol name given with `__asm__("name")`
+    pub asm_label: Option<String>,
+    /// The linker section given with `__attribute__((section(".config")))`
+    pub section: Option<String>,
+    /// The alignment in bytes given with `_Alignas(16)`, `_Alignas(double)` or
+    /// `__attribute__((aligned(16)))`, see [`layout`]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub align: Option<u64>,
+}
+
+impl Attributes {
+    /// Add the recognized attributes among `exts`, the alignments of types are taken from
+    /// the types of `ex`
+    fn add(&mut self, exts: &[Node<Extension>], ex: &Extraction) {
+        let text = |s: &StringLiteral| {
+            literal::string_bytes(s).map(|b| String::from_utf8_lossy(&b).into_owned())
+        };
+        for e in exts {
+            match &e.node {
+                // `__deprecated__` is the same as `deprecated`
+                Extension::Attribute(a) if a.name.node.trim_matches('_') == "deprecated" => {
+                    self.deprecated = true;
+                    self.deprecated_message = a.arguments.first().and_then(|m| match &m.node {
+                        Expression::StringLiteral(s) => text(&s.node),
+                        _ => None,
+                    });
+                }
+                Extension::Attribute(a) if a.name.node.trim_matches('_') == "section" => {
+                    self.section = a.arguments.first().and_then(|m| match &m.node {
+                        Expression::StringLiteral(s) => text(&s.node),
+                        _ => None,
+                    });
+                }
+                // Without an argument the largest alignment of the target
+                Extension::Attribute(a) if a.name.node.trim_matches('_') == "aligned" => {
+                    let align = match a.arguments.first() {
+                        Some(x) => alignment(&x.node, ex),
+                        None => Some(16),
+                    };
+                    self.align_to(align);
+                }
+                Extension::AsmLabel(s) => self.asm_label = text(&s.node),
+                _ => (),
+            }
+        }
+    }
+
+    /// Take the strictest of the alignments given
+    fn align_to(&mut self, align: Option<u64>) {
+        if let Some(a) = align {
+            self.align = Some(self.align.map_or(a, |b| b.max(a)));
+        }
+    }
+
+    fn from_declaration_specifiers(
+        specs: &[Node<DeclarationSpecifier>],
+        ex: &Extraction,
+    ) -> Attributes {
+        let mut attrs = Attributes::default();
+        for s in specs {
+            match &s.node {
+                DeclarationSpecifier::Extension(exts) => attrs.add(exts, ex),
+                DeclarationSpecifier::Alignment(a) => attrs.align_to(match &a.node {
+                    AlignmentSpecifier::Type(t) => align_of(&MyType::from_type_name(&t.node), ex),
+                    AlignmentSpecifier::Constant(e) => alignment(&e.node, ex),
+                }),
+                _ => (),
+            }
+        }
+        attrs
+    }
+
+    fn from_specifier_qualifiers(
+        specs: &[Node<SpecifierQualifier>],
+        ex: &Extraction,
+    ) -> Attributes {
+        let mut attrs = Attributes::default();
+        for s in specs {
+            if let SpecifierQualifier::Extension(exts) = &s.node {
+                attrs.add(exts, ex);
+            }
+        }
+        attrs
+    }
+
+    /// Add the attributes of `d` and its nested declarators
+    fn with_declarator(mut self, d: &Declarator, ex: &Extraction) -> Attributes {
+        self.add(&d.extensions, ex);
+        match &d.kind.node {
+            DeclaratorKind::Declarator(inner) => self.with_declarator(&inner.node, ex),
+            _ => self,
+        }
+    }
+}
+
+/// The alignment `x` of `_Alignas(x)` or `aligned(x)`, `_Alignof(type)` for the type form
+fn alignment(x: &Expression, ex: &Extraction) -> Option<u64> {
+    match x {
+        Expression::AlignOf(t) => align_of(&MyType::from_type_name(&t.node), ex),
+        x => u64::try_from(fold::eval(x, &fold::no_env)?).ok(),
+    }
+}
+
+fn align_of(typ: &MyType, ex: &Extraction) -> Option<u64> {
+    layout::size_align(typ, ex).map(|(_, align)| align)
+}
+
+/// Tags like ` [deprecated]` for the human output, empty without notable attributes
+impl fmt::Display for Attributes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.deprecated, &self.deprecated_message) {
+            (true, Some(m)) => write!(f, " [deprecated: {}]", m)?,
+            (true, None) => write!(f, " [deprecated]")?,
+            _ => (),
+        }
+        if let Some(l) = &self.asm_label {
+            write!(f, " [asm: {}]", l)?;
+        }
+        if let Some(s) = &self.section {
+            write!(f, " [section: {}]", s)?;
+        }
+        match self.align {
+            Some(a) => write!(f, " [align: {}]", a),
+            None => Ok(()),
+        }
+    }
+}
+
+impl MyStructType {
+    /// `struct` or `union`
+    pub fn keyword(&self) -> &'static str {
+        if self.union {
+            "union"
+        } else {
+            "struct"
+        }
+    }
+
+    /// Whether `other` has the same fields, e.g. if a struct is defined twice
+    pub fn same_fields(&self, other: &MyStructType) -> bool {
+        self.union == other.union
+            && self.fields.len() == other.fields.len()
+            && self
+                .fields
+                .iter()
+                .zip(&other.fields)
+                .all(|(a, b)| a.name == b.name && a.typ == b.typ && a.bit_width == b.bit_width)
+    }
+
+    /// The fields as declared in C, like `int a; char *b;`
+    fn declaration(&self) -> String {
+        let fields: Vec<String> = self
+            .fields
+            .iter()
+            .map(|f| match &f.bit_width {
+                Some(w) => format!("{} : {};", f.typ.declare(&f.name), w),
+                None => format!("{};", f.typ.declare(&f.name)),
+            })
+            .collect();
+        fields.join(" ")
+    }
+
+    pub fn new(name: &str) -> MyStructType {
+        MyStructType {
+            name: String::from(name),
+            union: false,
+            anonymous: false,
+            fields: Vec::new(),
+        }
+    }
+}
+
+/// Renders `T` like its `Display` impl, but indents with the given string instead of two
+/// spaces
+pub struct Indented<'t, T>(pub &'t T, pub &'t str);
+
+impl fmt::Display for MyStructType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Indented(self, "  ").fmt(f)
+    }
+}
+
+impl fmt::Display for Indented<'_, MyStructType> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Indented(st, indent) = self;
+        writeln!(f, "{} {} {{", st.keyword(), st.name)?;
+        for field in &st.fields {
+            write!(f, "{}{}", indent, field.typ.declare(&field.name))?;
+            if let Some(w) = &field.bit_width {
+                write!(f, " : {}", w)?;
+            }
+            writeln!(f, ";{}", field.attrs)?;
+        }
+        writeln!(f, "}}")
+    }
+}
+
+/// A named enum with its constants, the value is `None` if it couldn't be determined
+#[derive(Debug)]
+pub struct MyEnumType {
+    pub name: String,
+    /// Declared without a tag, the name is synthesized, see [`MyVisitor`]
+    pub anonymous: bool,
+    pub constants: Vec<(String, Option<i128>)>,
+    /// The fixed underlying type of `enum e : short { ... }`, also if only a forward
+    /// declaration had it
+    pub underlying: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub enum MyValue {
+    Struct(MyStruct),
+    Scalar {
+        name: String,
+        value: MyExpression,
+        attrs: Attributes,
+    },
+}
+
+impl MyValue {
+    pub fn new_scalar(name: &str, value: MyExpression) -> MyValue {
+        MyValue::Scalar {
+            name: String::from(name),
+            value,
+            attrs: Attributes::default(),
+        }
+    }
+
+    pub fn attrs(&self) -> &Attributes {
+        match self {
+            MyValue::Struct(s) => &s.attrs,
+            MyValue::Scalar { attrs, .. } => attrs,
+        }
+    }
+
+    fn with_attrs(mut self, a: Attributes) -> MyValue {
+        match &mut self {
+            MyValue::Struct(s) => s.attrs = a,
+            MyValue::Scalar { attrs, .. } => *attrs = a,
+        }
+        self
+    }
+}
+
+impl fmt::Display for MyValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Indented(self, "  ").fmt(f)
+    }
+}
+
+impl fmt::Display for Indented<'_, MyValue> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Flattened(self.0, &Style::new(self.1)).fmt(f)
+    }
+}
+
+/// How the human output shows the values of arrays
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FlattenArrays {
+    /// As the elements' `Debug` output, e.g. `Array([Integer { value: 1, raw: "1" }])`
+    None,
+    /// One `name[i] = ...` line per element
+    Indexed,
+    /// In one line like `[1, 2, 3]`, with only the values of integers
+    Compact,
+}
+
+/// How the human output shows values
+#[derive(Debug, Clone)]
+pub struct Style {
+    /// The indentation per nesting level
+    pub indent: String,
+    pub arrays: FlattenArrays,
+    /// The enum constants whose values are shown in hex, also in the integers referring to
+    /// them, see [`radix::hex_constants`]
+    pub hex: HashSet<String>,
+    /// Group the digits of the integer values with `_` like `1_000_000`, see [`radix::group`]
+    pub group: bool,
+}
+
+impl Style {
+    /// Indent by `indent`, with the arrays as `Debug` output and all integers in decimal
+    pub fn new(indent: &str) -> Style {
+        Style {
+            indent: String::from(indent),
+            arrays: FlattenArrays::None,
+            hex: HashSet::new(),
+            group: false,
+        }
+    }
+}
+
+/// Renders a value like [`Indented`] does, in the given style
+pub struct Flattened<'t>(pub &'t MyValue, pub &'t Style);
+
+impl fmt::Display for Flattened<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Flattened(value, style) = *self;
+        match value {
+            MyValue::Struct(s) => {
+                writeln!(f, "struct {} {}{}", s.typ, s.name, s.attrs)?;
+                write_fields(f, &s.values, style, 1)?;
+                writeln!(f)
+            }
+            MyValue::Scalar {
+                name: n,
+                value: v,
+                attrs,
+            } => {
+                let line = Line {
+                    style,
+                    suffix: attrs.to_string(),
+                };
+                line.write(f, 0, n, v)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MyStruct {
+    pub typ: String,
+    pub name: String,
+    pub attrs: Attributes,
+    pub values: Vec<(String, MyExpression)>,
+}
+
+impl fmt::Display for MyStruct {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Indented(self, "  ").fmt(f)
+    }
+}
+
+impl fmt::Display for Indented<'_, MyStruct> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Indented(s, indent) = self;
+        writeln!(f, "struct {} {}{}", s.typ, s.name, s.attrs)?;
+        write_fields(f, &s.values, &Style::new(indent), 1)
+    }
+}
+
+fn write_fields(
+    f: &mut fmt::Formatter<'_>,
+    values: &[(String, MyExpression)],
+    style: &Style,
+    depth: usize,
+) -> fmt::Result {
+    let line = Line {
+        style,
+        suffix: String::new(),
+    };
+    for (n, e) in values {
+        line.write(f, depth, &format!(".{}", n), e)?;
+    }
+    Ok(())
+}
+
+/// The lines of one value of the human output
+struct Line<'s> {
+    style: &'s Style,
+    /// Written at the end of each line, e.g. the attributes of a top level value
+    suffix: String,
+}
+
+impl Line<'_> {
+    fn write(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        depth: usize,
+        label: &str,
+        e: &MyExpression,
+    ) -> fmt::Result {
+        let indent = self.style.indent.repeat(depth);
+        match (e, self.style.arrays) {
+            // Top level values of struct type are `MyValue::Struct`s
+            (MyExpression::Struct(s), _) if depth > 0 => {
+                writeln!(f, "{}{} = struct {} {{", indent, label, s.typ)?;
+                write_fields(f, &s.values, self.style, depth + 1)?;
+                writeln!(f, "{}}}", indent)
+            }
+            (MyExpression::Array(xs), FlattenArrays::Indexed) if !xs.is_empty() => {
+                for (i, x) in xs.iter().enumerate() {
+                    self.write(f, depth, &format!("{}[{}]", label, i), x)?;
+                }
+                Ok(())
+            }
+            // Only the set elements
+            (MyExpression::SparseArray { entries, .. }, FlattenArrays::Indexed)
+                if !entries.is_empty() =>
+            {
+                for (i, x) in entries {
+                    self.write(f, depth, &format!("{}[{}]", label, i), x)?;
+                }
+                Ok(())
+            }
+            (MyExpression::Array(_), FlattenArrays::Indexed)
+            | (MyExpression::Array(_), FlattenArrays::Compact)
+            | (MyExpression::SparseArray { .. }, FlattenArrays::Indexed)
+            | (MyExpression::SparseArray { .. }, FlattenArrays::Compact) => {
+                let text = self.compact(e);
+                writeln!(f, "{}{} = {}{}", indent, label, text, self.suffix)
+            }
+            (MyExpression::Integer { value, raw }, _) if self.hex(raw) || self.style.group => {
+                writeln!(
+                    f,
+                    "{}{} = Integer {{ value: {}, raw: {:?} }}{}",
+                    indent,
+                    label,
+                    self.integer(*value, raw),
+                    raw,
+                    self.suffix
+                )
+            }
+            (e, _) => writeln!(f, "{}{} = {:?}{}", indent, label, e, self.suffix),
+        }
+    }
+
+    /// Whether the integer written as `raw` is shown in hex
+    fn hex(&self, raw: &str) -> bool {
+        !self.style.hex.is_empty() && radix::refers_to(raw, &self.style.hex)
+    }
+
+    /// The value of the integer written as `raw`, in hex and grouped as the style tells
+    fn integer(&self, value: i128, raw: &str) -> String {
+        let text = radix::format(value, self.hex(raw));
+        match self.style.group {
+            true => radix::group(&text),
+            false => text,
+        }
+    }
+
+    /// `e` as it is shown by [`FlattenArrays::Compact`]
+    fn compact(&self, e: &MyExpression) -> String {
+        match e {
+            MyExpression::Integer { value, raw } => self.integer(*value, raw),
+            MyExpression::Array(xs) => {
+                let xs: Vec<_> = xs.iter().map(|x| self.compact(x)).collect();
+                format!("[{}]", xs.join(", "))
+            }
+            // Like `[[0] = 1, [255] = 2; 256]`
+            MyExpression::SparseArray { len, entries } => {
+                let xs: Vec<_> = entries
+                    .iter()
+                    .map(|(i, x)| format!("[{}] = {}", i, self.compact(x)))
+                    .collect();
+                format!("[{}; {}]", xs.join(", "), len)
+            }
+            MyExpression::Struct(s) => {
+                let fields: Vec<_> = s
+                    .values
+                    .iter()
+                    .map(|(n, v)| format!(".{} = {}", n, self.compact(v)))
+                    .collect();
+                format!("{{{}}}", fields.join(", "))
+            }
+            e => tree::text(e),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub enum MyExpression {
+    Integer {
+        value: i128,
+        raw: String,
+    },
+    /// The literal with its suffix, e.g. `3.14159265358979323846L` for a `long double`
+    ///
+    /// It is kept as written to not lose precision beyond that of `f64`.
+    Float(String),
+    /// The value of a `_Bool`, e.g. of `bool on = FEATURE_X;` with `#define FEATURE_X 1`
+    Bool(bool),
+    String(String),
+    /// The literal as written in the source and its bytes, if it could be decoded
+    StringLiteral {
+        raw: Vec<String>,
+        bytes: Option<Vec<u8>>,
+    },
+    /// A wide or UTF-16/32 literal like `L"..."` as written and its code units, if it could
+    /// be decoded
+    ///
+    /// An array initialized by it without a length has one element per unit plus the
+    /// terminating NUL.
+    WideString {
+        encoding: Encoding,
+        raw: Vec<String>,
+        units: Option<Vec<u32>>,
+    },
+    Array(Vec<MyExpression>),
+    /// An array of `len` elements of which only those at the indices of `entries` are set,
+    /// the others are zero, e.g. `int lut[256] = { [0] = 1, [255] = 2 };`
+    ///
+    /// Used instead of [`MyExpression::Array`] for large arrays with few set elements, see
+    /// [`MyVisitor::with_dense_arrays`].
+    SparseArray {
+        len: usize,
+        entries: Vec<(usize, MyExpression)>,
+    },
+    /// The address of an object, e.g. `&cmd_a` as `cmd_a`
+    AddressOf(String),
+    /// A function assigned to a function pointer, e.g. `h1` or `&h1` of the dispatch table
+    /// `const handler_t table[] = { h1, &h2 };`
+    FunctionRef(String),
+    /// The null pointer constant `NULL`, i.e. `(void *)0`
+    Null,
+    /// A call of a function or a function-like macro that isn't constant, e.g.
+    /// `make_color(255, 0, 0)`
+    Call {
+        name: String,
+        args: Vec<MyExpression>,
+    },
+    /// The value of a struct member of struct type, named after the member
+    Struct(MyStruct),
+    Other {
+        category: OtherCategory,
+        raw: String,
+    },
+}
+
+/// The number of elements from which arrays with few set elements are kept as
+/// [`MyExpression::SparseArray`]
+pub const SPARSE_LEN: usize = 64;
+
+/// The character type of a prefixed string literal
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum Encoding {
+    /// `L"..."`, `wchar_t` of 32 bits as on Linux
+    Wide,
+    /// `u"..."`, `char16_t`
+    Utf16,
+    /// `U"..."`, `char32_t`
+    Utf32,
+}
+
+/// Why an expression could not be turned into one of the other `MyExpression` variants
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum OtherCategory {
+    /// The expression kind is not handled (yet)
+    Unsupported,
+    /// The expression can not be a constant, e.g. it contains a function call or an assignment
+    NonConstant,
+    /// The expression refers to an identifier whose value is not known
+    UnknownIdentifier,
+}
+
+/// Identifies an extracted value by the file and the (nested) scope it was declared in
+///
+/// The scope is empty for file scope declarations. Inside a function the first element is
+/// the function name followed by one `{n}` element per nested block, where `n` counts the
+/// blocks within the enclosing scope.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ValueKey {
+    pub file: Option<PathBuf>,
+    pub scope: Vec<String>,
+    pub name: String,
+}
+
+impl fmt::Display for ValueKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(file) = &self.file {
+            write!(f, "{}:", file.display())?;
+        }
+        for s in &self.scope {
+            write!(f, "{}::", s)?;
+        }
+        write!(f, "{}", self.name)
+    }
+}
+
+/// The keys of the values of `ex` sorted by file, scope and name, the order of all outputs
+pub fn sorted_keys(ex: &Extraction) -> Vec<&ValueKey> {
+    let mut keys: Vec<_> = ex.values.keys().collect();
+    keys.sort_unstable_by(|a, b| (&a.file, &a.scope, &a.name).cmp(&(&b.file, &b.scope, &b.name)));
+    keys
+}
+
+/// Turns expressions into values before the built-in transformation is tried
+///
+/// Register one with `MyVisitor::with_transformer` to map domain specific constructs, e.g.
+/// the calls of a `RGB(r, g, b)` macro, to structured values.
+pub trait ExpressionTransformer {
+    /// The value of `expr`, or `None` to leave it to the next transformer
+    fn try_transform(&self, expr: &Expression) -> Option<MyExpression>;
+}
+
+/// Which initialized declarations inside of functions are collected
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Locals {
+    None,
+    /// Only those with static storage duration, e.g. `static const int table[] = ...`
+    Static,
+    /// Also the `auto` and `register` ones
+    All,
+}
+
+/// What becomes of the values of anonymous struct and union members
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AnonymousFields {
+    /// Their members are promoted into the containing struct value, like C accesses them
+    Flatten,
+    /// They are kept as nested struct value under the synthesized field name
+    Nest,
+}
+
+/// Collects the types and values of translation units into an [`Extraction`]
+///
+/// Struct, union and enum types declared without a tag get a synthesized name: the typedef
+/// name for `typedef struct { ... } point_t`, the struct and member name joined by `_` for
+/// the member `pos` of `struct sprite` (`sprite_pos`), the variable name for
+/// `struct { ... } origin`, and `anonymous_1`, `anonymous_2` and so on otherwise. If a tag is
+/// named like that, e.g. `struct origin`, a number is appended like `origin_2`.
+pub struct MyVisitor<'a> {
+    cur_struct: Option<String>,
+    /// The name for an anonymous type declared by the specifiers being visited
+    anonymous_name: Option<String>,
+    /// The tags of the struct, union and enum types of the translation unit being visited,
+    /// which the names of anonymous types avoid
+    tags: HashSet<String>,
+    /// The name given to the last anonymous type, to refer to it in the declared types
+    last_anonymous: Option<String>,
+    /// The declaration being visited is `const` qualified
+    cur_const: bool,
+    /// The attributes of the declaration specifiers being visited
+    cur_attrs: Attributes,
+    /// The base type of the declaration being visited
+    cur_type: MyType,
+    /// The storage class specifiers of the declaration being visited, e.g. `extern`
+    cur_storage: Vec<String>,
+    /// The declared variables by scope, with the value of the `const` integers and floats,
+    /// which can be referred to by later initializers. By scope and name instead of by
+    /// [`ValueKey`], so they are looked up without allocating.
+    symbols: HashMap<Vec<String>, HashMap<String, Option<MyExpression>>>,
+    /// The declared `const` variables, the members of those of struct type can be referred
+    /// to by later initializers
+    constants: HashSet<ValueKey>,
+    /// The values of the `const` definitions of other files, for the `extern` declarations
+    externs: Option<&'a HashMap<String, MyExpression>>,
+    /// The values of the `const` definitions with external linkage, see
+    /// [`MyVisitor::definitions`]
+    definitions: HashMap<String, MyExpression>,
+    sink: Option<Box<dyn FnMut(ValueKey, MyValue) + 'a>>,
+    transformers: Vec<Box<dyn ExpressionTransformer + 'a>>,
+    functions: bool,
+    dense_arrays: bool,
+    warn_shadowing: bool,
+    source: Option<comments::Source<'a>>,
+    /// How the files of the located warnings are shown, see [`MyVisitor::with_shown_paths`]
+    shown_paths: Box<dyn Fn(&Path) -> PathBuf + 'a>,
+    /// The underlying types of the enum specifiers by offset, see [`enum_base`]
+    enum_bases: HashMap<usize, String>,
+    /// The integers written with digit separators by offset, see [`digits`]
+    separated: HashMap<usize, String>,
+    skip_system_headers: bool,
+    /// The typedefs of the system headers, only known while visiting
+    system_typedefs: HashSet<String>,
+    locals: Locals,
+    anonymous_fields: AnonymousFields,
+    /// The struct type or value to stop after, and whether it was collected
+    target: Option<(String, bool)>,
+    file: Option<PathBuf>,
+    scope: Vec<String>,
+    blocks: Vec<usize>,
+    ex: &'a mut Extraction,
+}
+
+impl<'a> MyVisitor<'a> {
+    pub fn new(file: Option<&Path>, ex: &'a mut Extraction) -> MyVisitor<'a> {
+        MyVisitor {
+            cur_struct: None,
+            anonymous_name: None,
+            tags: HashSet::new(),
+            last_anonymous: None,
+            cur_const: false,
+            cur_attrs: Attributes::default(),
+            cur_type: MyType::default(),
+            cur_storage: Vec::new(),
+            symbols: HashMap::new(),
+            constants: HashSet::new(),
+            externs: None,
+            definitions: HashMap::new(),
+            sink: None,
+            transformers: Vec::new(),
+            functions: false,
+            dense_arrays: false,
+            warn_shadowing: false,
+            source: None,
+            shown_paths: Box::new(Path::to_path_buf),
+            enum_bases: HashMap::new(),
+            separated: HashMap::new(),
+            skip_system_headers: false,
+            system_typedefs: HashSet::new(),
+            locals: Locals::All,
+            anonymous_fields: AnonymousFields::Flatten,
+            target: None,
+            file: file.map(PathBuf::from),
+            scope: Vec::new(),
+            blocks: vec![0],
+            ex,
+        }
+    }
+
+    /// Hand the elements of file scope array-of-struct initializers to `sink` one by one
+    ///
+    /// Such elements are then not collected into the values map, which keeps the memory
+    /// for the extracted values bounded on huge generated tables. Note that `lang_c` still
+    /// parses the whole translation unit up front, so the AST itself is held in memory
+    /// regardless.
+    pub fn with_sink(mut self, sink: impl FnMut(ValueKey, MyValue) + 'a) -> MyVisitor<'a> {
+        self.sink = Some(Box::new(sink));
+        self
+    }
+
+    /// Try `t` on initializer expressions, after the transformers registered before it
+    pub fn with_transformer(mut self, t: impl ExpressionTransformer + 'a) -> MyVisitor<'a> {
+        self.transformers.push(Box::new(t));
+        self
+    }
+
+    /// Also collect the signatures of the declared and defined functions
+    pub fn with_functions(mut self) -> MyVisitor<'a> {
+        self.functions = true;
+        self
+    }
+
+    /// Keep all elements of the arrays, also of the large ones with few set elements that
+    /// are [`MyExpression::SparseArray`]s otherwise
+    pub fn with_dense_arrays(mut self) -> MyVisitor<'a> {
+        self.dense_arrays = true;
+        self
+    }
+
+    /// Warn about the members designated in initializers that have the name of a file scope
+    /// variable declared before, like `.timeout = 5` with a global `timeout`, which is often
+    /// a copy and paste mistake
+    pub fn with_shadowing_warnings(mut self) -> MyVisitor<'a> {
+        self.warn_shadowing = true;
+        self
+    }
+
+    /// Take the documentation comments of the struct fields from the original files
+    ///
+    /// `source` is the preprocessed text the translation unit was parsed from, its line
+    /// markers lead to the original files.
+    pub fn with_source(mut self, source: &'a str) -> MyVisitor<'a> {
+        self.source = Some(comments::Source::new(source));
+        self
+    }
+
+    /// Show the files the warnings are located in as `shown` maps them, e.g. relative to a
+    /// base directory, instead of as the line markers of the source name them
+    pub fn with_shown_paths(mut self, shown: impl Fn(&Path) -> PathBuf + 'a) -> MyVisitor<'a> {
+        self.shown_paths = Box::new(shown);
+        self
+    }
+
+    /// Take the underlying types of the enums like `enum e : short` from `source`, the
+    /// preprocessed text as returned by [`parse_file`]
+    pub fn with_enum_bases(mut self, source: &str) -> MyVisitor<'a> {
+        self.enum_bases = enum_base::bases(source);
+        self
+    }
+
+    /// Keep the digit separators of integers like `1'000'000` in their raw form, taken from
+    /// `source`, the preprocessed text as returned by [`parse_file`]
+    pub fn with_digit_separators(mut self, source: &str) -> MyVisitor<'a> {
+        self.separated = digits::separated(source);
+        self
+    }
+
+    /// Don't collect the typedefs, struct types, enum types, values and functions declared in
+    /// system headers, as told by the source given with [`MyVisitor::with_source`]
+    ///
+    /// Their typedefs are only known while visiting, to resolve the types of the other
+    /// declarations, the types refer to them by name afterwards, e.g. `size_t`.
+    pub fn without_system_headers(mut self) -> MyVisitor<'a> {
+        self.skip_system_headers = true;
+        self
+    }
+
+    /// Collect only these declarations inside of functions, all by default
+    pub fn with_locals(mut self, locals: Locals) -> MyVisitor<'a> {
+        self.locals = locals;
+        self
+    }
+
+    /// Promote the members of anonymous struct and union members into the containing struct
+    /// values or keep them nested, promoted by default
+    pub fn with_anonymous_fields(mut self, mode: AnonymousFields) -> MyVisitor<'a> {
+        self.anonymous_fields = mode;
+        self
+    }
+
+    /// Stop visiting once a struct type or value named `name` is collected
+    ///
+    /// The rest of the external declaration it is found in is still visited, the following
+    /// ones are skipped. See [`MyVisitor::found`] to skip the following files as well.
+    pub fn stop_after(mut self, name: &str) -> MyVisitor<'a> {
+        self.target = Some((String::from(name), false));
+        self
+    }
+
+    /// Resolve the `extern` declarations of `const` variables to these values, e.g. the
+    /// [`MyVisitor::definitions`] of the other translation units of a program
+    pub fn with_externs(mut self, externs: &'a HashMap<String, MyExpression>) -> MyVisitor<'a> {
+        self.externs = Some(externs);
+        self
+    }
+
+    /// The values of the file scope `const` integers and floats that aren't `static`, i.e.
+    /// that other translation units can declare as `extern`
+    pub fn definitions(&self) -> &HashMap<String, MyExpression> {
+        &self.definitions
+    }
+
+    /// Whether the target of [`MyVisitor::stop_after`] was collected
+    pub fn found(&self) -> bool {
+        matches!(self.target, Some((_, true)))
+    }
+
+    fn collected(&mut self, name: &str) {
+        if let Some((target, found)) = &mut self.target {
+            *found |= target == name;
+        }
+    }
+
+    /// The name for the anonymous type being visited
+    fn anonymous(&mut self) -> String {
+        let name = match self.anonymous_name.take() {
+            Some(name) => name,
+            None => {
+                let count = self
+                    .ex
+                    .struct_types
+                    .values()
+                    .filter(|s| s.anonymous)
+                    .count()
+                    + self.ex.enum_types.values().filter(|e| e.anonymous).count();
+                format!("anonymous_{}", count + 1)
+            }
+        };
+        // The anonymous types of the same name are the same ones, e.g. of a header included
+        // by several files
+        let taken = |n: &str| {
+            self.tags.contains(n)
+                || self.ex.struct_types.get(n).is_some_and(|s| !s.anonymous)
+                || self.ex.enum_types.get(n).is_some_and(|e| !e.anonymous)
+        };
+        if !taken(&name) {
+            return name;
+        }
+        (2..)
+            .map(|i| format!("{}_{}", name, i))
+            .find(|n| !taken(n))
+            .unwrap_or(name)
+    }
+
+    /// Refer to the name of the anonymous type declared by the specifiers of `typ`
+    fn name_anonymous(&mut self, typ: &mut MyType) {
+        if let Some(name) = self.last_anonymous.take() {
+            if let Some(s) = typ
+                .specifiers
+                .iter_mut()
+                .find(|s| s.ends_with(" <anonymous>"))
+            {
+                *s = s.replace("<anonymous>", &name);
+            }
+        }
+    }
+
+    /// Locate the warnings at `span` until the guard is dropped, if the source is known
+    fn locate(&self, span: &Span) -> Option<diag::Located> {
+        let (file, line) = self.source.as_ref()?.location(span.start)?;
+        let file = (self.shown_paths)(&file);
+        Some(diag::locate(Some(Location { file, line })))
+    }
+
+    fn in_system_header(&self, span: &Span) -> bool {
+        match &self.source {
+            Some(s) if self.skip_system_headers => s.is_system(span.start),
+            _ => false,
+        }
+    }
+
+    fn add_function(&mut self, name: &str, typ: MyType, specifiers: &[Node<DeclarationSpecifier>]) {
+        if !self.functions || self.ex.functions.iter().any(|f| f.name == name) {
+            return;
+        }
+        if let Some(f) = FunctionDecl::new(name, typ, ctype::storage_specifiers(specifiers)) {
+            self.ex.functions.push(f);
+        }
+    }
+
+    fn insert(&mut self, key: ValueKey, val: MyValue) {
+        self.collected(&key.name);
+        self.ex.values.entry(key).or_insert(val);
+    }
+
+    fn insert_element(&mut self, key: ValueKey, val: MyValue) {
+        match &mut self.sink {
+            Some(sink) if key.scope.is_empty() => sink(key, val),
+            _ => self.insert(key, val),
+        }
+    }
+
+    fn new_struct(&self, typ: &str, name: &str, items: &[Node<InitializerListItem>]) -> MyStruct {
+        let mut mst = self.members(typ, name, items);
+        let values = std::mem::take(&mut mst.values);
+        mst.values = self.promote(typ, values);
+        mst
+    }
+
+    /// The struct `name` of type `typ` like [`MyVisitor::new_struct`], but with the values of
+    /// its anonymous members not yet promoted
+    ///
+    /// So the members of anonymous members set by several designators can be merged first,
+    /// e.g. of a struct in an anonymous union in an anonymous struct.
+    fn members(&self, typ: &str, name: &str, items: &[Node<InitializerListItem>]) -> MyStruct {
+        let mut mst = MyStruct {
+            typ: String::from(typ),
+            name: String::from(name),
+            attrs: Attributes::default(),
+            values: Vec::new(),
+        };
+        if let (Some(stype), true) = (self.ex.struct_types.get(typ), zero_init(items)) {
+            // `{0}` and `{}` set all members to zero
+            mst.values = stype
+                .fields
+                .iter()
+                .map(|f| (f.name.clone(), self.zero(&f.typ, &f.name)))
+                .collect();
+        } else if let Some(stype) = self.ex.struct_types.get(typ) {
+            let record = MyType {
+                specifiers: vec![format!("{} {}", stype.keyword(), typ)],
+                ..MyType::default()
+            };
+            let items = &*self.braced(&record, items);
+            // Designated members, also GNU's old style `field: value`, set the position for
+            // the following members. With the values the item that set the whole member last.
+            let mut values: Vec<(Option<MyExpression>, Option<usize>)> = Vec::new();
+            values.resize_with(stype.fields.len(), || (None, None));
+            let mut next = 0;
+            for (k, x) in items.iter().enumerate() {
+                let ini = &x.node.initializer.node;
+                let designation = &x.node.designation;
+                let i = match designation.first().map(|d| &d.node) {
+                    None => next,
+                    Some(Designator::Member(id)) => {
+                        let member = &id.node.name;
+                        self.check_shadowing(member, name);
+                        let (i, item) = match (
+                            stype.fields.iter().position(|f| f.name == *member),
+                            self.in_anonymous(stype, member),
+                        ) {
+                            (Some(i), _) if designation.len() == 1 => (i, None),
+                            (Some(i), _) => {
+                                // `.inner.x = 5` only sets `x` of the member `inner`, and
+                                // `.rows[1] = {...}` only the element 1 of `rows`
+                                let mut item = x.clone();
+                                item.node.designation.remove(0);
+                                (i, Some(item))
+                            }
+                            // `.i = 1` for the member `union { int i; float f; };`
+                            (None, Some(i)) => (i, Some(x.clone())),
+                            (None, None) => {
+                                log::warn!(
+                                    target: "c_ast::designator",
+                                    "Ignoring {} = {} of {}, struct {} has no such member",
+                                    id.node.name,
+                                    cexpr::initializer(ini),
+                                    name,
+                                    typ
+                                );
+                                continue;
+                            }
+                        };
+                        if let Some(item) = item {
+                            let field = &stype.fields[i];
+                            let (set, whole) = &mut values[i];
+                            let array = matches!(
+                                field.typ.resolve(&self.ex.typedefs).derived.last(),
+                                Some(Derived::Array(_))
+                            );
+                            *set = Some(
+                                if array && item.node.designation.len() < designation.len() {
+                                    // The elements of an array can't be merged, its items since
+                                    // it was set as a whole are taken together
+                                    let list = self.designated(items, k, &field.name, *whole);
+                                    self.field_value(&field.typ, &field.name, &list)
+                                } else {
+                                    let list = Initializer::List(vec![item]);
+                                    let value = self.member_value(field, &list);
+                                    self.merge(set.take(), value)
+                                },
+                            );
+                            next = i + 1;
+                            continue;
+                        }
+                        i
+                    }
+                    Some(_) if designation.len() == 1 => {
+                        log::warn!(
+                            target: "c_ast::designator",
+                            "Ignoring {} of {} with an array designator",
+                            cexpr::initializer(ini),
+                            name
+                        );
+                        continue;
+                    }
+                    Some(_) => {
+                        log::warn!(
+                            target: "c_ast::designator",
+                            "Ignoring {} of {} with unsupported nested designators",
+                            cexpr::initializer(ini),
+                            name
+                        );
+                        continue;
+                    }
+                };
+                match stype.fields.get(i) {
+                    Some(field) => values[i] = (Some(self.member_value(field, ini)), Some(k)),
+                    None => log::warn!(
+                        target: "c_ast::designator",
+                        "Ignoring the excess element {} of {}",
+                        cexpr::initializer(ini),
+                        name
+                    ),
+                }
+                next = i + 1;
+            }
+            mst.values = stype
+                .fields
+                .iter()
+                .zip(values)
+                .filter_map(|(f, (v, _))| Some((f.name.clone(), v?)))
+                .collect();
+        } else {
+            // Without the field names keep what the initializer tells, the designated
+            // names or else the position
+            log::warn!(
+                target: "c_ast::unknown_struct",
+                "Struct type '{}' not found, the fields of {} are only partially known",
+                typ,
+                name
+            );
+            for (i, x) in items.iter().enumerate() {
+                let fname = match x.node.designation.first().map(|d| &d.node) {
+                    Some(Designator::Member(id)) => id.node.name.clone(),
+                    _ => format!("[{}]", i),
+                };
+                self.fill(&mut mst.values, &fname, &x.node.initializer.node);
+            }
+        }
+        mst
+    }
+
+    /// `items` initializing the aggregate `typ` with the braces that may be left out made
+    /// explicit, and with the positional items after a nested designator designated
+    ///
+    /// The items of a member or element that is an aggregate itself don't need braces of
+    /// their own, `struct { int a[2]; int b; } x = {1, 2, 3};` sets `a` to `{1, 2}`. A
+    /// positional item sets the subobject after the one set before, after `.inner.x = 1`
+    /// that is `inner.y`, as if designated with `.inner.y`.
+    fn braced<'i>(
+        &self,
+        typ: &MyType,
+        items: &'i [Node<InitializerListItem>],
+    ) -> Cow<'i, [Node<InitializerListItem>]> {
+        // Only copied once something changes
+        let mut out: Option<Vec<Node<InitializerListItem>>> = None;
+        let mut next = 0;
+        let mut k = 0;
+        while k < items.len() {
+            let levels = match items[k].node.designation.as_slice() {
+                [] => vec![(typ.clone(), next)],
+                designation => match self.levels(typ, designation) {
+                    Some(levels) => levels,
+                    // Warned about when the item is visited
+                    None => {
+                        if let Some(out) = &mut out {
+                            out.push(items[k].clone());
+                        }
+                        k += 1;
+                        continue;
+                    }
+                },
+            };
+            let leaf = levels
+                .last()
+                .and_then(|(parent, i)| self.subobject(parent, *i));
+            match self.elided(leaf.map(|(t, _)| t).as_ref(), &items[k..]) {
+                Some((taken, item)) => {
+                    out.get_or_insert_with(|| items[..k].to_vec()).push(item);
+                    k += taken;
+                }
+                None => {
+                    if let Some(out) = &mut out {
+                        out.push(items[k].clone());
+                    }
+                    k += 1;
+                }
+            }
+            // The positional items following a nested designator set the subobjects after
+            // the designated one, in the same aggregate or one around it
+            let mut inner: Vec<(MyType, usize)> = levels[1..].to_vec();
+            let mut advance = true;
+            loop {
+                while let Some((parent, i)) = inner.last_mut().filter(|_| advance) {
+                    *i += 1;
+                    if self.subobject(parent, *i).is_some() {
+                        break;
+                    }
+                    inner.pop();
+                }
+                advance = true;
+                let positional = items.get(k).is_some_and(|x| x.node.designation.is_empty());
+                if inner.is_empty() || !positional {
+                    break;
+                }
+                let mut designation = Vec::new();
+                let mut leaf = None;
+                let mut anonymous = false;
+                for (parent, i) in levels[..1].iter().chain(&inner) {
+                    let (sub, d) = self.subobject(parent, *i).unwrap_or_default();
+                    anonymous = d.is_none();
+                    designation.extend(d.map(|d| Node::new(d, items[k].span)));
+                    leaf = Some(sub);
+                }
+                // An anonymous member has no designator, the item sets its first member
+                if anonymous {
+                    match &items[k].node.initializer.node {
+                        Initializer::Expression(_) => {
+                            inner.extend(leaf.map(|sub| (sub, 0)));
+                            advance = false;
+                            continue;
+                        }
+                        Initializer::List(_) => {
+                            log::warn!(
+                                target: "c_ast::designator",
+                                "Ignoring {} after a nested designator, braces for an anonymous \
+                                 member can't be placed",
+                                cexpr::initializer(&items[k].node.initializer.node)
+                            );
+                            out.get_or_insert_with(|| items[..k].to_vec());
+                            k += 1;
+                            break;
+                        }
+                    }
+                }
+                let (taken, mut item) = self
+                    .elided(leaf.as_ref(), &items[k..])
+                    .unwrap_or_else(|| (1, items[k].clone()));
+                item.node.designation = designation;
+                out.get_or_insert_with(|| items[..k].to_vec()).push(item);
+                k += taken;
+            }
+            next = levels[0].1 + 1;
+        }
+        match out {
+            Some(out) => Cow::Owned(out),
+            None => Cow::Borrowed(items),
+        }
+    }
+
+    /// How many of `items` set the aggregate `typ` without braces of their own, and these
+    /// items as one list, `None` if the first item has braces or sets all of `typ`
+    fn elided(
+        &self,
+        typ: Option<&MyType>,
+        items: &[Node<InitializerListItem>],
+    ) -> Option<(usize, Node<InitializerListItem>)> {
+        let first = &items[0];
+        let taken = match (typ, &first.node.initializer.node) {
+            (Some(t), Initializer::Expression(e))
+                if self.is_aggregate(t) && !self.whole(t, &e.node) =>
+            {
+                self.elided_len(t, items)
+            }
+            _ => return None,
+        };
+        let mut list = items[..taken].to_vec();
+        list[0].node.designation.clear();
+        let initializer = Node::new(Initializer::List(list), first.node.initializer.span);
+        let item = InitializerListItem {
+            designation: first.node.designation.clone(),
+            initializer: Box::new(initializer),
+        };
+        Some((taken, Node::new(item, first.span)))
+    }
+
+    /// How many of `items` set the subobjects of the aggregate `typ` without braces, the
+    /// first one regardless of its designator
+    fn elided_len(&self, typ: &MyType, items: &[Node<InitializerListItem>]) -> usize {
+        let mut taken = 0;
+        let mut i = 0;
+        while let (Some(x), Some((sub, _))) = (items.get(taken), self.subobject(typ, i)) {
+            if taken > 0 && !x.node.designation.is_empty() {
+                break;
+            }
+            taken += match &x.node.initializer.node {
+                Initializer::Expression(e)
+                    if self.is_aggregate(&sub) && !self.whole(&sub, &e.node) =>
+                {
+                    self.elided_len(&sub, &items[taken..])
+                }
+                _ => 1,
+            };
+            i += 1;
+        }
+        taken.max(1)
+    }
+
+    /// The aggregates and the positions in them that `designation` designates in `typ`, the
+    /// anonymous members a member is found in too
+    fn levels(
+        &self,
+        typ: &MyType,
+        designation: &[Node<Designator>],
+    ) -> Option<Vec<(MyType, usize)>> {
+        let env = &|n: &str| self.lookup(n);
+        let mut levels = Vec::new();
+        let mut parent = typ.clone();
+        for d in designation {
+            let i = match &d.node {
+                Designator::Index(e) => usize::try_from(fold::eval(&e.node, env)?).ok()?,
+                // The items after a range continue after its end
+                Designator::Range(r) => usize::try_from(fold::eval(&r.node.to.node, env)?).ok()?,
+                Designator::Member(id) => loop {
+                    let resolved = parent.resolve(&self.ex.typedefs);
+                    let stype = self.ex.struct_types.get(resolved.struct_name()?)?;
+                    let name = &id.node.name;
+                    if let Some(i) = stype.fields.iter().position(|f| f.name == *name) {
+                        break i;
+                    }
+                    let i = self.in_anonymous(stype, name)?;
+                    let sub = stype.fields[i].typ.clone();
+                    levels.push((parent, i));
+                    parent = sub;
+                },
+            };
+            let (sub, _) = self.subobject(&parent, i)?;
+            levels.push((parent, i));
+            parent = sub;
+        }
+        Some(levels)
+    }
+
+    /// The type of the subobject `i` of the aggregate `typ` with its designator (none for an
+    /// anonymous member), `None` after the last one
+    ///
+    /// Only the first member of a union is set by a positional item.
+    fn subobject(&self, typ: &MyType, i: usize) -> Option<(MyType, Option<Designator>)> {
+        let typ = typ.resolve(&self.ex.typedefs);
+        match typ.derived.last() {
+            Some(Derived::Array(_)) => {
+                if typ.array_dims()[0].is_some_and(|len| i >= len) {
+                    return None;
+                }
+                let mut element = typ.into_owned();
+                element.derived.pop();
+                let index = Expression::Constant(Box::new(Node::new(
+                    Constant::Integer(Integer {
+                        base: IntegerBase::Decimal,
+                        number: i.to_string().into_boxed_str(),
+                        suffix: IntegerSuffix {
+                            size: IntegerSize::Int,
+                            unsigned: false,
+                            imaginary: false,
+                        },
+                    }),
+                    Span::none(),
+                )));
+                let index = Box::new(Node::new(index, Span::none()));
+                Some((element, Some(Designator::Index(*index))))
+            }
+            Some(_) => None,
+            None => {
+                let stype = self.ex.struct_types.get(typ.struct_name()?)?;
+                let field = stype.fields.get(i).filter(|_| !stype.union || i == 0)?;
+                let designator = Designator::Member(Node::new(
+                    Identifier {
+                        name: field.name.clone(),
+                    },
+                    Span::none(),
+                ));
+                Some((
+                    field.typ.clone(),
+                    Some(designator).filter(|_| !field.anonymous),
+                ))
+            }
+        }
+    }
+
+    /// Whether `typ` is an array or a known struct or union
+    fn is_aggregate(&self, typ: &MyType) -> bool {
+        let typ = typ.resolve(&self.ex.typedefs);
+        match typ.derived.last() {
+            Some(Derived::Array(_)) => true,
+            Some(_) => false,
+            None => typ
+                .struct_name()
+                .is_some_and(|s| self.ex.struct_types.contains_key(s)),
+        }
+    }
+
+    /// Whether `e` sets all of the aggregate `typ` rather than its first subobject, as a
+    /// string literal does for an array of characters or a struct value for a struct
+    ///
+    /// The types of the expressions aren't known, identifiers without a known scalar value
+    /// are taken for struct values.
+    fn whole(&self, typ: &MyType, e: &Expression) -> bool {
+        let typ = typ.resolve(&self.ex.typedefs);
+        match (typ.derived.as_slice(), e) {
+            ([Derived::Array(_)], Expression::StringLiteral(_)) => typ.struct_name().is_none(),
+            (_, Expression::CompoundLiteral(_) | Expression::Call(_)) => typ.derived.is_empty(),
+            (_, Expression::Identifier(_) | Expression::Member(_)) if typ.derived.is_empty() => {
+                !matches!(
+                    self.transform(e),
+                    MyExpression::Integer { .. } | MyExpression::Float(_) | MyExpression::Bool(_)
+                )
+            }
+            _ => false,
+        }
+    }
+
+    /// The value of the member `field` initialized by `ini`, of an anonymous member with its
+    /// anonymous members not yet promoted, see [`MyVisitor::members`]
+    fn member_value(&self, field: &MyField, ini: &Initializer) -> MyExpression {
+        let typ = field.typ.resolve(&self.ex.typedefs);
+        match (ini, typ.derived.last(), typ.struct_name()) {
+            (Initializer::List(items), None, Some(s))
+                if field.anonymous && self.ex.struct_types.contains_key(s) =>
+            {
+                MyExpression::Struct(self.members(s, &field.name, items))
+            }
+            _ => self.field_value(&field.typ, &field.name, ini),
+        }
+    }
+
+    /// Warn if the designated `member` of `name` has the name of a file scope variable, see
+    /// [`MyVisitor::with_shadowing_warnings`]
+    fn check_shadowing(&self, member: &str, name: &str) {
+        let globals = self.symbols.get(&[] as &[String]);
+        if self.warn_shadowing && globals.is_some_and(|g| g.contains_key(member)) {
+            log::warn!(
+                target: "c_ast::shadowing",
+                "The member .{} designated in {} has the name of a global variable",
+                member,
+                name
+            );
+        }
+    }
+
+    /// The items among `items` up to `k` that set the array member `name`, as one list
+    ///
+    /// These are the items of the initializer that set it as a whole last, at `whole`, and
+    /// the following items designating its elements like `.name[2] = 5`.
+    fn designated(
+        &self,
+        items: &[Node<InitializerListItem>],
+        k: usize,
+        name: &str,
+        whole: Option<usize>,
+    ) -> Initializer {
+        let mut list = match whole.map(|w| &items[w].node.initializer.node) {
+            Some(Initializer::List(xs)) => xs.clone(),
+            _ => Vec::new(),
+        };
+        for x in &items[whole.map_or(0, |w| w + 1)..=k] {
+            match x.node.designation.first().map(|d| &d.node) {
+                Some(Designator::Member(id))
+                    if id.node.name == name && x.node.designation.len() > 1 =>
+                {
+                    let mut item = x.clone();
+                    item.node.designation.remove(0);
+                    list.push(item);
+                }
+                _ => (),
+            }
+        }
+        Initializer::List(list)
+    }
+
+    /// The position of the anonymous member of `stype` that has a member `name`, maybe
+    /// through further anonymous members
+    fn in_anonymous(&self, stype: &MyStructType, name: &str) -> Option<usize> {
+        stype
+            .fields
+            .iter()
+            .position(|f| f.anonymous && self.has_member(&f.typ, name))
+    }
+
+    fn has_member(&self, typ: &MyType, name: &str) -> bool {
+        let typ = typ.resolve(&self.ex.typedefs);
+        match typ.struct_name().and_then(|s| self.ex.struct_types.get(s)) {
+            Some(st) => {
+                st.fields.iter().any(|f| f.name == name) || self.in_anonymous(st, name).is_some()
+            }
+            None => false,
+        }
+    }
+
+    /// The value of a member with the members designated in `new` set in `old`, e.g. of an
+    /// anonymous member or of `inner` for `.inner.x = 1, .inner.y = 2`
+    fn merge(&self, old: Option<MyExpression>, new: MyExpression) -> MyExpression {
+        match (old, new) {
+            (Some(MyExpression::Struct(mut old)), MyExpression::Struct(new))
+                if old.typ == new.typ
+                    && !self
+                        .ex
+                        .struct_types
+                        .get(&new.typ)
+                        .is_some_and(|st| st.union) =>
+            {
+                for (n, v) in new.values {
+                    match old.values.iter_mut().find(|(o, _)| *o == n) {
+                        Some(o) => o.1 = self.merge(Some(o.1.clone()), v),
+                        None => old.values.push((n, v)),
+                    }
+                }
+                // In the order of the fields, whichever was designated first
+                if let Some(st) = self.ex.struct_types.get(&old.typ) {
+                    old.values.sort_by_key(|(n, _)| {
+                        st.fields
+                            .iter()
+                            .position(|f| f.name == *n)
+                            .unwrap_or(usize::MAX)
+                    });
+                }
+                MyExpression::Struct(old)
+            }
+            // Designating the member of a union again sets its members, e.g. of an anonymous
+            // struct in it
+            (Some(MyExpression::Struct(mut old)), MyExpression::Struct(mut new))
+                if old.typ == new.typ
+                    && old.values.len() == 1
+                    && new.values.len() == 1
+                    && old.values[0].0 == new.values[0].0 =>
+            {
+                let (n, v) = new.values.remove(0);
+                let (_, o) = old.values.remove(0);
+                old.values.push((n, self.merge(Some(o), v)));
+                MyExpression::Struct(old)
+            }
+            // The last designated member of a union is the one that is set
+            (_, new) => new,
+        }
+    }
+
+    /// `values` of the struct type `typ` with the values of its anonymous members promoted
+    /// into it, also of the anonymous members in these, unless these are kept nested, see [`MyVisitor::with_anonymous_fields`]
+    fn promote(
+        &self,
+        typ: &str,
+        values: Vec<(String, MyExpression)>,
+    ) -> Vec<(String, MyExpression)> {
+        let stype = match (self.anonymous_fields, self.ex.struct_types.get(typ)) {
+            (AnonymousFields::Flatten, Some(st)) if st.fields.iter().any(|f| f.anonymous) => st,
+            _ => return values,
+        };
+        values
+            .into_iter()
+            .flat_map(|(n, v)| match v {
+                MyExpression::Struct(s)
+                    if stype.fields.iter().any(|f| f.anonymous && f.name == n) =>
+                {
+                    self.promote(&s.typ, s.values)
+                }
+                v => vec![(n, v)],
+            })
+            .collect()
+    }
+
+    /// The value of the struct member `name` of type `typ`
+    ///
+    /// Members of struct and array type keep their structure, braces around a scalar are
+    /// dropped. Empty braces (C23) are the zero value of the type.
+    fn field_value(&self, typ: &MyType, name: &str, ini: &Initializer) -> MyExpression {
+        let items = match ini {
+            Initializer::Expression(e) => {
+                let value = self.typed(typ, &e.node);
+                self.check_range(name, typ, &value);
+                return value;
+            }
+            Initializer::List(items) => items,
+        };
+        let typ = typ.resolve(&self.ex.typedefs);
+        match (typ.derived.last(), typ.struct_name()) {
+            (Some(Derived::Array(_)), _) => {
+                self.array(name, Some(&element_type(&typ)), &typ.array_dims(), items)
+            }
+            (None, Some(s)) if self.ex.struct_types.contains_key(s) => {
+                MyExpression::Struct(self.new_struct(s, name, items))
+            }
+            _ => match items.first() {
+                Some(x) => self.field_value(&typ, name, &x.node.initializer.node),
+                None => self.zero(&typ, name),
+            },
+        }
+    }
+
+    /// The zero value of the member `name` of type `typ`, e.g. an empty string for a char
+    /// array or `NULL` for a pointer
+    fn zero(&self, typ: &MyType, name: &str) -> MyExpression {
+        let typ = typ.resolve(&self.ex.typedefs);
+        let mut element = typ.clone().into_owned();
+        match element.derived.pop() {
+            Some(Derived::Pointer(_)) => MyExpression::Null,
+            Some(Derived::Array(_)) if element.derived.is_empty() && is_char(&element) => {
+                MyExpression::StringLiteral {
+                    raw: vec![String::from("\"\"")],
+                    bytes: Some(Vec::new()),
+                }
+            }
+            Some(Derived::Array(len)) => {
+                let len = len.and_then(|l| l.parse().ok()).unwrap_or(0);
+                if self.is_sparse(len, 0) {
+                    return MyExpression::SparseArray {
+                        len,
+                        entries: Vec::new(),
+                    };
+                }
+                MyExpression::Array(vec![self.zero(&element, name); len])
+            }
+            Some(Derived::Function { .. }) => MyExpression::Other {
+                category: OtherCategory::Unsupported,
+                raw: String::from("0"),
+            },
+            None => match typ.struct_name() {
+                Some(s) => match self.ex.struct_types.get(s) {
+                    Some(st) => MyExpression::Struct(MyStruct {
+                        typ: String::from(s),
+                        name: String::from(name),
+                        attrs: Attributes::default(),
+                        values: self.promote(
+                            s,
+                            st.fields
+                                .iter()
+                                .map(|f| (f.name.clone(), self.zero(&f.typ, &f.name)))
+                                .collect(),
+                        ),
+                    }),
+                    None => MyExpression::Other {
+                        category: OtherCategory::Unsupported,
+                        raw: String::from("{0}"),
+                    },
+                },
+                None if typ.specifiers.iter().any(|s| s == "float" || s == "double") => {
+                    MyExpression::Float(String::from("0.0"))
+                }
+                None if typ.is_bool() => MyExpression::Bool(false),
+                None => MyExpression::Integer {
+                    value: 0,
+                    raw: String::from("0"),
+                },
+            },
+        }
+    }
+
+    /// Warn if `value` is an integer that doesn't fit `name` of type `typ`
+    fn check_range(&self, name: &str, typ: &MyType, value: &MyExpression) {
+        let (len, raw) = match value {
+            MyExpression::StringLiteral {
+                raw,
+                bytes: Some(bytes),
+            } => (bytes.len(), raw),
+            MyExpression::WideString {
+                raw,
+                units: Some(units),
+                ..
+            } => (units.len(), raw),
+            _ => (0, &Vec::new()),
+        };
+        if let Some(Derived::Array(Some(size))) = typ.resolve(&self.ex.typedefs).derived.last() {
+            // The terminating NUL is left out if it doesn't fit
+            match size.parse::<usize>() {
+                Ok(size) if len > size => log::warn!(
+                    target: "c_ast::range",
+                    "{} = {} has {} characters, more than fit",
+                    typ.declare(name),
+                    raw.join(" "),
+                    len
+                ),
+                _ => (),
+            }
+        }
+        if let MyExpression::Integer { value, raw } = value {
+            if let Some((min, max)) = typ.resolve(&self.ex.typedefs).int_range() {
+                if *value < min || *value > max {
+                    log::warn!(
+                        target: "c_ast::range",
+                        "{} = {} is out of the range {}..={} of the type",
+                        typ.declare(name),
+                        raw,
+                        min,
+                        max
+                    );
+                }
+            }
+        }
+    }
+
+    fn key(&self, name: &str) -> ValueKey {
+        self.key_of(String::from(name))
+    }
+
+    fn key_of(&self, name: String) -> ValueKey {
+        ValueKey {
+            file: self.file.clone(),
+            scope: self.scope.clone(),
+            name,
+        }
+    }
+
+    /// The value of the `const` variable `name` as seen from the current scope
+    ///
+    /// `name` may also be a member access like `cfg.base.value` or `table[1].x`, see
+    /// [`MyVisitor::member`].
+    fn lookup(&self, name: &str) -> Option<MyExpression> {
+        let value = if name.contains(['.', '[']) {
+            self.member(name)
+        } else {
+            self.symbols[self.declared(name)?][name].clone()
+        };
+        // A `_Bool` is an integer in expressions
+        match value? {
+            MyExpression::Bool(b) => Some(MyExpression::Integer {
+                value: i128::from(b),
+                raw: String::from(name),
+            }),
+            value => Some(value),
+        }
+    }
+
+    /// The scope of the declaration of `name` that is visible in the current scope
+    fn declared(&self, name: &str) -> Option<&[String]> {
+        let mut scope = self.scope.as_slice();
+        loop {
+            // An inner declaration hides the outer ones, even if it is no constant
+            if self
+                .symbols
+                .get(scope)
+                .is_some_and(|s| s.contains_key(name))
+            {
+                return Some(scope);
+            }
+            scope = scope.split_last()?.1;
+        }
+    }
+
+    /// Declare the variable `name` in the current scope, with its value if it can be
+    /// referred to
+    fn declare(&mut self, name: &str, value: Option<MyExpression>) {
+        match self.symbols.get_mut(self.scope.as_slice()) {
+            Some(symbols) => {
+                symbols.insert(String::from(name), value);
+            }
+            None => {
+                let symbols = HashMap::from([(String::from(name), value)]);
+                self.symbols.insert(self.scope.clone(), symbols);
+            }
+        }
+    }
+
+    /// The value of the access `path` like `cfg.base.value` or `grid[1][2]`, if the accessed
+    /// variable is a `const` struct or array with a known value there, see [`fold::path`]
+    fn member(&self, path: &str) -> Option<MyExpression> {
+        let mut segments = path.split('.');
+        let first = segments.next()?;
+        let (name, indices) = indexed(first)?;
+        let key = ValueKey {
+            file: self.file.clone(),
+            scope: self.declared(name)?.to_vec(),
+            name: String::from(name),
+        };
+        if !self.constants.contains(&key) {
+            return None;
+        }
+        // The elements of arrays of structs are values of their own, e.g. `table[1]`
+        let element = ValueKey {
+            name: String::from(first),
+            ..key.clone()
+        };
+        let (value, indices) = match self.ex.values.get(&element) {
+            Some(v) => (v, Vec::new()),
+            None => (self.ex.values.get(&key)?, indices),
+        };
+        let mut found = match value {
+            MyValue::Struct(s) => MyExpression::Struct(s.clone()),
+            MyValue::Scalar { value, .. } => value.clone(),
+        };
+        for i in indices {
+            found = element_at(found, i)?;
+        }
+        for segment in segments {
+            let (field, indices) = indexed(segment)?;
+            found = match found {
+                MyExpression::Struct(s) => s.values.into_iter().find(|(n, _)| n == field)?.1,
+                _ => return None,
+            };
+            for i in indices {
+                found = element_at(found, i)?;
+            }
+        }
+        match found {
+            MyExpression::Other { .. } => None,
+            MyExpression::Integer { value, .. } => Some(MyExpression::Integer {
+                value,
+                raw: String::from(path),
+            }),
+            e => Some(e),
+        }
+    }
+
+    fn enter_scope(&mut self, name: String) {
+        self.scope.push(name);
+        self.blocks.push(0);
+    }
+
+    fn leave_scope(&mut self) {
+        self.scope.pop();
+        self.blocks.pop();
+    }
+
+    /// Try the registered transformers in order before the built-in `transform`
+    fn transform(&self, expr: &Expression) -> MyExpression {
+        if let Some(e) = self.transformers.iter().find_map(|t| t.try_transform(expr)) {
+            return e;
+        }
+        if let Expression::Constant(c) = expr {
+            if let (Some(raw), Constant::Integer(i)) = (self.separated.get(&c.span.start), &c.node)
+            {
+                return match integer(i) {
+                    MyExpression::Integer { value, .. } => MyExpression::Integer {
+                        value,
+                        raw: raw.clone(),
+                    },
+                    e => e,
+                };
+            }
+        }
+        if let Expression::OffsetOf(o) = expr {
+            if let Some(value) = self.offset_of(&o.node) {
+                return MyExpression::Integer {
+                    value: i128::from(value),
+                    raw: cexpr::render(expr),
+                };
+            }
+        }
+        transform(expr, &|n| self.lookup(n))
+    }
+
+    /// The value of `expr` assigned to an object of type `typ`, the functions assigned to
+    /// function pointers are [`MyExpression::FunctionRef`]s and the integers assigned to
+    /// a `_Bool` are [`MyExpression::Bool`]s
+    fn typed(&self, typ: &MyType, expr: &Expression) -> MyExpression {
+        let typ = typ.resolve(&self.ex.typedefs);
+        if typ.is_bool() {
+            // Also the `true` and `false` of C23 without `<stdbool.h>`
+            return match (self.transform(expr), expr) {
+                (MyExpression::Integer { value, .. }, _) => MyExpression::Bool(value != 0),
+                (_, Expression::Identifier(id)) if id.node.name == "true" => {
+                    MyExpression::Bool(true)
+                }
+                (_, Expression::Identifier(id)) if id.node.name == "false" => {
+                    MyExpression::Bool(false)
+                }
+                (value, _) => value,
+            };
+        }
+        if let (Some((min, max)), Expression::Identifier(id)) = (typ.int_range(), expr) {
+            // `const int whole = SCALE;` with `const double SCALE = 1.5;` truncates to 1
+            if let Some(MyExpression::Float(f)) = self.lookup(&id.node.name) {
+                let truncated = f
+                    .trim_end_matches(['f', 'F', 'l', 'L'])
+                    .parse::<f64>()
+                    .ok()
+                    .map(f64::trunc)
+                    .filter(|t| (min as f64..=max as f64).contains(t));
+                return match truncated {
+                    Some(t) => MyExpression::Integer {
+                        value: t as i128,
+                        raw: id.node.name.clone(),
+                    },
+                    None => MyExpression::Other {
+                        category: OtherCategory::Unsupported,
+                        raw: id.node.name.clone(),
+                    },
+                };
+            }
+        }
+        if typ.is_function_pointer() {
+            let function = match expr {
+                Expression::Identifier(id) if self.lookup(&id.node.name).is_none() => {
+                    Some(&id.node.name)
+                }
+                Expression::UnaryOperator(u) if u.node.operator.node == UnaryOperator::Address => {
+                    match &u.node.operand.node {
+                        Expression::Identifier(id) => Some(&id.node.name),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            };
+            if let Some(name) = function {
+                return MyExpression::FunctionRef(name.clone());
+            }
+        }
+        self.transform(expr)
+    }
+
+    /// `typ` with the derived declarators of `d`, with the array lengths given with `sizeof`
+    /// or `_Alignof` evaluated if the layouts of the types are known, see [`layout`]
+    fn apply_declarator(&self, typ: &MyType, d: &Declarator) -> MyType {
+        typ.clone().with_declarator_sized(d, &|e| {
+            let rendered = cexpr::render(e);
+            if !rendered.contains("sizeof") && !rendered.contains("_Alignof") {
+                return None;
+            }
+            u64::try_from(self.eval(e)?).ok()
+        })
+    }
+
+    /// The value of the integer constant expression `e`, with the constants declared before
+    /// and the layouts of the types extracted so far
+    fn eval(&self, e: &Expression) -> Option<i128> {
+        fold::eval_sized(e, &|n| self.lookup(n), &|t| layout::size_align(t, self.ex))
+    }
+
+    /// The value of `offsetof(T, m)` if the layout of `T` is known
+    fn offset_of(&self, o: &OffsetOfExpression) -> Option<u64> {
+        let typ = MyType::from_type_name(&o.type_name.node);
+        let mut members = vec![layout::Member::Field(
+            o.designator.node.base.node.name.clone(),
+        )];
+        for m in &o.designator.node.members {
+            members.push(match &m.node {
+                OffsetMember::Member(id) => layout::Member::Field(id.node.name.clone()),
+                OffsetMember::Index(e) => {
+                    let i = fold::eval(&e.node, &|n| self.lookup(n))?;
+                    layout::Member::Index(u64::try_from(i).ok()?)
+                }
+                OffsetMember::IndirectMember(_) => return None,
+            });
+        }
+        layout::offset_of(&typ, &members, self.ex)
+    }
+
+    fn fill(&self, acc: &mut Vec<(String, MyExpression)>, fname: &str, ini: &Initializer) {
+        match &ini {
+            Initializer::Expression(e) => acc.push((String::from(fname), self.transform(&e.node))),
+            Initializer::List(ls) => {
+                for l in ls {
+                    self.fill(acc, fname, &l.node.initializer.node)
+                }
+            }
+        }
+    }
+
+    /// The elements of the array `name` with the lengths `dims`
+    ///
+    /// Index designators (`[3] = 5`) and GNU range designators (`[1 ... 3] = 0`) place the
+    /// values at the given positions, the gaps are filled with zeros. The array is as long as
+    /// the declared length (the first of `dims`), or up to the largest initialized index if
+    /// none is given, large arrays with few elements set stay a `MyExpression::SparseArray`.
+    /// Elements outside of a known length are dropped with a warning, as are elements with a
+    /// negative index. If the `element` type is a known struct type, the braced elements are
+    /// structs and may be designated further like `[1].port = 80`, the elements not set are
+    /// zero structs.
+    fn array(
+        &self,
+        name: &str,
+        element: Option<&MyType>,
+        dims: &[Option<usize>],
+        items: &[Node<InitializerListItem>],
+    ) -> MyExpression {
+        let env = &|n: &str| self.lookup(n);
+        let record = match element.map(|t| t.resolve(&self.ex.typedefs)) {
+            Some(t) if dims.len() <= 1 && t.derived.is_empty() => t
+                .struct_name()
+                .filter(|s| self.ex.struct_types.contains_key(*s))
+                .map(String::from),
+            _ => None,
+        };
+        // The type of the array, to tell its subobjects
+        let typ = element.map(|t| {
+            let mut typ = t.clone();
+            for len in dims.iter().rev() {
+                typ.derived.push(Derived::Array(len.map(|l| l.to_string())));
+            }
+            typ
+        });
+        let braced;
+        let items = match &typ {
+            Some(t) => {
+                braced = self.braced(t, items);
+                &*braced
+            }
+            None => items,
+        };
+        // Only the set elements, `int big[] = { [100000000] = 1 };` must not allocate the gap
+        let mut elems: BTreeMap<usize, MyExpression> = BTreeMap::new();
+        let mut len = 0;
+        let mut next = 0;
+        for item in items {
+            let mut range = next..=next;
+            if let Some(d) = item.node.designation.first() {
+                let bounds = match &d.node {
+                    Designator::Index(e) => fold::eval(&e.node, env).map(|i| (i, i)),
+                    Designator::Range(r) => fold::eval(&r.node.from.node, env)
+                        .and_then(|from| Some((from, fold::eval(&r.node.to.node, env)?))),
+                    Designator::Member(_) => None,
+                };
+                match bounds {
+                    Some((from, _)) if from < 0 => {
+                        log::warn!(
+                            target: "c_ast::designator",
+                            "Ignoring element {} of {} with the negative index {}",
+                            cexpr::initializer(&item.node.initializer.node),
+                            name,
+                            from
+                        );
+                        continue;
+                    }
+                    Some((from, to)) if to >= from => {
+                        range = from as usize..=to as usize;
+                    }
+                    _ => {
+                        log::warn!(
+                            target: "c_ast::designator",
+                            "Ignoring array element with unsupported designator {}",
+                            cexpr::initializer(&item.node.initializer.node)
+                        );
+                        continue;
+                    }
+                }
+            }
+            next = range.end() + 1;
+            if let Some(&Some(len)) = dims.first() {
+                if *range.start() >= len {
+                    log::warn!(
+                        target: "c_ast::designator",
+                        "Index {} is out of the bounds of {}[{}], ignoring element {}",
+                        range.start(),
+                        name,
+                        len,
+                        cexpr::initializer(&item.node.initializer.node)
+                    );
+                    continue;
+                } else if *range.end() >= len {
+                    log::warn!(
+                        target: "c_ast::designator",
+                        "Index {} is out of the bounds of {}[{}], only filling up to {}",
+                        range.end(),
+                        name,
+                        len,
+                        len - 1
+                    );
+                    range = *range.start()..=len - 1;
+                }
+            }
+            len = len.max(range.end() + 1);
+            let inner = format!("{}[{}]", name, range.start());
+            if item.node.designation.len() > 1 {
+                // `[1].port = 80` only sets `port` of the struct at 1
+                let Some(record) = &record else {
+                    log::warn!(
+                        target: "c_ast::designator",
+                        "Ignoring {} of {} with unsupported nested designators",
+                        cexpr::initializer(&item.node.initializer.node),
+                        name
+                    );
+                    continue;
+                };
+                let mut rest = item.clone();
+                rest.node.designation.remove(0);
+                let value = self.new_struct(record, &inner, std::slice::from_ref(&rest));
+                for i in range {
+                    let merged = self.merge(elems.remove(&i), MyExpression::Struct(value.clone()));
+                    elems.insert(i, merged);
+                }
+                continue;
+            }
+            let value = match (&item.node.initializer.node, &record) {
+                (Initializer::Expression(e), _) => match element {
+                    Some(t) if dims.len() <= 1 => self.typed(t, &e.node),
+                    _ => self.transform(&e.node),
+                },
+                (Initializer::List(xs), Some(record)) => {
+                    MyExpression::Struct(self.new_struct(record, &inner, xs))
+                }
+                (Initializer::List(xs), None) => {
+                    self.array(&inner, element, dims.get(1..).unwrap_or(&[]), xs)
+                }
+            };
+            for i in range {
+                elems.insert(i, value.clone());
+            }
+        }
+        if let Some(&Some(declared)) = dims.first() {
+            len = len.max(declared);
+        }
+        if self.is_sparse(len, elems.len()) {
+            return MyExpression::SparseArray {
+                len,
+                entries: elems.into_iter().collect(),
+            };
+        }
+        let zero = |i| match (&record, element, &typ) {
+            (Some(_), Some(t), _) => self.zero(t, &format!("{}[{}]", name, i)),
+            (None, _, Some(t)) if dims.len() > 1 => {
+                let mut sub = t.clone();
+                sub.derived.pop();
+                self.zero(&sub, &format!("{}[{}]", name, i))
+            }
+            _ => MyExpression::Integer {
+                value: 0,
+                raw: String::from("0"),
+            },
+        };
+        MyExpression::Array(
+            (0..len)
+                .map(|i| elems.remove(&i).unwrap_or_else(|| zero(i)))
+                .collect(),
+        )
+    }
+
+    /// Insert the structs among the `elements` of the array of structs `name` as values of
+    /// their own, like `rows[1]`
+    fn insert_elements(&mut self, name: String, elements: MyExpression, attrs: &Attributes) {
+        match elements {
+            MyExpression::Struct(mst) => {
+                let val = MyValue::Struct(mst).with_attrs(attrs.clone());
+                self.insert_element(self.key_of(name), val);
+            }
+            MyExpression::Array(xs) => {
+                for (i, x) in xs.into_iter().enumerate() {
+                    self.insert_elements(format!("{}[{}]", name, i), x, attrs);
+                }
+            }
+            // The elements that aren't set are zero and left out
+            MyExpression::SparseArray { entries, .. } => {
+                for (i, x) in entries {
+                    self.insert_elements(format!("{}[{}]", name, i), x, attrs);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    /// Whether an array of `len` elements with `set` elements initialized is kept as
+    /// [`MyExpression::SparseArray`], if it has at least [`SPARSE_LEN`] elements and less
+    /// than a quarter of them are set
+    fn is_sparse(&self, len: usize, set: usize) -> bool {
+        !self.dense_arrays && len >= SPARSE_LEN && set * 4 < len
+    }
+}
+
+impl<'ast, 'a> Visit<'ast> for MyVisitor<'a> {
+    fn visit_translation_unit(&mut self, n: &'ast TranslationUnit) {
+        let mut tags = Tags(HashSet::new());
+        tags.visit_translation_unit(n);
+        self.tags = tags.0;
+        for (i, e) in n.0.iter().enumerate() {
+            if self.found() {
+                log::debug!(
+                    "Found {}, skipping the remaining {} external declarations",
+                    self.target.as_ref().map_or("", |t| t.0.as_str()),
+                    n.0.len() - i
+                );
+                self.ex.skipped += n.0.len() - i;
+                break;
+            }
+            let _at = self.locate(&e.span);
+            self.visit_external_declaration(&e.node, &e.span);
+        }
+        for name in self.system_typedefs.drain() {
+            self.ex.typedefs.remove(&name);
+        }
+    }
+
+    fn visit_function_definition(&mut self, n: &'ast FunctionDefinition, span: &'ast Span) {
+        if self.in_system_header(span) {
+            return;
+        }
+        let name = declarator_name(&n.declarator.node).unwrap_or("<function>");
+        let typ = MyType::from_declaration(&n.specifiers).with_declarator(&n.declarator.node);
+        self.add_function(name, typ, &n.specifiers);
+        self.enter_scope(String::from(name));
+        if let Statement::Compound(items) = &n.statement.node {
+            // The function body is the function scope itself, not a nested block
+            for s in &n.specifiers {
+                self.visit_declaration_specifier(&s.node, &s.span);
+            }
+            self.visit_declarator(&n.declarator.node, &n.declarator.span);
+            for d in &n.declarations {
+                self.visit_declaration(&d.node, &d.span);
+            }
+            for item in items {
+                self.visit_block_item(&item.node, &item.span);
+            }
+        } else {
+            visit::visit_function_definition(self, n, span);
+        }
+        self.leave_scope();
+    }
+
+    fn visit_statement(&mut self, n: &'ast Statement, span: &'ast Span) {
+        if let Statement::Compound(_) = n {
+            let idx = self.blocks.last().copied().unwrap_or(0);
+            if let Some(b) = self.blocks.last_mut() {
+                *b += 1;
+            }
+            self.enter_scope(format!("{{{}}}", idx));
+            visit::visit_statement(self, n, span);
+            self.leave_scope();
+        } else {
+            visit::visit_statement(self, n, span);
+        }
+    }
+
+    fn visit_declaration(&mut self, n: &'ast Declaration, span: &'ast Span) {
+        self.anonymous_name = n
+            .declarators
+            .iter()
+            .find_map(|d| declarator_name(&d.node.declarator.node))
+            .map(String::from);
+        self.last_anonymous = None;
+        for s in &n.specifiers {
+            self.visit_declaration_specifier(&s.node, &s.span);
+        }
+        self.anonymous_name = None;
+        let mut typ = MyType::from_declaration(&n.specifiers);
+        self.name_anonymous(&mut typ);
+        if n.specifiers.iter().any(|s| {
+            matches!(&s.node, DeclarationSpecifier::StorageClass(sc)
+                     if sc.node == StorageClassSpecifier::Typedef)
+        }) {
+            let system = self.in_system_header(span);
+            for d in &n.declarators {
+                let d = &d.node.declarator.node;
+                if let Some(name) = declarator_name(d) {
+                    let target = self.apply_declarator(&typ, d);
+                    self.ex.typedefs.insert(String::from(name), target);
+                    match system {
+                        true => self.system_typedefs.insert(String::from(name)),
+                        false => self.system_typedefs.remove(name),
+                    };
+                }
+            }
+            return;
+        }
+        if self.in_system_header(span) {
+            return;
+        }
+        let storage = ctype::storage_specifiers(&n.specifiers);
+        let is_static = storage.iter().any(|s| s == "static");
+        let skip = match self.locals {
+            _ if self.scope.is_empty() => false,
+            Locals::None => true,
+            Locals::Static => !is_static,
+            Locals::All => false,
+        };
+        if skip {
+            // They still shadow the declarations of the outer scopes
+            for d in &n.declarators {
+                if let Some(name) = declarator_name(&d.node.declarator.node) {
+                    self.declare(name, None);
+                }
+            }
+            return;
+        }
+        let resolved = typ.resolve(&self.ex.typedefs);
+        self.cur_struct = resolved.struct_name().map(String::from);
+        self.cur_const = resolved.qualifiers.iter().any(|q| q == "const");
+        self.cur_attrs = Attributes::from_declaration_specifiers(&n.specifiers, self.ex);
+        self.cur_type = typ.clone();
+        self.cur_storage = storage;
+        for d in &n.declarators {
+            if let Some(name) = declarator_name(&d.node.declarator.node) {
+                self.add_function(
+                    name,
+                    typ.clone().with_declarator(&d.node.declarator.node),
+                    &n.specifiers,
+                );
+            }
+            self.visit_init_declarator(&d.node, &d.span);
+        }
+    }
+
+    fn visit_struct_type(&mut self, n: &'ast StructType, span: &'ast Span) {
+        if self.in_system_header(span) {
+            self.cur_struct = None;
+            return;
+        }
+        let name = match (&n.identifier, &n.declarations) {
+            (Some(id), _) => id.node.name.clone(),
+            (None, Some(_)) => self.anonymous(),
+            (None, None) => {
+                self.cur_struct = None;
+                return visit::visit_struct_type(self, n, span);
+            }
+        };
+        self.cur_struct = Some(name.clone());
+        if n.declarations.is_some() {
+            if let Some(known) = self.ex.struct_types.remove(&name) {
+                // Collect the fields again to compare them, but keep the first definition
+                let mut st = MyStructType::new(&name);
+                st.union = n.kind.node == StructKind::Union;
+                st.anonymous = known.anonymous;
+                self.ex.struct_types.insert(name.clone(), st);
+                visit::visit_struct_type(self, n, span);
+                let again = self.ex.struct_types.insert(name.clone(), known);
+                let known = &self.ex.struct_types[&name];
+                match again {
+                    Some(again) if !known.same_fields(&again) => log::warn!(
+                        target: "c_ast::redefinition",
+                        "{} {} is redefined as `{}`, keeping `{}`",
+                        known.keyword(),
+                        name,
+                        again.declaration(),
+                        known.declaration()
+                    ),
+                    _ => log::debug!("struct {} is already known, not collecting it again", name),
+                }
+            } else {
+                let mut st = MyStructType::new(&name);
+                st.union = n.kind.node == StructKind::Union;
+                st.anonymous = n.identifier.is_none();
+                self.ex.struct_types.insert(name.clone(), st);
+                visit::visit_struct_type(self, n, span);
+                self.collected(&name);
+            }
+            if n.identifier.is_none() {
+                self.last_anonymous = Some(name);
+            }
+            return;
+        }
+        visit::visit_struct_type(self, n, span);
+    }
+
+    fn visit_enum_type(&mut self, n: &'ast EnumType, span: &'ast Span) {
+        let anonymous = match n.identifier {
+            None if !n.enumerators.is_empty() => Some(self.anonymous()),
+            _ => None,
+        };
+        let mut constants = Vec::new();
+        let mut next = Some(0);
+        for e in &n.enumerators {
+            let value = match &e.node.expression {
+                Some(x) => fold::eval(&x.node, &|n| self.lookup(n)),
+                None => next,
+            };
+            let name = &e.node.identifier.node.name;
+            // The constants are in scope for the following enumerators and initializers,
+            // e.g. `int flags = A | B;`
+            let symbol = value.map(|value| MyExpression::Integer {
+                value,
+                raw: name.clone(),
+            });
+            self.declare(name, symbol);
+            constants.push((name.clone(), value));
+            next = value.and_then(|v| v.checked_add(1));
+        }
+        let name = match (&n.identifier, &anonymous) {
+            (Some(id), _) => Some(&id.node.name),
+            (None, a) => a.as_ref(),
+        };
+        let mut underlying = self
+            .enum_bases
+            .get(&span.start)
+            .map(|b| ctype::normalize(b));
+        if let Some(name) = name {
+            match &underlying {
+                Some(b) => {
+                    self.ex.enum_bases.insert(name.clone(), b.clone());
+                }
+                None => underlying = self.ex.enum_bases.get(name).cloned(),
+            }
+        }
+        if self.in_system_header(span) {
+            // The constants are still needed to evaluate the other initializers
+        } else if let (Some(name), false) = (name, n.enumerators.is_empty()) {
+            self.ex
+                .enum_types
+                .entry(name.clone())
+                .or_insert(MyEnumType {
+                    name: name.clone(),
+                    anonymous: anonymous.is_some(),
+                    constants,
+                    underlying,
+                });
+        }
+        if anonymous.is_some() {
+            self.last_anonymous = anonymous;
+        }
+        visit::visit_enum_type(self, n, span);
+    }
+
+    fn visit_struct_field(&mut self, n: &'ast StructField, span: &'ast Span) {
+        let _at = self.locate(span);
+        if let Some(struct_name) = self.cur_struct.clone() {
+            let member = n.declarators.iter().find_map(|d| {
+                let x = d.node.declarator.as_ref()?;
+                declarator_name(&x.node)
+            });
+            let anonymous = match member {
+                None if is_anonymous_member(n) => {
+                    let count = self
+                        .ex
+                        .struct_types
+                        .get(&struct_name)
+                        .map_or(0, |st| st.fields.iter().filter(|f| f.anonymous).count());
+                    Some(format!("anonymous_{}", count + 1))
+                }
+                _ => None,
+            };
+            // The types declared by the specifiers, e.g. `struct { int x, y; } pos;`
+            if let Some(member) = member.or(anonymous.as_deref()) {
+                self.anonymous_name = Some(format!("{}_{}", struct_name, member));
+                self.last_anonymous = None;
+                for s in &n.specifiers {
+                    self.visit_specifier_qualifier(&s.node, &s.span);
+                }
+                self.anonymous_name = None;
+                self.cur_struct = Some(struct_name.clone());
+            }
+            let mut base = MyType::from_specifier_qualifiers(&n.specifiers);
+            self.name_anonymous(&mut base);
+            let base_attrs = Attributes::from_specifier_qualifiers(&n.specifiers, self.ex);
+            for declarator in &n.declarators {
+                if let Some(x) = &declarator.node.declarator {
+                    if let Some(name) = declarator_name(&x.node) {
+                        let doc = self.source.as_ref().and_then(|s| s.comment(x.span.start));
+                        let attrs = base_attrs.clone().with_declarator(&x.node, self.ex);
+                        let typ = self.apply_declarator(&base, &x.node);
+                        self.ex
+                            .struct_types
+                            .entry(struct_name.clone())
+                            .or_insert(MyStructType::new(&struct_name))
+                            .fields
+                            .push(MyField {
+                                name: String::from(name),
+                                typ,
+                                attrs,
+                                bit_width: declarator
+                                    .node
+                                    .bit_width
+                                    .as_ref()
+                                    .map(|w| cexpr::render(&w.node)),
+                                doc,
+                                anonymous: false,
+                            });
+                    }
+                }
+            }
+            if let Some(name) = anonymous {
+                let doc = self.source.as_ref().and_then(|s| s.comment(span.start));
+                self.ex
+                    .struct_types
+                    .entry(struct_name.clone())
+                    .or_insert(MyStructType::new(&struct_name))
+                    .fields
+                    .push(MyField {
+                        name,
+                        typ: base,
+                        attrs: base_attrs,
+                        bit_width: None,
+                        doc,
+                        anonymous: true,
+                    });
+            }
+        } else {
+            log::warn!(
+                target: "c_ast::no_struct",
+                "I visit struct fields but I don't know in which struct I am!\n {:#?}",
+                n
+            )
+        }
+    }
+
+    fn visit_init_declarator(&mut self, n: &'ast InitDeclarator, span: &'ast Span) {
+        let _at = self.locate(span);
+        if let (Some(name), true) = (declarator_name(&n.declarator.node), self.cur_const) {
+            self.constants.insert(self.key(name));
+        }
+        if let Some(name) = declarator_name(&n.declarator.node) {
+            let external = match (&n.initializer, self.externs) {
+                (None, Some(externs)) if self.cur_storage.iter().any(|s| s == "extern") => {
+                    externs.get(name).cloned()
+                }
+                _ => None,
+            };
+            self.declare(name, external);
+        }
+        let (name, ini) = match (declarator_name(&n.declarator.node), &n.initializer) {
+            (Some(name), Some(ini)) => (name, ini),
+            (None, Some(ini)) => {
+                log::warn!(
+                    target: "c_ast::unnamed",
+                    "Skipping initializer {} of a declarator without a name",
+                    cexpr::initializer(&ini.node)
+                );
+                return;
+            }
+            _ => return,
+        };
+        let attrs = self
+            .cur_attrs
+            .clone()
+            .with_declarator(&n.declarator.node, self.ex);
+        let typ = self.apply_declarator(&self.cur_type, &n.declarator.node);
+        // Not for pointers to structs, e.g. `const struct cmd *commands[]`. Taken for the
+        // declarator instead of cloned, it is put back for the next one.
+        let cur_struct = self.cur_struct.take();
+        let structs = match &cur_struct {
+            Some(s)
+                if typ
+                    .resolve(&self.ex.typedefs)
+                    .derived
+                    .iter()
+                    .all(|d| matches!(d, Derived::Array(_))) =>
+            {
+                Some(s.as_str())
+            }
+            _ => None,
+        };
+        match &ini.node {
+            Initializer::List(xs) => {
+                if let Some(struct_name) = structs {
+                    if is_array(&n.declarator.node) {
+                        // Each struct is a value of its own, at the index it is designated to
+                        let dims = array_dims(&n.declarator.node, &|e| self.eval(e));
+                        let element = element_type(&typ);
+                        let elements = self.array(name, Some(&element), &dims, xs);
+                        self.insert_elements(String::from(name), elements, &attrs);
+                    } else {
+                        let mst = self.new_struct(struct_name, name, xs);
+                        self.insert(self.key(name), MyValue::Struct(mst).with_attrs(attrs));
+                    }
+                } else if is_array(&n.declarator.node) {
+                    let dims = array_dims(&n.declarator.node, &|e| self.eval(e));
+                    let element = element_type(&typ);
+                    let value = self.array(name, Some(&element), &dims, xs);
+                    let val = MyValue::new_scalar(name, value);
+                    self.insert(self.key(name), val.with_attrs(attrs));
+                } else if typ.struct_name().is_none() {
+                    // A scalar in braces like `int x = {5};`, or `{}` for zero
+                    let value = self.field_value(&typ, name, &ini.node);
+                    let val = MyValue::new_scalar(name, value);
+                    self.insert(self.key(name), val.with_attrs(attrs));
+                }
+            }
+            Initializer::Expression(e) => {
+                let value = self.typed(&typ, &e.node);
+                self.check_range(name, &typ, &value);
+                if let (MyExpression::Other { .. }, Some(f)) =
+                    (&value, floating(&e.node, &|n| self.lookup(n)))
+                {
+                    log::warn!(
+                        target: "c_ast::unresolved",
+                        "{} = {} isn't folded, arithmetic with the floating constant {} isn't \
+                         supported",
+                        name,
+                        cexpr::render(&e.node),
+                        f
+                    );
+                }
+                let unknown = matches!(
+                    value,
+                    MyExpression::Other {
+                        category: OtherCategory::UnknownIdentifier,
+                        ..
+                    }
+                );
+                if unknown && is_access(&e.node) {
+                    log::warn!(
+                        target: "c_ast::unresolved",
+                        "{} = {} isn't resolved, there is no known value of a const struct or \
+                         array declared before",
+                        name,
+                        cexpr::render(&e.node)
+                    );
+                } else if unknown && self.cur_const && n.declarator.node.derived.is_empty() {
+                    log::warn!(
+                        target: "c_ast::unresolved",
+                        "const {} = {} refers to an identifier that isn't a constant declared \
+                         before",
+                        name,
+                        cexpr::render(&e.node)
+                    );
+                }
+                if self.cur_const && n.declarator.node.derived.is_empty() {
+                    match &value {
+                        MyExpression::Integer { .. }
+                        | MyExpression::Float(_)
+                        | MyExpression::Bool(_) => {
+                            self.declare(name, Some(value.clone()));
+                            if self.scope.is_empty()
+                                && !self.cur_storage.iter().any(|s| s == "static")
+                            {
+                                self.definitions
+                                    .entry(String::from(name))
+                                    .or_insert_with(|| value.clone());
+                            }
+                        }
+                        _ => (),
+                    }
+                }
+                let val = MyValue::new_scalar(name, value).with_attrs(attrs);
+                self.insert(self.key(name), val);
+            }
+        }
+        self.cur_struct = cur_struct;
+    }
+}
+
+/// The name and indices of a segment of a member access like `servers[1][0]`
+fn indexed(segment: &str) -> Option<(&str, Vec<usize>)> {
+    let (name, mut rest) = match segment.find('[') {
+        Some(at) => segment.split_at(at),
+        None => (segment, ""),
+    };
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return None;
+    }
+    let mut indices = Vec::new();
+    while !rest.is_empty() {
+        let (index, after) = rest.strip_prefix('[')?.split_once(']')?;
+        indices.push(index.parse().ok()?);
+        rest = after;
+    }
+    Some((name, indices))
+}
+
+fn element_at(e: MyExpression, i: usize) -> Option<MyExpression> {
+    match e {
+        MyExpression::Array(xs) => xs.into_iter().nth(i),
+        MyExpression::SparseArray { len, entries } if i < len => {
+            Some(entries.into_iter().find(|(at, _)| *at == i).map_or_else(
+                || MyExpression::Integer {
+                    value: 0,
+                    raw: String::from("0"),
+                },
+                |(_, e)| e,
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// The type of the elements of the array type `typ`, of all its dimensions
+fn element_type(typ: &MyType) -> MyType {
+    let mut element = typ.clone();
+    while let Some(Derived::Array(_)) = element.derived.last() {
+        element.derived.pop();
+    }
+    element
+}
+
+fn is_array(d: &Declarator) -> bool {
+    d.derived
+        .iter()
+        .any(|d| matches!(d.node, DerivedDeclarator::Array(_)))
+}
+
+/// Collects the tags of the struct, union and enum types of a translation unit
+struct Tags(HashSet<String>);
+
+impl<'ast> Visit<'ast> for Tags {
+    fn visit_struct_type(&mut self, n: &'ast StructType, span: &'ast Span) {
+        if let Some(id) = &n.identifier {
+            self.0.insert(id.node.name.clone());
+        }
+        visit::visit_struct_type(self, n, span);
+    }
+
+    fn visit_enum_type(&mut self, n: &'ast EnumType, span: &'ast Span) {
+        if let Some(id) = &n.identifier {
+            self.0.insert(id.node.name.clone());
+        }
+        visit::visit_enum_type(self, n, span);
+    }
+}
+
+/// Whether `n` is an anonymous struct or union member like `union { int i; float f; };`
+fn is_anonymous_member(n: &StructField) -> bool {
+    n.declarators.is_empty()
+        && n.specifiers.iter().any(|s| match &s.node {
+            SpecifierQualifier::TypeSpecifier(t) => matches!(&t.node,
+                TypeSpecifier::Struct(st) if st.node.identifier.is_none()
+                    && st.node.declarations.is_some()),
+            _ => false,
+        })
+}
+
+/// Whether `items` is `{0}` or `{}`, which zero the whole aggregate
+fn zero_init(items: &[Node<InitializerListItem>]) -> bool {
+    match items {
+        [] => true,
+        [x] if x.node.designation.is_empty() => match &x.node.initializer.node {
+            Initializer::Expression(e) => matches!(&e.node,
+                Expression::Constant(c) if matches!(&c.node,
+                    Constant::Integer(i) if fold::integer(i) == Some(0))),
+            Initializer::List(_) => false,
+        },
+        _ => false,
+    }
+}
+
+fn is_char(typ: &MyType) -> bool {
+    typ.specifiers.iter().any(|s| s == "char")
+}
+
+/// The declared identifier of `d`
+///
+/// Pointers and their qualifiers are derived declarators of `d` itself, only parentheses like
+/// in `char *(*p)[2]` nest the identifier in further declarators.
+fn declarator_name(d: &Declarator) -> Option<&str> {
+    match &d.kind.node {
+        DeclaratorKind::Identifier(id) => Some(&id.node.name),
+        DeclaratorKind::Declarator(inner) => declarator_name(&inner.node),
+        DeclaratorKind::Abstract => None,
+    }
+}
+
+/// The declared lengths of the array dimensions of `d` as `eval` evaluates them, outermost
+/// first
+fn array_dims(d: &Declarator, eval: &dyn Fn(&Expression) -> Option<i128>) -> Vec<Option<usize>> {
+    d.derived
+        .iter()
+        .filter_map(|dd| match &dd.node {
+            DerivedDeclarator::Array(a) => Some(match &a.node.size {
+                ArraySize::VariableExpression(e) | ArraySize::StaticExpression(e) => {
+                    eval(&e.node).map(|l| l as usize)
+                }
+                _ => None,
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The built-in transformation of an initializer expression into a value
+///
+/// Identifiers are resolved through `env`.
+pub fn transform(expr: &Expression, env: &fold::Env) -> MyExpression {
+    match expr {
+        Expression::Identifier(id) => match env(&id.node.name) {
+            Some(MyExpression::Integer { value, .. }) => MyExpression::Integer {
+                value,
+                raw: id.node.name.clone(),
+            },
+            Some(e) => e,
+            None => MyExpression::Other {
+                category: OtherCategory::UnknownIdentifier,
+                raw: id.node.name.clone(),
+            },
+        },
+        Expression::Constant(a) => match &a.node {
+            Constant::Integer(b) => integer(b),
+            Constant::Float(b) if b.suffix.imaginary => MyExpression::Other {
+                category: OtherCategory::Unsupported,
+                raw: cexpr::constant(&a.node),
+            },
+            Constant::Float(_) => MyExpression::Float(cexpr::constant(&a.node)),
+            Constant::Character(b) => match literal::multi_char(b) {
+                Some(value) => MyExpression::Integer {
+                    value,
+                    raw: String::from(b),
+                },
+                None => MyExpression::String(String::from(b)),
+            },
+        },
+        Expression::StringLiteral(a) => match literal::encoding(&a.node) {
+            Some(encoding) => MyExpression::WideString {
+                encoding,
+                raw: a.node.clone(),
+                units: literal::string_units(&a.node, encoding),
+            },
+            None => MyExpression::StringLiteral {
+                raw: a.node.clone(),
+                bytes: literal::string_bytes(&a.node),
+            },
+        },
+        Expression::UnaryOperator(u) if u.node.operator.node == UnaryOperator::Address => {
+            MyExpression::AddressOf(cexpr::render(&u.node.operand.node))
+        }
+        Expression::Cast(c) if is_null(&c.node, env) => MyExpression::Null,
+        a if is_access(a) => access(a, env),
+        Expression::GenericSelection(g) => match fold::select(&g.node) {
+            Some(e) => transform(e, env),
+            None => MyExpression::Other {
+                category: OtherCategory::Unsupported,
+                raw: cexpr::render(expr),
+            },
+        },
+        a @ (Expression::UnaryOperator(_)
+        | Expression::BinaryOperator(_)
+        | Expression::Conditional(_)
+        | Expression::Cast(_)
+        | Expression::Call(_)) => match fold::eval(a, env) {
+            Some(value) => MyExpression::Integer {
+                value,
+                raw: cexpr::render(a),
+            },
+            // Complex arithmetic isn't folded, but should at least be readable
+            None if imaginary(a) => MyExpression::Other {
+                category: OtherCategory::Unsupported,
+                raw: cexpr::render(a),
+            },
+            // Neither is the arithmetic with floating constants, which are kept as written
+            None if floating(a, env).is_some() => MyExpression::Other {
+                category: OtherCategory::Unsupported,
+                raw: cexpr::render(a),
+            },
+            None => match a {
+                Expression::Call(c) => call(&c.node, env),
+                _ => MyExpression::Other {
+                    category: categorize(a),
+                    raw: cexpr::render(a),
+                },
+            },
+        },
+        a => MyExpression::Other {
+            category: categorize(a),
+            raw: cexpr::render(a),
+        },
+    }
+}
+
+/// Whether `expr` is a member access or an index like `cfg.rows[i].x`
+fn is_access(expr: &Expression) -> bool {
+    match expr {
+        Expression::Member(_) => true,
+        Expression::BinaryOperator(b) => b.node.operator.node == BinaryOperator::Index,
+        _ => false,
+    }
+}
+
+/// The value of the member access or index `expr` like `cfg.rows[i].x`, see [`fold::path`]
+fn access(expr: &Expression, env: &fold::Env) -> MyExpression {
+    match fold::path(expr, env).and_then(|p| env(&p)) {
+        Some(MyExpression::Integer { value, .. }) => MyExpression::Integer {
+            value,
+            raw: cexpr::render(expr),
+        },
+        Some(e) => e,
+        None => MyExpression::Other {
+            category: OtherCategory::UnknownIdentifier,
+            raw: cexpr::render(expr),
+        },
+    }
+}
+
+/// A call by name with the transformed arguments, else it isn't readable as a value
+fn call(c: &CallExpression, env: &fold::Env) -> MyExpression {
+    match &c.callee.node {
+        Expression::Identifier(id) => MyExpression::Call {
+            name: id.node.name.clone(),
+            args: c
+                .arguments
+                .iter()
+                .map(|a| transform(&a.node, env))
+                .collect(),
+        },
+        _ => MyExpression::Other {
+            category: OtherCategory::NonConstant,
+            raw: cexpr::render(&c.callee.node) + "(...)",
+        },
+    }
+}
+
+/// Whether `c` is the expansion of `NULL`, `(void *)0`
+fn is_null(c: &CastExpression, env: &fold::Env) -> bool {
+    let typ = MyType::from_type_name(&c.type_name.node);
+    typ.specifiers == ["void"]
+        && typ.derived.len() == 1
+        && matches!(typ.derived[0], Derived::Pointer(_))
+        && fold::eval(&c.expression.node, env) == Some(0)
+}
+
+fn integer(i: &Integer) -> MyExpression {
+    let raw = cexpr::integer(i);
+    if i.suffix.imaginary {
+        return MyExpression::Other {
+            category: OtherCategory::Unsupported,
+            raw,
+        };
+    }
+    match fold::integer(i) {
+        Some(value) => MyExpression::Integer { value, raw },
+        None => MyExpression::Other {
+            category: OtherCategory::Unsupported,
+            raw: format!("{} (out of range)", raw),
+        },
+    }
+}
+
+/// Whether `expr` contains an imaginary constant like `2.0i`, as `I` of `complex.h` does
+fn imaginary(expr: &Expression) -> bool {
+    match expr {
+        Expression::Constant(c) => match &c.node {
+            Constant::Integer(i) => i.suffix.imaginary,
+            Constant::Float(f) => f.suffix.imaginary,
+            Constant::Character(_) => false,
+        },
+        Expression::UnaryOperator(u) => imaginary(&u.node.operand.node),
+        Expression::BinaryOperator(b) => imaginary(&b.node.lhs.node) || imaginary(&b.node.rhs.node),
+        Expression::Cast(c) => imaginary(&c.node.expression.node),
+        Expression::Conditional(c) => {
+            imaginary(&c.node.then_expression.node) || imaginary(&c.node.else_expression.node)
+        }
+        _ => false,
+    }
+}
+
+/// The first identifier in `expr` that `env` knows as a floating constant, e.g. `SCALE` of
+/// `SCALE * 2` with `const double SCALE = 1.5;`
+fn floating<'e>(expr: &'e Expression, env: &fold::Env) -> Option<&'e str> {
+    match expr {
+        Expression::Identifier(id) => match env(&id.node.name) {
+            Some(MyExpression::Float(_)) => Some(&id.node.name),
+            _ => None,
+        },
+        Expression::UnaryOperator(u) => floating(&u.node.operand.node, env),
+        Expression::BinaryOperator(b) => {
+            floating(&b.node.lhs.node, env).or_else(|| floating(&b.node.rhs.node, env))
+        }
+        Expression::Cast(c) => floating(&c.node.expression.node, env),
+        Expression::Conditional(c) => floating(&c.node.condition.node, env)
+            .or_else(|| floating(&c.node.then_expression.node, env))
+            .or_else(|| floating(&c.node.else_expression.node, env)),
+        _ => None,
+    }
+}
+
+fn is_builtin(callee: &Expression) -> bool {
+    matches!(callee, Expression::Identifier(id) if id.node.name.starts_with("__builtin_"))
+}
+
+fn categorize(expr: &Expression) -> OtherCategory {
+    use OtherCategory::*;
+    let worst = |exprs: &[&Expression]| {
+        exprs
+            .iter()
+            .map(|e| categorize(e))
+            .fold(Unsupported, |acc, c| match (acc, c) {
+                (NonConstant, _) | (_, NonConstant) => NonConstant,
+                (UnknownIdentifier, _) | (_, UnknownIdentifier) => UnknownIdentifier,
+                _ => Unsupported,
+            })
+    };
+    match expr {
+        Expression::Identifier(_) => UnknownIdentifier,
+        // Other builtins may well be constant
+        Expression::Call(c) if is_builtin(&c.node.callee.node) => Unsupported,
+        Expression::Call(_)
+        | Expression::Comma(_)
+        | Expression::Statement(_)
+        | Expression::VaArg(_) => NonConstant,
+        Expression::UnaryOperator(u) => match u.node.operator.node {
+            UnaryOperator::PostIncrement
+            | UnaryOperator::PostDecrement
+            | UnaryOperator::PreIncrement
+            | UnaryOperator::PreDecrement => NonConstant,
+            _ => worst(&[&u.node.operand.node]),
+        },
+        Expression::BinaryOperator(b) => match b.node.operator.node {
+            BinaryOperator::Assign
+            | BinaryOperator::AssignMultiply
+            | BinaryOperator::AssignDivide
+            | BinaryOperator::AssignModulo
+            | BinaryOperator::AssignPlus
+            | BinaryOperator::AssignMinus
+            | BinaryOperator::AssignShiftLeft
+            | BinaryOperator::AssignShiftRight
+            | BinaryOperator::AssignBitwiseAnd
+            | BinaryOperator::AssignBitwiseXor
+            | BinaryOperator::AssignBitwiseOr => NonConstant,
+            _ => worst(&[&b.node.lhs.node, &b.node.rhs.node]),
+        },
+        Expression::Cast(c) => worst(&[&c.node.expression.node]),
+        Expression::Conditional(c) => worst(&[
+            &c.node.condition.node,
+            &c.node.then_expression.node,
+            &c.node.else_expression.node,
+        ]),
+        _ => Unsupported,
+    }
+}
@@ -0,0 +1,203 @@
+//! Checks for suspicious code that is valid C, reported as warnings
+
+use crate::comments::Source;
+use crate::{diag, tree, Extraction, Location, MyValue, ValueKey};
+use lang_c::ast::*;
+use lang_c::span::Span;
+use lang_c::visit::{self, Visit};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Warn about octal literals like `010` that would mean another value as decimal
+///
+/// `source` is the preprocessed text `unit` was parsed from, used to report the locations,
+/// their files as `shown` maps them.
+pub fn octal(unit: &TranslationUnit, source: &str, shown: &dyn Fn(&Path) -> PathBuf) {
+    Octal {
+        source: Source::new(source),
+        shown,
+    }
+    .visit_translation_unit(unit);
+}
+
+struct Octal<'s> {
+    source: Source<'s>,
+    shown: &'s dyn Fn(&Path) -> PathBuf,
+}
+
+impl<'ast> Visit<'ast> for Octal<'_> {
+    fn visit_integer(&mut self, n: &'ast Integer, span: &'ast Span) {
+        if n.base == IntegerBase::Octal && !n.number.is_empty() {
+            let octal = i128::from_str_radix(&n.number, 8).ok();
+            let decimal = n.number.parse::<i128>().ok();
+            if let (Some(o), Some(d)) = (octal, decimal) {
+                if o != d {
+                    let at = self
+                        .source
+                        .location(span.start)
+                        .map(|(file, line)| Location {
+                            file: (self.shown)(&file),
+                            line,
+                        });
+                    let _at = diag::locate(at);
+                    log::warn!(
+                        target: "c_ast::octal",
+                        "0{} is octal, i.e. {} and not {}",
+                        n.number,
+                        o,
+                        d
+                    );
+                }
+            }
+        }
+        visit::visit_integer(self, n, span);
+    }
+}
+
+/// Add the names of the struct and union types `unit` uses to `used`
+///
+/// A type is used if something is declared with it, e.g. a variable, a field, a typedef or a
+/// parameter, or if it is named in a cast or `sizeof`. Neither its own definition nor pointers
+/// to it from its own fields count.
+pub fn struct_uses(unit: &TranslationUnit, used: &mut HashSet<String>) {
+    Uses {
+        used,
+        defining: Vec::new(),
+        counting: false,
+    }
+    .visit_translation_unit(unit);
+}
+
+/// Warn about the struct types of `ex` that aren't in `used`, see [`struct_uses`]
+///
+/// Anonymous struct types are used where they are declared, they aren't reported.
+pub fn unused_structs(ex: &Extraction, used: &HashSet<String>) {
+    let mut unused: Vec<_> = ex
+        .struct_types
+        .values()
+        .filter(|st| !st.anonymous && !used.contains(&st.name))
+        .collect();
+    unused.sort_by_key(|st| &st.name);
+    for st in unused {
+        log::warn!(
+            target: "c_ast::unused",
+            "{} {} is never used",
+            st.keyword(),
+            st.name
+        );
+    }
+}
+
+struct Uses<'u> {
+    used: &'u mut HashSet<String>,
+    /// The struct types whose fields are visited, innermost last
+    defining: Vec<String>,
+    /// Whether the type specifiers visited declare something
+    counting: bool,
+}
+
+impl Uses<'_> {
+    fn counting<F: FnOnce(&mut Self)>(&mut self, counting: bool, f: F) {
+        let outer = std::mem::replace(&mut self.counting, counting);
+        f(self);
+        self.counting = outer;
+    }
+}
+
+impl<'ast> Visit<'ast> for Uses<'_> {
+    fn visit_declaration(&mut self, n: &'ast Declaration, span: &'ast Span) {
+        self.counting(!n.declarators.is_empty(), |v| {
+            visit::visit_declaration(v, n, span)
+        });
+    }
+
+    fn visit_struct_field(&mut self, n: &'ast StructField, span: &'ast Span) {
+        self.counting(!n.declarators.is_empty(), |v| {
+            visit::visit_struct_field(v, n, span)
+        });
+    }
+
+    fn visit_parameter_declaration(&mut self, n: &'ast ParameterDeclaration, span: &'ast Span) {
+        self.counting(true, |v| visit::visit_parameter_declaration(v, n, span));
+    }
+
+    fn visit_type_name(&mut self, n: &'ast TypeName, span: &'ast Span) {
+        self.counting(true, |v| visit::visit_type_name(v, n, span));
+    }
+
+    fn visit_struct_type(&mut self, n: &'ast StructType, span: &'ast Span) {
+        let name = match &n.identifier {
+            Some(id) => &id.node.name,
+            None => return visit::visit_struct_type(self, n, span),
+        };
+        if self.counting && !self.defining.contains(name) {
+            self.used.insert(name.clone());
+        }
+        if n.declarations.is_some() {
+            self.defining.push(name.clone());
+            visit::visit_struct_type(self, n, span);
+            self.defining.pop();
+        }
+    }
+}
+
+/// Keep the file scope values defined the same in several of the `files` only once, and warn
+/// about those defined differently
+///
+/// A value is kept in the first of the `files` (the shown paths in the order they were
+/// visited) it is defined in, e.g. a `const` of a header all files include. A value with the
+/// same name but another value, e.g. with another type or initializer, is kept in both files.
+pub fn duplicates(ex: &mut Extraction, files: &[PathBuf]) {
+    let order = |k: &ValueKey| files.iter().position(|f| k.file.as_ref() == Some(f));
+    let mut keys: Vec<ValueKey> = ex
+        .values
+        .keys()
+        .filter(|k| k.scope.is_empty())
+        .cloned()
+        .collect();
+    keys.sort_by_key(|k| order(k));
+    let mut first: HashMap<&str, Vec<&ValueKey>> = HashMap::new();
+    let mut repeated = Vec::new();
+    for k in &keys {
+        let seen = first.entry(&k.name).or_default();
+        if seen.iter().any(|s| same(&ex.values[*s], &ex.values[k])) {
+            repeated.push(k);
+            continue;
+        }
+        if let Some(other) = seen.first() {
+            log::warn!(
+                target: "c_ast::conflict",
+                "{} = {} in {} conflicts with {} = {} in {}",
+                k.name,
+                text(&ex.values[*other]),
+                location(other),
+                k.name,
+                text(&ex.values[k]),
+                location(k)
+            );
+        }
+        seen.push(k);
+    }
+    let repeated: Vec<ValueKey> = repeated.into_iter().cloned().collect();
+    for k in repeated {
+        ex.values.remove(&k);
+    }
+}
+
+fn same(a: &MyValue, b: &MyValue) -> bool {
+    serde_json::to_value(a).ok() == serde_json::to_value(b).ok()
+}
+
+fn text(v: &MyValue) -> String {
+    match v {
+        MyValue::Struct(s) => format!("struct {} {{...}}", s.typ),
+        MyValue::Scalar { value, .. } => tree::text(value),
+    }
+}
+
+fn location(k: &ValueKey) -> String {
+    match &k.file {
+        Some(f) => f.display().to_string(),
+        None => String::from("<input>"),
+    }
+}
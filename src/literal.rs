@@ -0,0 +1,240 @@
+//! Decoding of character constants and string literals
+
+use crate::Encoding;
+use std::convert::TryFrom;
+
+/// One element of a decoded literal: either a source character or the value of an escape
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Unit {
+    Char(char),
+    Escape(u32),
+}
+
+/// Decode the escape sequences of a literal body (without the quotes)
+///
+/// As in C, a hexadecimal escape consumes as many hex digits as follow, an octal escape
+/// at most three digits. Returns `None` on an invalid escape.
+pub fn unescape(body: &str) -> Option<Vec<Unit>> {
+    let mut units = Vec::new();
+    let mut chars = body.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            units.push(Unit::Char(c));
+            continue;
+        }
+        let e = match chars.next()? {
+            'n' => 0x0a,
+            't' => 0x09,
+            'r' => 0x0d,
+            'a' => 0x07,
+            'b' => 0x08,
+            'f' => 0x0c,
+            'v' => 0x0b,
+            'e' => 0x1b,
+            c @ ('\\' | '\'' | '"' | '?') => c as u32,
+            'x' => {
+                let mut v: u32 = 0;
+                let mut n = 0;
+                while let Some(d) = chars.peek().and_then(|c| c.to_digit(16)) {
+                    v = v.wrapping_mul(16).wrapping_add(d);
+                    chars.next();
+                    n += 1;
+                }
+                if n == 0 {
+                    return None;
+                }
+                v
+            }
+            c @ '0'..='7' => {
+                let mut v = c.to_digit(8)?;
+                for _ in 0..2 {
+                    match chars.peek().and_then(|c| c.to_digit(8)) {
+                        Some(d) => {
+                            v = v * 8 + d;
+                            chars.next();
+                        }
+                        None => break,
+                    }
+                }
+                v
+            }
+            c @ ('u' | 'U') => {
+                let len = if c == 'u' { 4 } else { 8 };
+                let hex: String = (0..len).filter_map(|_| chars.next()).collect();
+                if hex.len() != len {
+                    return None;
+                }
+                let c = char::from_u32(u32::from_str_radix(&hex, 16).ok()?)?;
+                units.push(Unit::Char(c));
+                continue;
+            }
+            _ => return None,
+        };
+        units.push(Unit::Escape(e));
+    }
+    Some(units)
+}
+
+/// The value of a multi-character constant like `'ABCD'`
+///
+/// Multi-character constants are implementation-defined; this uses the big-endian packing
+/// of GCC and Clang, the first character ends up in the most significant byte of an `int`.
+/// Characters beyond the fourth shift the first ones out. Returns `None` for single
+/// character and prefixed (`L'..'`, `u'..'`) constants, or if a character doesn't fit a byte.
+pub fn multi_char(raw: &str) -> Option<i128> {
+    let body = raw.strip_prefix('\'')?.strip_suffix('\'')?;
+    let units = unescape(body)?;
+    if units.len() < 2 {
+        return None;
+    }
+    let mut v: u32 = 0;
+    for u in units {
+        let b = match u {
+            Unit::Char(c) if (c as u32) < 0x80 => c as u32,
+            Unit::Escape(e) if e < 0x100 => e,
+            _ => return None,
+        };
+        v = (v << 8) | b;
+    }
+    Some(v as i32 as i128)
+}
+
+/// The bytes of a (possibly concatenated) narrow string literal, without the terminating
+/// NUL
+///
+/// `parts` are the adjacent literals as written in the source, including the quotes. Source
+/// characters are encoded as UTF-8, escapes as the byte they denote, so strings that are no
+/// valid UTF-8 are represented exactly. Returns `None` for wide literals (`L".."`, `u".."`,
+/// `U".."`) and if an escape doesn't fit a byte.
+pub fn string_bytes(parts: &[String]) -> Option<Vec<u8>> {
+    let mut bytes = Vec::new();
+    for p in parts {
+        let p = p.strip_prefix("u8").unwrap_or(p);
+        let body = p.strip_prefix('"')?.strip_suffix('"')?;
+        for u in unescape(body)? {
+            match u {
+                Unit::Char(c) => bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes()),
+                Unit::Escape(e) => bytes.push(u8::try_from(e).ok()?),
+            }
+        }
+    }
+    Some(bytes)
+}
+
+/// The encoding of a (possibly concatenated) string literal, `None` for narrow and UTF-8
+/// literals
+///
+/// The unprefixed parts take the prefix of the others, like `L"a" "b"`.
+pub fn encoding(parts: &[String]) -> Option<Encoding> {
+    parts.iter().find_map(|p| prefix(p.split('"').next()?))
+}
+
+fn prefix(prefix: &str) -> Option<Encoding> {
+    match prefix {
+        "L" => Some(Encoding::Wide),
+        "u" => Some(Encoding::Utf16),
+        "U" => Some(Encoding::Utf32),
+        _ => None,
+    }
+}
+
+/// The code units of a (possibly concatenated) wide literal of `encoding`, without the
+/// terminating NUL
+///
+/// Source characters are encoded as UTF-16 for `u".."` and as code points otherwise, escapes
+/// as the unit they denote. Returns `None` if the parts have different prefixes or if an
+/// escape doesn't fit a unit.
+pub fn string_units(parts: &[String], encoding: Encoding) -> Option<Vec<u32>> {
+    let mut units = Vec::new();
+    for p in parts {
+        let (written, body) = p.split_once('"')?;
+        if !written.is_empty() && prefix(written) != Some(encoding) {
+            return None;
+        }
+        for u in unescape(body.strip_suffix('"')?)? {
+            match (u, encoding) {
+                (Unit::Char(c), Encoding::Utf16) => {
+                    units.extend(c.encode_utf16(&mut [0; 2]).iter().map(|u| u32::from(*u)))
+                }
+                (Unit::Char(c), _) => units.push(c as u32),
+                (Unit::Escape(e), Encoding::Utf16) => units.push(u32::from(u16::try_from(e).ok()?)),
+                (Unit::Escape(e), _) => units.push(e),
+            }
+        }
+    }
+    Some(units)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parts(parts: &[&str]) -> Vec<String> {
+        parts.iter().map(|p| String::from(*p)).collect()
+    }
+
+    #[test]
+    fn escapes() {
+        assert_eq!(
+            unescape("a\\n\\x41\\101\\0"),
+            Some(vec![
+                Unit::Char('a'),
+                Unit::Escape(0x0a),
+                Unit::Escape(0x41),
+                Unit::Escape(0o101),
+                Unit::Escape(0),
+            ])
+        );
+        assert_eq!(unescape("\\x"), None);
+        assert_eq!(unescape("\\q"), None);
+        assert_eq!(unescape("\\u00e9"), Some(vec![Unit::Char('é')]));
+    }
+
+    #[test]
+    fn multi_character_constants() {
+        assert_eq!(multi_char("'ABCD'"), Some(0x4142_4344));
+        assert_eq!(multi_char("'AB'"), Some(0x4142));
+        // The first character is shifted out
+        assert_eq!(multi_char("'ABCDE'"), Some(0x4243_4445));
+        assert_eq!(multi_char("'\\xff\\xff\\xff\\xff'"), Some(-1));
+        assert_eq!(multi_char("'A'"), None);
+        assert_eq!(multi_char("L'AB'"), None);
+    }
+
+    #[test]
+    fn narrow_strings() {
+        assert_eq!(
+            string_bytes(&parts(&["\"\\101\\t\\x42\"", "\"\\xff\""])),
+            Some(vec![65, 9, 66, 255])
+        );
+        assert_eq!(string_bytes(&parts(&["u8\"é\""])), Some(vec![0xc3, 0xa9]));
+        assert_eq!(string_bytes(&parts(&["L\"a\""])), None);
+        assert_eq!(string_bytes(&parts(&["\"\\x100\""])), None);
+    }
+
+    #[test]
+    fn wide_strings() {
+        assert_eq!(encoding(&parts(&["\"a\"", "L\"b\""])), Some(Encoding::Wide));
+        assert_eq!(encoding(&parts(&["u8\"a\""])), None);
+        assert_eq!(
+            string_units(&parts(&["u\"\\U0001F600\""]), Encoding::Utf16),
+            Some(vec![0xd83d, 0xde00])
+        );
+        assert_eq!(
+            string_units(&parts(&["U\"\\U0001F600\""]), Encoding::Utf32),
+            Some(vec![0x1f600])
+        );
+        assert_eq!(
+            string_units(&parts(&["L\"a\"", "\"b\""]), Encoding::Wide),
+            Some(vec![97, 98])
+        );
+        assert_eq!(
+            string_units(&parts(&["L\"a\"", "u\"b\""]), Encoding::Wide),
+            None
+        );
+        assert_eq!(
+            string_units(&parts(&["u\"\\x10000\""]), Encoding::Utf16),
+            None
+        );
+    }
+}
@@ -1,47 +1,809 @@
-use anyhow::Result;
-use lang_c::ast::*;
-use lang_c::driver::{parse, Config};
-use lang_c::span::Span;
-use lang_c::visit;
+use anyhow::{bail, Result};
+use c_ast::diag::Annotations;
+use c_ast::progress::Progress;
+use c_ast::radix::EnumRadix;
+use c_ast::typemap::TypeMap;
+use c_ast::{
+    comments, diag, graph, header, json, lint, markdown, proto, radix, rename, repl, rust,
+    sorted_keys, sql, stats, tree, verify, walk, watch, AnonymousFields, Extraction, FlattenArrays,
+    Flattened, Indented, Locals, MyVisitor, Style,
+};
+use lang_c::driver::{Config, Parse};
 use lang_c::visit::Visit;
-use log;
-use simple_logger::SimpleLogger;
-use std::collections::HashMap;
-use std::fmt;
-use std::path::PathBuf;
+use log::LevelFilter;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, Instant, SystemTime};
 use structopt::StructOpt;
 use structopt_flags::LogLevel;
 
+/// The file extensions accepted with `--require-c-extension`
+const C_EXTENSIONS: &[&str] = &["c", "h", "i"];
+
 fn main() -> Result<()> {
-    let (config, file) = setup()?;
-    let unit = parse(&config, file)?.unit;
-    let strcts = &mut HashMap::new();
-    let vals = &mut HashMap::new();
-    let mut myp = MyVisitor::new(strcts, vals);
-    myp.visit_translation_unit(&unit);
-    println!("Struct-Types: {:#?}", &myp.struct_types);
-    println!("");
-    for v in myp.values.values() {
-        println!("{}", v);
+    let (config, opt) = setup()?;
+    let mut files = opt.files.clone();
+    if let Some(dir) = &opt.dir {
+        files.extend(walk::find(
+            dir,
+            &opt.glob,
+            opt.respect_gitignore,
+            opt.since,
+        )?);
+    }
+    if opt.require_c_extension {
+        for file in &files {
+            match file.extension().and_then(|e| e.to_str()) {
+                Some(e) if C_EXTENSIONS.contains(&e) => (),
+                _ => bail!(
+                    "{} doesn't have one of the extensions {}",
+                    file.display(),
+                    C_EXTENSIONS.join(", ")
+                ),
+            }
+        }
+    }
+    if opt.dry_run {
+        return dry_run(&config, &opt, &files);
+    }
+    let result = run(&config, &opt, &files);
+    if !opt.watch {
+        return result;
+    }
+    if let Err(e) = result {
+        eprintln!("Error: {:#}", e);
+    }
+    let mut watcher = watch::Watcher::new(&files);
+    watch::rerun(&mut watcher, || {
+        println!("// ---- run at {} ----", watch::clock(SystemTime::now()));
+        diag::reset();
+        // Keep watching, the next change may fix the error
+        if let Err(e) = run(&config, &opt, &files) {
+            eprintln!("Error: {:#}", e);
+        }
+    });
+    Ok(())
+}
+
+/// Only parse `files`, once per profile if there are any, and report the files that failed
+fn dry_run(config: &Config, opt: &Opt, files: &[PathBuf]) -> Result<()> {
+    let mut configs = vec![(None, config.clone())];
+    if !opt.profiles.is_empty() {
+        configs = opt
+            .profiles
+            .iter()
+            .map(|p| {
+                let mut config = config.clone();
+                for d in &p.defines {
+                    config.cpp_options.push(format!("-D{}", d));
+                }
+                (Some(p.name.as_str()), config)
+            })
+            .collect();
+    }
+    let (mut parsed, mut failed) = (0, 0);
+    let mut progress = progress(opt, files.len() * configs.len());
+    for (profile, config) in &configs {
+        for file in files {
+            let parse = check_size(opt, file)
+                .and_then(|_| Ok(c_ast::parse_file(config, file, opt.preprocessed)?));
+            progress.tick(file);
+            match parse {
+                Ok(_) => parsed += 1,
+                // The error tells the file
+                Err(e) => {
+                    failed += 1;
+                    match profile {
+                        Some(p) => eprintln!("Error in profile {}: {:#}", p, e),
+                        None => eprintln!("Error: {:#}", e),
+                    }
+                }
+            }
+        }
+    }
+    drop(progress);
+    println!("{} parsed, {} failed", parsed, failed);
+    if failed > 0 {
+        bail!("{} of {} parses failed", failed, parsed + failed);
+    }
+    Ok(())
+}
+
+/// Extract from `files` and print the result, once per profile if there are any
+fn run(config: &Config, opt: &Opt, files: &[PathBuf]) -> Result<()> {
+    let mut timings = Vec::new();
+    let mut runs = Vec::new();
+    // One JSON document per file, labeled with its shown path instead of a profile
+    let per_file = opt.format == Format::Json
+        && files.len() > 1
+        && opt.merge_format != MergeFormat::Combined
+        && opt.profiles.is_empty()
+        && !opt.merge
+        && !opt.dot;
+    let labels: Vec<String> = files
+        .iter()
+        .map(|f| shown_path(f, opt).display().to_string())
+        .collect();
+    let mut progress = progress(opt, files.len() * opt.profiles.len().max(1));
+    if per_file {
+        for (file, label) in files.iter().zip(&labels) {
+            let file = std::slice::from_ref(file);
+            runs.push((
+                Some(label.as_str()),
+                extract(config, opt, file, None, &mut timings, &mut progress)?,
+            ));
+        }
+    } else if opt.profiles.is_empty() {
+        runs.push((
+            None,
+            extract(config, opt, files, None, &mut timings, &mut progress)?,
+        ));
+    } else {
+        for p in &opt.profiles {
+            let mut config = config.clone();
+            for d in &p.defines {
+                config.cpp_options.push(format!("-D{}", d));
+            }
+            log::debug!("profile {} cpp_options {:?}", p.name, config.cpp_options);
+            let ex = extract(
+                &config,
+                opt,
+                files,
+                Some(&p.name),
+                &mut timings,
+                &mut progress,
+            )?;
+            runs.push((Some(p.name.as_str()), ex));
+        }
+    }
+    drop(progress);
+    if opt.timing {
+        print_timings(&timings);
+    }
+    let result = output(opt, &mut runs, per_file);
+    if let Some(path) = &opt.summary_json {
+        let parse_time = timings.iter().map(|t| t.1).sum();
+        let shown: Vec<_> = files.iter().map(|f| shown_path(f, opt)).collect();
+        let per = if per_file { "per_file" } else { "profiles" };
+        let summary = json::summary(&shown, &runs, per, parse_time, &diag::warnings());
+        fs::write(path, format!("{:#}\n", summary))?;
+    }
+    if let Some(path) = &opt.sql_script {
+        let dumps: Vec<_> = runs
+            .iter()
+            .map(|(p, ex)| sql::dump(ex, p.filter(|_| !per_file), &opt.path_separator))
+            .collect();
+        fs::write(path, dumps.concat())?;
+    }
+    result?;
+    let warnings = diag::warnings();
+    let errors: Vec<String> = opt
+        .error_on
+        .iter()
+        .filter_map(|c| {
+            let n = warnings.get(&format!("c_ast::{}", c))?;
+            Some(format!("{} {}", n, c))
+        })
+        .collect();
+    if !errors.is_empty() {
+        bail!("Warnings treated as errors: {}", errors.join(", "));
+    }
+    Ok(())
+}
+
+/// Verify or print the extractions, as selected by `opt`
+///
+/// With `per_file` the runs are of the input files rather than the profiles.
+fn output(opt: &Opt, runs: &mut [(Option<&str>, Extraction)], per_file: bool) -> Result<()> {
+    if let Some(Command::Verify { spec }) = &opt.cmd {
+        let exps = verify::parse_spec(&fs::read_to_string(spec)?)?;
+        let mut failures = Vec::new();
+        for (profile, ex) in runs.iter() {
+            for f in verify::verify(&ex.values, &exps) {
+                match profile {
+                    Some(p) => failures.push(format!("[{}] {}", p, f)),
+                    None => failures.push(f),
+                }
+            }
+        }
+        for f in &failures {
+            println!("{}", f);
+        }
+        let total = exps.len() * runs.len();
+        if !failures.is_empty() {
+            bail!("{} of {} expectations not met", failures.len(), total);
+        }
+        println!("All {} expectations met", total);
+        return Ok(());
+    }
+    for (_, ex) in runs.iter_mut() {
+        rename::strip_prefixes(ex, &opt.strip_prefixes);
+        if let Some(section) = &opt.section {
+            ex.values
+                .retain(|_, v| v.attrs().section.as_ref() == Some(section));
+        }
+    }
+    if let Some(Command::Repl) = &opt.cmd {
+        return match &*runs {
+            [(None, ex)] => run_repl(ex, &opt.path_separator),
+            _ => bail!("The repl doesn't support --profile"),
+        };
+    }
+    if opt.fingerprint {
+        for (profile, ex) in runs.iter() {
+            match profile {
+                Some(p) => println!("{} {}", json::fingerprint(ex), p),
+                None => println!("{}", json::fingerprint(ex)),
+            }
+        }
+        return Ok(());
+    }
+    if let Some(n) = opt.limit {
+        for (profile, ex) in runs.iter_mut() {
+            let total = ex.values.len();
+            if limit(ex, n) {
+                match profile {
+                    Some(p) => eprintln!("[{}] Showing {} of {} values", p, n, total),
+                    None => eprintln!("Showing {} of {} values", n, total),
+                }
+            }
+        }
+    }
+    if let Some(pointer) = &opt.get_ptr {
+        for (profile, ex) in runs.iter() {
+            if let Some(p) = profile {
+                println!("// profile {}", p);
+            }
+            match verify::lookup_pointer(&ex.values, pointer)? {
+                verify::Found::Value(v) => {
+                    println!("{}", Flattened(v, &style(opt, ex)))
+                }
+                verify::Found::Expression(e) => println!("{} = {:?}", pointer, e),
+            }
+        }
+        return Ok(());
+    }
+    if let Some(path) = &opt.field_values {
+        let (name, field) = match path.split_once('.') {
+            Some(names) => names,
+            None => bail!("Expected STRUCT.FIELD but got '{}'", path),
+        };
+        for (profile, ex) in runs.iter() {
+            if let Some(p) = profile {
+                println!("// profile {}", p);
+            }
+            match ex.struct_types.get(name) {
+                Some(st) if st.fields.iter().any(|f| f.name == field) => (),
+                Some(_) => bail!("struct {} has no member {}", name, field),
+                None => bail!("struct {} not found", name),
+            }
+            for (value, count) in stats::field_values(ex, name, field) {
+                println!("{:>6} {}", count, value);
+            }
+        }
+        return Ok(());
+    }
+    if let Some(name) = &opt.fields_only {
+        for (profile, ex) in runs.iter() {
+            if let Some(p) = profile {
+                println!("// profile {}", p);
+            }
+            match ex.struct_types.get(name) {
+                Some(st) => {
+                    for f in &st.fields {
+                        println!("{};", f.typ.declare(&f.name));
+                    }
+                }
+                None => {
+                    let mut names: Vec<&str> = ex.struct_types.keys().map(|n| n.as_str()).collect();
+                    names.sort_unstable();
+                    bail!("struct {} not found, known are: {}", name, names.join(", "));
+                }
+            }
+        }
+        return Ok(());
+    }
+    // The declarations of the C header, the Rust types and the messages need them
+    if opt.no_anonymous
+        && !matches!(
+            opt.format,
+            Format::CHeader | Format::Rust | Format::ProtobufSchema
+        )
+    {
+        for (_, ex) in runs.iter_mut() {
+            ex.struct_types.retain(|_, st| !st.anonymous);
+            ex.enum_types.retain(|_, et| !et.anonymous);
+        }
+    }
+    if per_file && opt.merge_format == MergeFormat::Stream {
+        for (file, ex) in runs.iter() {
+            let mut json = json::extraction(ex);
+            json["file"] = serde_json::json!(file);
+            println!("{}", json);
+        }
+        return Ok(());
+    }
+    if opt.format == Format::Json && !opt.dot {
+        let json = match &*runs {
+            [(None, ex)] => json::extraction(ex),
+            _ => runs
+                .iter()
+                .map(|(p, ex)| (String::from(p.unwrap_or_default()), json::extraction(ex)))
+                .collect(),
+        };
+        println!("{:#}", json);
+        return Ok(());
+    }
+    if opt.format == Format::Jsonl && !opt.dot {
+        for (profile, ex) in runs.iter() {
+            for mut v in json::values(ex) {
+                if let Some(p) = profile {
+                    v["profile"] = serde_json::json!(p);
+                }
+                println!("{}", v);
+            }
+        }
+        return Ok(());
+    }
+    let type_map = match &opt.type_map {
+        Some(path) => TypeMap::parse(&fs::read_to_string(path)?)?,
+        None => TypeMap::default(),
+    };
+    for (profile, ex) in runs.iter() {
+        if let Some(p) = profile {
+            println!("// profile {}", p);
+        }
+        if opt.dot {
+            print!("{}", graph::dot(ex));
+        } else if opt.format == Format::Tree {
+            print!("{}", tree::render(ex));
+        } else if opt.format == Format::CHeader {
+            print!("{}", header::render(ex, opt.enum_radix));
+        } else if opt.format == Format::Rust {
+            print!("{}", rust::render(ex, &type_map, opt.enum_radix));
+        } else if opt.format == Format::ProtobufSchema {
+            print!("{}", proto::render(ex));
+        } else if opt.format == Format::Markdown {
+            print!("{}", markdown::render(ex));
+        } else {
+            print(ex, &style(opt, ex), opt.enum_radix);
+        }
+    }
+    Ok(())
+}
+
+/// Answer the queries read from stdin until `quit` or the end of the input
+fn run_repl(ex: &Extraction, separator: &str) -> Result<()> {
+    let session = repl::Session::new(ex).with_separator(separator);
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        print!("> ");
+        io::stdout().flush()?;
+        let line = match lines.next() {
+            Some(l) => l?,
+            None => {
+                println!();
+                return Ok(());
+            }
+        };
+        match session.dispatch(&line) {
+            Ok(repl::Reply::Output(out)) => print!("{}", out),
+            Ok(repl::Reply::Quit) => return Ok(()),
+            Err(e) => println!("error: {}", e),
+        }
+    }
+}
+
+/// Parse and visit all input files with `config`
+///
+/// With `--merge` all files are parsed before they are visited, see [`merged`].
+fn extract(
+    config: &Config,
+    opt: &Opt,
+    files: &[PathBuf],
+    profile: Option<&str>,
+    timings: &mut Vec<(String, Duration, Duration)>,
+    progress: &mut Progress,
+) -> Result<Extraction> {
+    let mut used = HashSet::new();
+    let mut extraction = if opt.merge {
+        merged(config, opt, files, profile, timings, progress, &mut used)?
+    } else {
+        let mut extraction = Extraction::default();
+        for file in files {
+            let start = Instant::now();
+            let parse = parse(config, opt, file, &mut used);
+            progress.tick(file);
+            let Some(parse) = skip_failed(opt, parse)? else {
+                continue;
+            };
+            let parsed = Instant::now();
+            let shown = shown_path(file, opt);
+            let several = files.len() > 1;
+            let mut myp = visitor(opt, &parse, profile, &shown, several, &mut extraction);
+            myp.visit_translation_unit(&parse.unit);
+            let found = myp.found();
+            drop(myp);
+            if opt.pragmas {
+                add_pragmas(&mut extraction, opt, &parse, &shown);
+            }
+            timings.push((label(profile, &shown), parsed - start, parsed.elapsed()));
+            if found {
+                break;
+            }
+        }
+        extraction
+    };
+    rename::decode_identifiers(&mut extraction, opt.raw_identifiers);
+    if opt.report_unused {
+        lint::unused_structs(&extraction, &used);
+    }
+    Ok(extraction)
+}
+
+/// Visit the parsed `files` as one program, resolving the `extern` constants of each file to
+/// the definitions in the others
+///
+/// The files are visited again and again in the given order, each time with the definitions
+/// found in the pass before, until no more of them are found. Then they are visited a last
+/// time with the warnings enabled, the values of this pass are returned. An `extern const`
+/// declaration is matched by name to a file scope `const` definition that isn't `static`,
+/// in the first file defining it if there are several. The values defined the same in
+/// several files are only kept once, see [`lint::duplicates`].
+fn merged(
+    config: &Config,
+    opt: &Opt,
+    files: &[PathBuf],
+    profile: Option<&str>,
+    timings: &mut Vec<(String, Duration, Duration)>,
+    progress: &mut Progress,
+    used: &mut HashSet<String>,
+) -> Result<Extraction> {
+    let mut parses = Vec::new();
+    for file in files {
+        let start = Instant::now();
+        let parse = parse(config, opt, file, used);
+        progress.tick(file);
+        if let Some(parse) = skip_failed(opt, parse)? {
+            parses.push((shown_path(file, opt), parse, start.elapsed()));
+        }
+    }
+    // More externs can only resolve more definitions, so the number of them tells if a pass
+    // found new ones
+    let mut externs = HashMap::new();
+    let level = log::max_level();
+    log::set_max_level(LevelFilter::Off);
+    loop {
+        let mut scratch = Extraction::default();
+        let mut definitions = HashMap::new();
+        for (shown, parse, _) in &parses {
+            let mut myp =
+                visitor(opt, parse, profile, shown, false, &mut scratch).with_externs(&externs);
+            myp.visit_translation_unit(&parse.unit);
+            for (name, value) in myp.definitions() {
+                definitions
+                    .entry(name.clone())
+                    .or_insert_with(|| value.clone());
+            }
+        }
+        if definitions.len() == externs.len() {
+            break;
+        }
+        externs = definitions;
+    }
+    log::set_max_level(level);
+    log::debug!("Resolving {} extern constants", externs.len());
+    let mut extraction = Extraction::default();
+    for (shown, parse, parse_time) in &parses {
+        let start = Instant::now();
+        visitor(opt, parse, profile, shown, false, &mut extraction)
+            .with_externs(&externs)
+            .visit_translation_unit(&parse.unit);
+        if opt.pragmas {
+            add_pragmas(&mut extraction, opt, parse, shown);
+        }
+        timings.push((label(profile, shown), *parse_time, start.elapsed()));
+    }
+    let shown: Vec<PathBuf> = parses.into_iter().map(|(shown, _, _)| shown).collect();
+    lint::duplicates(&mut extraction, &shown);
+    Ok(extraction)
+}
+
+/// The progress of processing `total` files, not shown with `--quiet`
+fn progress(opt: &Opt, total: usize) -> Progress {
+    if opt.verbose.get_level_filter() < LevelFilter::Warn {
+        Progress::hidden(total)
+    } else {
+        Progress::new(total)
+    }
+}
+
+/// Parse `file` and run the lints asked for on it, the struct types it uses are added to
+/// `used`
+fn parse(config: &Config, opt: &Opt, file: &Path, used: &mut HashSet<String>) -> Result<Parse> {
+    check_size(opt, file)?;
+    let parse = c_ast::parse_file(config, file, opt.preprocessed)?;
+    if opt.warn_octal {
+        lint::octal(&parse.unit, &parse.source, &|p| shown_path(p, opt));
+    }
+    if opt.report_unused {
+        lint::struct_uses(&parse.unit, used);
+    }
+    Ok(parse)
+}
+
+/// Fail if `file` is larger than `--max-file-size`, before it is preprocessed
+fn check_size(opt: &Opt, file: &Path) -> Result<()> {
+    if let Some(max) = opt.max_file_size {
+        let size = match fs::metadata(file) {
+            Ok(m) => m.len(),
+            Err(e) => bail!("{}: {}", file.display(), e),
+        };
+        if size > max {
+            bail!(
+                "{} has {} bytes, more than the maximum of {}",
+                file.display(),
+                size,
+                max
+            );
+        }
     }
     Ok(())
 }
 
-fn setup() -> Result<(Config, PathBuf)> {
+/// The parse of a file, or `None` with a warning if it failed and `--continue-on-error` is
+/// given
+///
+/// The errors tell the file already.
+fn skip_failed(opt: &Opt, parse: Result<Parse>) -> Result<Option<Parse>> {
+    match parse {
+        Ok(parse) => Ok(Some(parse)),
+        Err(e) if opt.continue_on_error => {
+            log::warn!(target: "c_ast::skipped", "{:#}, skipping the file", e);
+            Ok(None)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Add the `#pragma` lines of `parse` to `ex`, those of a header included before only once
+///
+/// `shown` is the file of `parse` as it is shown, the other files are shown the same way.
+fn add_pragmas(ex: &mut Extraction, opt: &Opt, parse: &Parse, shown: &Path) {
+    let pragmas = comments::Source::new(&parse.source).pragmas(shown, &|p| shown_path(p, opt));
+    for p in pragmas {
+        if !ex.pragmas.contains(&p) {
+            ex.pragmas.push(p);
+        }
+    }
+}
+
+/// A visitor collecting the declarations of `parse` into `ex` as the options tell
+///
+/// With `several` files or profiles the values streamed in the human format start with the
+/// file, see [`label`].
+fn visitor<'a>(
+    opt: &'a Opt,
+    parse: &'a Parse,
+    profile: Option<&'a str>,
+    shown: &Path,
+    several: bool,
+    ex: &'a mut Extraction,
+) -> MyVisitor<'a> {
+    // The enum types are not all known yet, so the values are shown in decimal
+    let style = Style {
+        indent: opt.indent.clone(),
+        arrays: opt.flatten_arrays,
+        hex: HashSet::new(),
+        group: opt.group_digits,
+    };
+    let mut myp = MyVisitor::new(Some(shown), ex);
+    if opt.stream && opt.format == Format::Jsonl {
+        myp = myp.with_sink(move |k, v| {
+            let mut v = json::value(&k, &v);
+            if let Some(p) = profile {
+                v["profile"] = serde_json::json!(p);
+            }
+            println!("{}", v)
+        });
+    } else if opt.stream && (several || profile.is_some()) {
+        let label = label(profile, shown);
+        myp = myp.with_sink(move |_, v| println!("{}:{}", label, Flattened(&v, &style)));
+    } else if opt.stream {
+        myp = myp.with_sink(move |_, v| println!("{}", Flattened(&v, &style)));
+    }
+    if opt.functions {
+        myp = myp.with_functions();
+    }
+    if opt.dense_arrays {
+        myp = myp.with_dense_arrays();
+    }
+    if opt.warn_shadowing {
+        myp = myp.with_shadowing_warnings();
+    }
+    // The line markers of the source locate the annotations
+    if opt.format == Format::Markdown || opt.no_system_headers || opt.annotations.is_some() {
+        myp = myp.with_source(&parse.source);
+        myp = myp.with_shown_paths(move |p| shown_path(p, opt));
+    }
+    if opt.no_system_headers {
+        myp = myp.without_system_headers();
+    }
+    myp = myp.with_enum_bases(&parse.source);
+    myp = myp.with_digit_separators(&parse.source);
+    myp = myp.with_locals(opt.locals);
+    myp = myp.with_anonymous_fields(opt.include_anonymous_fields);
+    if let (true, Some(name)) = (opt.first_only, &opt.fields_only) {
+        myp = myp.stop_after(name);
+    }
+    myp
+}
+
+/// The name of `shown` in the timings
+fn label(profile: Option<&str>, shown: &Path) -> String {
+    match profile {
+        Some(p) => format!("{}:{}", p, shown.display()),
+        None => shown.display().to_string(),
+    }
+}
+
+/// `file` as it is shown in the output, relative to the base of `--relative-paths` if given
+fn shown_path(file: &Path, opt: &Opt) -> PathBuf {
+    let base = match &opt.relative_paths {
+        Some(Some(base)) => base.clone(),
+        Some(None) => PathBuf::from("."),
+        None => return file.to_path_buf(),
+    };
+    let absolute = |p: &Path| {
+        fs::canonicalize(p).unwrap_or_else(|_| match std::env::current_dir() {
+            Ok(cwd) => cwd.join(p),
+            Err(_) => p.to_path_buf(),
+        })
+    };
+    let (file, base) = (absolute(file), absolute(&base));
+    match file.strip_prefix(&base) {
+        Ok(rel) => rel.to_path_buf(),
+        Err(_) => file,
+    }
+}
+
+/// Keep only the first `n` top level values of `ex` by file, scope and name, `true` if there
+/// were more
+fn limit(ex: &mut Extraction, n: usize) -> bool {
+    if ex.values.len() <= n {
+        return false;
+    }
+    let keys: Vec<_> = sorted_keys(ex).into_iter().cloned().collect();
+    for k in &keys[n..] {
+        ex.values.remove(k);
+    }
+    true
+}
+
+/// Print the time spent per file as tab separated table with a total to stderr
+fn print_timings(timings: &[(String, Duration, Duration)]) {
+    let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+    eprintln!("file\tparse_ms\tvisit_ms");
+    for (file, parse, visit) in timings {
+        eprintln!("{}\t{:.3}\t{:.3}", file, ms(*parse), ms(*visit));
+    }
+    let parse: Duration = timings.iter().map(|t| t.1).sum();
+    let visit: Duration = timings.iter().map(|t| t.2).sum();
+    eprintln!("total\t{:.3}\t{:.3}", ms(parse), ms(visit));
+}
+
+/// How `opt` tells to show the values of `ex` in the human output
+fn style(opt: &Opt, ex: &Extraction) -> Style {
+    Style {
+        indent: opt.indent.clone(),
+        arrays: opt.flatten_arrays,
+        hex: radix::hex_constants(ex, opt.enum_radix),
+        group: opt.group_digits,
+    }
+}
+
+fn print(ex: &Extraction, style: &Style, radix: EnumRadix) {
+    let indent = style.indent.as_str();
+    let mut typedefs: Vec<_> = ex.typedefs.iter().collect();
+    typedefs.sort_by_key(|(n, _)| n.as_str());
+    println!("Typedefs:");
+    for (name, typ) in typedefs {
+        let resolved = typ.resolve(&ex.typedefs);
+        if *resolved == *typ {
+            println!("typedef {};", typ.declare(name));
+        } else {
+            println!(
+                "typedef {}; // {}",
+                typ.declare(name),
+                resolved.declare(name)
+            );
+        }
+    }
+    println!();
+    let mut struct_types: Vec<_> = ex.struct_types.values().collect();
+    struct_types.sort_by_key(|s| &s.name);
+    println!("Struct-Types:");
+    for s in struct_types {
+        println!("{}", Indented(s, indent));
+    }
+    let mut enum_types: Vec<_> = ex.enum_types.values().collect();
+    enum_types.sort_by_key(|e| &e.name);
+    println!("Enum-Types:");
+    for et in enum_types {
+        let hex = radix::is_hex(et, radix);
+        match &et.underlying {
+            Some(u) => println!("enum {} : {} {{", et.name, u),
+            None => println!("enum {} {{", et.name),
+        }
+        for (name, value) in &et.constants {
+            match value {
+                Some(v) => println!("{}{} = {},", indent, name, radix::format(*v, hex)),
+                None => println!("{}{},", indent, name),
+            }
+        }
+        println!("}}\n");
+    }
+    if !ex.functions.is_empty() {
+        println!();
+        println!("Functions:");
+        for f in &ex.functions {
+            println!("{};", f);
+        }
+        println!();
+    }
+    if !ex.pragmas.is_empty() {
+        println!("Pragmas:");
+        for (p, at) in &ex.pragmas {
+            println!("{} // {}", p, at);
+        }
+        println!();
+    }
+    let keys = sorted_keys(ex);
+    let first_file = keys.first().map(|k| &k.file);
+    let multi_file = keys.iter().any(|k| Some(&k.file) != first_file);
+    for k in keys {
+        match (&k.file, k.scope.is_empty()) {
+            (Some(f), true) if multi_file => println!("// in {}", f.display()),
+            (Some(f), false) if multi_file => {
+                println!("// in {} {}", f.display(), k.scope.join("::"))
+            }
+            (_, false) => println!("// in {}", k.scope.join("::")),
+            _ => (),
+        }
+        println!("{}", Flattened(&ex.values[k], style));
+    }
+}
+
+fn setup() -> Result<(Config, Opt)> {
     let opt = Opt::from_args();
-    SimpleLogger::new()
-        .with_level(opt.verbose.get_level_filter())
-        .init()?;
+    diag::init(opt.verbose.get_level_filter(), opt.annotations)?;
+    // The format may come from the environment, so clap can't tell
+    if opt.stream && !matches!(opt.format, Format::Human | Format::Jsonl) {
+        bail!("--stream only works with --format human or jsonl");
+    }
     let mut config = Config::default();
-    let mut push_cpp_opts = |switch: &str, items: Vec<String>| {
+    if let Some(cmd) = &opt.preprocessor {
+        let mut words = cmd.split_whitespace().map(String::from);
+        config.cpp_command = words.next().unwrap_or_default();
+        config.cpp_options = words.collect();
+    }
+    let mut push_cpp_opts = |switch: &str, items: &[String]| {
         for i in items {
             config.cpp_options.push(format!("{}{}", switch, i));
         }
     };
-    push_cpp_opts("-I", opt.cpp_includes);
-    push_cpp_opts("-D", opt.cpp_macros);
-    log::debug!("cpp_options {:?}", config.cpp_options);
-    Ok((config, opt.file))
+    push_cpp_opts("-I", &opt.cpp_includes);
+    push_cpp_opts("-D", &opt.cpp_macros);
+    log::debug!(
+        "cpp_command {} cpp_options {:?}",
+        config.cpp_command,
+        config.cpp_options
+    );
+    Ok((config, opt))
 }
 
 #[derive(Debug, StructOpt)]
@@ -53,200 +815,460 @@ struct Opt {
     cpp_macros: Vec<String>,
     #[structopt(name = "INCLUDE_PATH", short = "I")]
     cpp_includes: Vec<String>,
-    #[structopt(name = "FILE", parse(try_from_str = parse_path))]
-    file: PathBuf,
+    /// Print the elements of top level arrays of structs as they are extracted instead of
+    /// collecting them first, only with `--format human` or `jsonl`
+    ///
+    /// The human lines of several files or profiles start with the file like `a.c:`, the
+    /// JSON lines always have the file.
+    #[structopt(long, conflicts_with = "dot")]
+    stream: bool,
+    /// Which initialized declarations inside of functions to collect, `none`, `static` (those
+    /// with static storage duration) or `all` (also `auto` and `register` ones)
+    #[structopt(
+        long,
+        default_value = "static",
+        possible_values = &["none", "static", "all"],
+        parse(try_from_str = parse_locals)
+    )]
+    locals: Locals,
+    /// What to do with the values of anonymous struct and union members like
+    /// `union { int i; float f; };`, `flatten` promotes their members into the containing
+    /// struct, `nest` keeps them as nested struct named `anonymous_1`, `anonymous_2` and so on
+    #[structopt(
+        long,
+        default_value = "flatten",
+        possible_values = &["flatten", "nest"],
+        parse(try_from_str = parse_anonymous_fields)
+    )]
+    include_anonymous_fields: AnonymousFields,
+    /// Show the identifiers with universal character names as UCNs like `caf\u00e9` instead
+    /// of as `café`
+    ///
+    /// The UCNs are the ones of the preprocessed source, GCC turns the characters written as
+    /// UTF-8 into ones like `caf\U000000e9`. The names of the types, fields, variables and
+    /// enum constants and the names in the raw form of expressions are all shown so.
+    #[structopt(long)]
+    raw_identifiers: bool,
+    /// Remove this prefix from struct, field and variable names in the output (repeatable)
+    #[structopt(long = "strip-prefix", name = "PREFIX", number_of_values = 1)]
+    strip_prefixes: Vec<String>,
+    /// Print the relationships between the struct types as graphviz digraph
+    #[structopt(long)]
+    dot: bool,
+    /// Also list the signatures of the declared and defined functions
+    #[structopt(long)]
+    functions: bool,
+    /// Leave the struct, union and enum types declared without a tag, whose names are
+    /// synthesized, out of the listed types
+    ///
+    /// The values of these types are still shown. The `c-header`, `rust` and
+    /// `protobuf-schema` formats still declare them, as the other types refer to them.
+    #[structopt(long)]
+    no_anonymous: bool,
+    /// Also list the `#pragma` lines of the files and the headers they include (except the
+    /// system headers) with their location, e.g. `#pragma config WDTE = OFF`
+    #[structopt(long)]
+    pragmas: bool,
+    /// Indent the human output by this many spaces, or by tabs with `tab`
+    #[structopt(long, default_value = "2", parse(try_from_str = parse_indent))]
+    indent: String,
+    /// How the human output shows arrays, `none` (as the elements' debug output), `indexed`
+    /// (one `name[i] = ...` line per element) or `compact` (in one line like `[1, 2, 3]`)
+    #[structopt(
+        long,
+        default_value = "none",
+        possible_values = &["none", "indexed", "compact"],
+        parse(try_from_str = parse_flatten_arrays)
+    )]
+    flatten_arrays: FlattenArrays,
+    /// Keep all elements of large arrays with few set elements, e.g. of `int lut[256] = {
+    /// [0] = 1, [255] = 2 }`, instead of only the set ones
+    #[structopt(long)]
+    dense_arrays: bool,
+    /// Warn about the members designated in initializers that have the name of a global
+    /// variable declared before, like `.timeout = 5` with a global `timeout`, often a copy
+    /// and paste mistake
+    #[structopt(long)]
+    warn_shadowing: bool,
+    /// Show the values of enum constants, and of the integers referring to them, in `dec`,
+    /// `hex`, or with `auto` in hex for the enums that look like flags
+    ///
+    /// An enum looks like flags if its name contains `flag` or `mask`, or if its constants
+    /// are powers of two (at least one of them 4 or more) or combinations of these.
+    #[structopt(
+        long,
+        default_value = "dec",
+        possible_values = &["dec", "hex", "auto"],
+        parse(try_from_str = parse_enum_radix)
+    )]
+    enum_radix: EnumRadix,
+    /// Group the digits of the integer values in the human output with `_`, like `1_000_000`
+    /// or `0xff_ffff`
+    ///
+    /// The raw forms are shown as written, also with C23 digit separators like `1'000'000`.
+    #[structopt(long)]
+    group_digits: bool,
+    /// Leave out the typedefs, struct and enum types, values and functions declared in system
+    /// headers, e.g. below `/usr/include` or an `-isystem` directory
+    #[structopt(long)]
+    no_system_headers: bool,
+    /// Extract again whenever an input file changes, until interrupted
+    #[structopt(long)]
+    watch: bool,
+    /// Warn about octal literals like `010` that mean another value than the same digits as
+    /// decimal number
+    #[structopt(long)]
+    warn_octal: bool,
+    /// Warn about the struct types that no variable, field, typedef, parameter, cast or
+    /// `sizeof` in any of the files uses
+    #[structopt(long)]
+    report_unused: bool,
+    /// Print the time spent parsing and visiting each file to stderr
+    #[structopt(long)]
+    timing: bool,
+    /// Output format, `human`, `json`, `jsonl` (one JSON object per top level value and
+    /// line), `tree`, `markdown` (a table of the fields per struct type), `c-header` (the
+    /// enum and struct types as C declarations), `rust` (those types as `#[repr(C)]` Rust
+    /// types) or `protobuf-schema` (the struct types as `proto3` messages)
+    ///
+    /// The flag takes precedence over the `C_AST_FORMAT` environment variable, which in turn
+    /// takes precedence over the default `human`.
+    #[structopt(
+        long,
+        name = "FORMAT",
+        env = "C_AST_FORMAT",
+        default_value = "human",
+        possible_values = &[
+            "human",
+            "json",
+            "jsonl",
+            "tree",
+            "markdown",
+            "c-header",
+            "rust",
+            "protobuf-schema"
+        ]
+    )]
+    format: Format,
+    /// How `--format json` of several files is shaped: `keyed` is one object with the
+    /// document of each file under its path, `stream` one document per file and line with
+    /// its path as `file`, `combined` one document of all files like for a single file
+    ///
+    /// `--merge` and `--profile` always combine the files.
+    #[structopt(
+        long,
+        default_value = "keyed",
+        possible_values = &["keyed", "stream", "combined"]
+    )]
+    merge_format: MergeFormat,
+    /// A TOML file of `"C type" = "Rust type"` lines for `--format rust`, consulted before
+    /// the built-in mapping of the C types
+    #[structopt(long, name = "TYPE_MAP", parse(try_from_str = parse_path))]
+    type_map: Option<PathBuf>,
+    /// Extract once per profile `NAME:DEFINES`, with the comma separated DEFINES passed to
+    /// the preprocessor in addition to the `-D` macros (repeatable)
+    ///
+    /// This shows how values selected by `#ifdef` differ between build configurations.
+    #[structopt(long = "profile", name = "PROFILE", number_of_values = 1)]
+    profiles: Vec<Profile>,
+    /// Only print a hash of the extraction, which changes whenever the JSON output would
+    ///
+    /// With profiles there is one line with the hash and the name per profile.
+    #[structopt(long)]
+    fingerprint: bool,
+    /// Only print the first N top level values (sorted by file, scope and name), the note
+    /// that there are more goes to stderr
+    #[structopt(long, name = "N")]
+    limit: Option<usize>,
+    /// Only print the value at this JSON pointer, e.g. `/config/servers/0/port` for the port
+    /// of the first element of the `servers` array in the struct `config`
+    #[structopt(long, name = "POINTER")]
+    get_ptr: Option<String>,
+    /// Only keep the values placed in this linker section with
+    /// `__attribute__((section(".config")))`
+    #[structopt(long, name = "SECTION")]
+    section: Option<String>,
+    /// Only print the fields of this struct
+    #[structopt(long, value_name = "STRUCT")]
+    fields_only: Option<String>,
+    /// Only print the distinct values of this member of a struct type, e.g. `server.port`,
+    /// with how many of the values of the struct type are initialized with them
+    #[structopt(long, name = "STRUCT.FIELD")]
+    field_values: Option<String>,
+    /// Stop extracting once the struct of `--fields-only` is found, the following
+    /// declarations and files aren't visited
+    #[structopt(long, requires = "fields-only")]
+    first_only: bool,
+    /// Treat the input files as the translation units of one program, the `extern const`
+    /// declarations of each file get the values defined in the others
+    ///
+    /// All files are parsed before they are visited, and visited several times until the
+    /// constants referring to other files are resolved. File scope values defined the same
+    /// in several files, e.g. in a header, are only shown once, with a warning if they are
+    /// defined differently.
+    #[structopt(long, conflicts_with_all = &["stream", "first-only"])]
+    merge: bool,
+    /// Fail if there were warnings of this category, e.g. `no_struct` or `range`, after
+    /// printing the output as usual
+    ///
+    /// Can be given several times, the other categories stay warnings.
+    #[structopt(long, name = "CATEGORY", possible_values = diag::CATEGORIES, number_of_values = 1)]
+    error_on: Vec<String>,
+    /// Print the warnings as annotations of a CI service to stdout instead of logging them,
+    /// `github` for GitHub Actions (`::warning file=test.c,line=3,title=range::...`)
+    ///
+    /// The warnings about declarations are located at the line of the declaration in the
+    /// original file.
+    #[structopt(long, possible_values = &["github"], parse(try_from_str = parse_annotations))]
+    annotations: Option<Annotations>,
+    /// Write a summary of the run (counts, warnings, files and parse time) as JSON to this file
+    #[structopt(long, name = "SUMMARY_PATH", parse(try_from_str = parse_path))]
+    summary_json: Option<PathBuf>,
+    /// Write the types and values as SQL script to this file, to be loaded into an SQLite
+    /// database with e.g. `sqlite3 ast.db < ast.sql`
+    ///
+    /// The script creates the tables if they don't exist yet, so the scripts of several runs
+    /// can be loaded into one database. The values have columns for their file and profile.
+    #[structopt(long, name = "SQL_PATH", parse(try_from_str = parse_path))]
+    sql_script: Option<PathBuf>,
+    /// Separate the names in the paths of the members by this instead of `.`, e.g. `/` or
+    /// `::` for field names that other tools would take apart
+    ///
+    /// For the member paths of `--sql-script` and the paths queried with `get` in the repl, the
+    /// indices of array elements stay in brackets like `servers[0]/port`.
+    #[structopt(
+        long,
+        name = "SEPARATOR",
+        default_value = ".",
+        parse(try_from_str = parse_separator)
+    )]
+    path_separator: String,
+    /// The input files are already preprocessed (e.g. `.i` files from `gcc -E`), parse them
+    /// as they are without running the preprocessor again
+    ///
+    /// Use this if the original include paths and defines aren't available, or if running
+    /// the preprocessor again would change the code, e.g. expand `unix` to `1` in output
+    /// preprocessed for another target. `-D` and `-I` have no effect then.
+    #[structopt(long)]
+    preprocessed: bool,
+    /// Only parse the input files and report the counts of those that parsed and failed,
+    /// without extracting anything, e.g. for a pre-commit check
+    ///
+    /// Fails if any file didn't parse. With profiles each file is parsed once per profile.
+    #[structopt(long, conflicts_with_all = &["watch", "merge"])]
+    dry_run: bool,
+    /// Don't parse the input files larger than this many bytes, e.g. to not run out of
+    /// memory on a huge generated file
+    ///
+    /// The size of the file itself is checked, not that of the preprocessed text. It is an
+    /// error unless `--continue-on-error` is given.
+    #[structopt(long, name = "BYTES")]
+    max_file_size: Option<u64>,
+    /// Skip the input files that can't be parsed or are too large (see `--max-file-size`)
+    /// with a warning of the category `skipped` instead of failing
+    #[structopt(long)]
+    continue_on_error: bool,
+    /// Preprocess with this command instead of `gcc -E`, e.g. `"arm-none-eabi-gcc -E"`
+    ///
+    /// The command is split at whitespace, there is no quoting. It is run once per input file
+    /// with the `-I` and `-D` options, e.g. `-Iinclude -DNDEBUG=1`, and the path of the file
+    /// as last arguments, and with the working directory of c-ast. It has to write the
+    /// preprocessed source to stdout and exit with status 0, otherwise its stderr is reported
+    /// as error. Line markers like `# 1 "config.h"` are used for the comments and locations.
+    #[structopt(long, name = "CMD", conflicts_with = "preprocessed")]
+    preprocessor: Option<String>,
+    /// Fail before parsing if an input file doesn't have a C extension (.c, .h or .i)
+    #[structopt(long)]
+    require_c_extension: bool,
+    /// Extract from all files below this directory whose path matches `--glob`
+    #[structopt(long, name = "DIR", parse(try_from_str = parse_path))]
+    dir: Option<PathBuf>,
+    /// The pattern the paths below `--dir` (relative to it) have to match
+    #[structopt(long, name = "PATTERN", default_value = "*.[ch]")]
+    glob: glob::Pattern,
+    /// Skip the files below `--dir` that are ignored by a `.gitignore`
+    #[structopt(long)]
+    respect_gitignore: bool,
+    /// Skip the files below `--dir` modified before this, a time ago like `2h`, `3d` or
+    /// `1h30m` (units `s`, `m`, `h`, `d` and `w`), a UTC date like `2024-05-01` or
+    /// `2024-05-01T12:30`, or `@` and the seconds since the Unix epoch
+    #[structopt(long, name = "WHEN", requires = "DIR", parse(try_from_str = parse_since))]
+    since: Option<SystemTime>,
+    /// Show the file paths relative to BASE, or to the current directory without a value
+    ///
+    /// Paths outside of BASE are shown as absolute paths. The value has to be given as
+    /// `--relative-paths=BASE`.
+    #[structopt(long, name = "BASE", require_equals = true)]
+    relative_paths: Option<Option<PathBuf>>,
+    #[structopt(
+        name = "FILE",
+        parse(try_from_str = parse_path),
+        required_unless = "DIR"
+    )]
+    files: Vec<PathBuf>,
+    #[structopt(subcommand)]
+    cmd: Option<Command>,
 }
 
-fn parse_path(s: &str) -> Result<PathBuf> {
-    let s = shellexpand::full(s)?;
-    Ok(PathBuf::from(String::from(s)))
+#[derive(Debug, StructOpt)]
+enum Command {
+    /// Check the extracted values against the `dotted.path = value` lines of a TOML file
+    Verify {
+        #[structopt(name = "SPEC", parse(try_from_str = parse_path))]
+        spec: PathBuf,
+    },
+    /// Parse once, then answer queries like `get NAME` read from stdin, see `help`
+    Repl,
 }
 
-#[derive(Debug)]
-pub struct MyStructType {
-    name: String,
-    fields: Vec<String>,
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format {
+    Human,
+    Json,
+    Jsonl,
+    Tree,
+    Markdown,
+    CHeader,
+    Rust,
+    ProtobufSchema,
 }
 
-impl MyStructType {
-    fn new(name: &str) -> MyStructType {
-        MyStructType {
-            name: String::from(name),
-            fields: Vec::new(),
+impl FromStr for Format {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Format> {
+        match s {
+            "human" => Ok(Format::Human),
+            "json" => Ok(Format::Json),
+            "jsonl" => Ok(Format::Jsonl),
+            "tree" => Ok(Format::Tree),
+            "markdown" => Ok(Format::Markdown),
+            "c-header" => Ok(Format::CHeader),
+            "rust" => Ok(Format::Rust),
+            "protobuf-schema" => Ok(Format::ProtobufSchema),
+            _ => bail!("Unknown format '{}'", s),
         }
     }
 }
 
-#[derive(Debug)]
-pub enum MyValue {
-    Struct(MyStruct),
-    Scalar { name: String, value: MyExpression },
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum MergeFormat {
+    Keyed,
+    Stream,
+    Combined,
 }
 
-impl MyValue {
-    fn new_struct(typ: &str, name: &str) -> MyValue {
-        MyValue::Struct(MyStruct {
-            typ: String::from(typ),
-            name: String::from(name),
-            values: Vec::new(),
-        })
-    }
+impl FromStr for MergeFormat {
+    type Err = anyhow::Error;
 
-    fn new_scalar(name: &str, value: MyExpression) -> MyValue {
-        MyValue::Scalar {
-            name: String::from(name),
-            value: value,
+    fn from_str(s: &str) -> Result<MergeFormat> {
+        match s {
+            "keyed" => Ok(MergeFormat::Keyed),
+            "stream" => Ok(MergeFormat::Stream),
+            "combined" => Ok(MergeFormat::Combined),
+            _ => bail!("Unknown merge format '{}'", s),
         }
     }
 }
 
-impl fmt::Display for MyValue {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            MyValue::Struct(s) => writeln!(f, "{}", s)?,
-            MyValue::Scalar { name: n, value: v } => writeln!(f, "{} = {:?}", n, v)?,
+/// A named set of preprocessor defines, see `--profile`
+#[derive(Debug)]
+struct Profile {
+    name: String,
+    defines: Vec<String>,
+}
+
+impl FromStr for Profile {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Profile> {
+        match s.split_once(':') {
+            Some((name, defines)) if !name.is_empty() => Ok(Profile {
+                name: String::from(name),
+                defines: defines
+                    .split(',')
+                    .filter(|d| !d.is_empty())
+                    .map(String::from)
+                    .collect(),
+            }),
+            _ => bail!("Expected a profile as NAME:DEFINES but got '{}'", s),
         }
-        Ok(())
     }
 }
 
-#[derive(Debug)]
-pub struct MyStruct {
-    typ: String,
-    name: String,
-    values: Vec<(String, MyExpression)>,
+fn parse_locals(s: &str) -> Result<Locals> {
+    match s {
+        "none" => Ok(Locals::None),
+        "static" => Ok(Locals::Static),
+        "all" => Ok(Locals::All),
+        _ => bail!("Unknown locals '{}'", s),
+    }
 }
 
-impl fmt::Display for MyStruct {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        writeln!(f, "struct {} {}", self.typ, self.name)?;
-        for (n, e) in &self.values {
-            writeln!(f, "  .{} = {:?}", n, e)?;
-        }
-        Ok(())
+fn parse_annotations(s: &str) -> Result<Annotations> {
+    match s {
+        "github" => Ok(Annotations::Github),
+        _ => bail!("Unknown annotations '{}'", s),
     }
 }
 
-#[derive(Debug)]
-pub enum MyExpression {
-    Integer(String),
-    Float(String),
-    String(String),
-    StringLiteral(Vec<String>),
-    Other(String),
-}
-
-pub struct MyVisitor<'a> {
-    cur_struct: Option<String>,
-    struct_types: &'a mut HashMap<String, MyStructType>,
-    values: &'a mut HashMap<(Option<String>, String), MyValue>,
-}
-
-impl<'a> MyVisitor<'a> {
-    pub fn new(
-        s: &'a mut HashMap<String, MyStructType>,
-        v: &'a mut HashMap<(Option<String>, String), MyValue>,
-    ) -> MyVisitor<'a> {
-        MyVisitor {
-            cur_struct: None,
-            struct_types: s,
-            values: v,
-        }
-    }
-}
-
-impl<'ast, 'a> Visit<'ast> for MyVisitor<'a> {
-    fn visit_struct_type(&mut self, n: &'ast StructType, span: &'ast Span) {
-        if let Some(ref id) = n.identifier {
-            self.cur_struct = Some(String::from(&id.node.name));
-        }
-        visit::visit_struct_type(self, n, span);
-    }
-
-    fn visit_struct_field(&mut self, n: &'ast StructField, _: &'ast Span) {
-        if let Some(struct_name) = self.cur_struct.as_ref() {
-            for declarator in &n.declarators {
-                if let Some(x) = &declarator.node.declarator {
-                    match &x.node.kind.node {
-                        DeclaratorKind::Identifier(y) => {
-                            self.struct_types
-                                .entry(String::from(struct_name))
-                                .or_insert(MyStructType::new(struct_name))
-                                .fields
-                                .push(String::from(&y.node.name));
-                        }
-                        _ => (),
-                    }
-                }
-            }
-        } else {
-            log::warn!(
-                "I visit struct fields but I don't know in which struct I am!\n {:#?}",
-                n
-            )
-        }
-    }
-
-    fn visit_init_declarator(&mut self, n: &'ast InitDeclarator, _: &'ast Span) {
-        match &n.declarator.node.kind.node {
-            DeclaratorKind::Identifier(x) => {
-                if let Some(ini) = &n.initializer {
-                    match &ini.node {
-                        Initializer::List(xs) => {
-                            if let Some(struct_name) = &self.cur_struct {
-                                if let MyValue::Struct(mst) = self
-                                    .values
-                                    .entry((self.cur_struct.clone(), String::from(&x.node.name)))
-                                    .or_insert(MyValue::new_struct(struct_name, &x.node.name))
-                                {
-                                    if let Some(stype) =
-                                        self.struct_types.get(self.cur_struct.as_ref().unwrap())
-                                    {
-                                        for (x, fname) in xs.into_iter().zip(stype.fields.clone()) {
-                                            fill(&mut mst.values, &fname, &x.node.initializer.node);
-                                        }
-                                    } else {
-                                        panic!(
-                                            "Struct type '{}' not found",
-                                            self.cur_struct.as_ref().unwrap()
-                                        )
-                                    }
-                                }
-                            }
-                        }
-                        Initializer::Expression(e) => {
-                            self.values
-                                .entry((self.cur_struct.clone(), String::from(&x.node.name)))
-                                .or_insert(MyValue::new_scalar(&x.node.name, transform(&e.node)));
-                            ()
-                        }
-                    }
-                }
-            }
-            x => panic!("Expected an identifier but got {:?}", x),
-        }
+fn parse_anonymous_fields(s: &str) -> Result<AnonymousFields> {
+    match s {
+        "flatten" => Ok(AnonymousFields::Flatten),
+        "nest" => Ok(AnonymousFields::Nest),
+        _ => bail!("Unknown anonymous fields '{}'", s),
     }
 }
 
-fn fill(acc: &mut Vec<(String, MyExpression)>, fname: &str, ini: &Initializer) {
-    match &ini {
-        Initializer::Expression(e) => acc.push((String::from(fname), transform(&e.node))),
-        Initializer::List(ls) => {
-            for l in ls {
-                fill(acc, fname, &l.node.initializer.node)
-            }
-        }
+fn parse_flatten_arrays(s: &str) -> Result<FlattenArrays> {
+    match s {
+        "none" => Ok(FlattenArrays::None),
+        "indexed" => Ok(FlattenArrays::Indexed),
+        "compact" => Ok(FlattenArrays::Compact),
+        _ => bail!("Unknown array rendering '{}'", s),
     }
 }
 
-fn transform(expr: &Expression) -> MyExpression {
-    match expr {
-        Expression::Constant(a) => match &a.node {
-            Constant::Integer(b) => MyExpression::Integer(String::from(b.number.as_ref())),
-            Constant::Float(b) => MyExpression::Float(String::from(b.number.as_ref())),
-            Constant::Character(b) => MyExpression::String(String::from(b)),
+fn parse_enum_radix(s: &str) -> Result<EnumRadix> {
+    match s {
+        "dec" => Ok(EnumRadix::Dec),
+        "hex" => Ok(EnumRadix::Hex),
+        "auto" => Ok(EnumRadix::Auto),
+        _ => bail!("Unknown enum radix '{}'", s),
+    }
+}
+
+fn parse_indent(s: &str) -> Result<String> {
+    match s {
+        "tab" => Ok(String::from("\t")),
+        n => match n.parse() {
+            Ok(n) => Ok(" ".repeat(n)),
+            Err(_) => bail!("Expected a number of spaces or `tab` but got '{}'", s),
         },
-        Expression::StringLiteral(a) => MyExpression::StringLiteral(a.node.clone()),
-        a => MyExpression::Other(format!("{:?}", a)),
     }
 }
+
+fn parse_separator(s: &str) -> Result<String> {
+    if s.is_empty() || s.contains(['[', ']']) || s.contains(char::is_whitespace) {
+        bail!(
+            "Expected a path separator without brackets and spaces but got '{}'",
+            s
+        );
+    }
+    Ok(String::from(s))
+}
+
+fn parse_since(s: &str) -> Result<SystemTime> {
+    match walk::since(s, SystemTime::now()) {
+        Some(t) => Ok(t),
+        None => bail!(
+            "Expected a time ago like `3d`, a date like `2024-05-01` or `@` and seconds but got '{}'",
+            s
+        ),
+    }
+}
+
+fn parse_path(s: &str) -> Result<PathBuf> {
+    let s = shellexpand::full(s)?;
+    Ok(PathBuf::from(String::from(s)))
+}
@@ -0,0 +1,86 @@
+//! Render the struct types as Markdown tables, e.g. to document configuration structs
+
+use crate::{sorted_keys, tree, Extraction, MyExpression, MyStruct, MyValue};
+use std::fmt::Write;
+
+/// One section per struct type with a table of its fields
+///
+/// The default values are taken from the first file scope instance of the struct, in the
+/// order of file and name.
+pub fn render(ex: &Extraction) -> String {
+    let keys: Vec<_> = sorted_keys(ex)
+        .into_iter()
+        .filter(|k| k.scope.is_empty())
+        .collect();
+    let mut struct_types: Vec<_> = ex.struct_types.values().collect();
+    struct_types.sort_by_key(|s| &s.name);
+    let mut out = String::new();
+    for st in struct_types {
+        let instance = keys.iter().find_map(|k| match &ex.values[*k] {
+            MyValue::Struct(s) if s.typ == st.name => Some(s),
+            _ => None,
+        });
+        writeln!(out, "## {}\n", st.name).unwrap();
+        if let Some(s) = instance {
+            writeln!(out, "Defaults from `{}`.\n", s.name).unwrap();
+        }
+        writeln!(out, "| Field | Type | Default | Description |").unwrap();
+        writeln!(out, "|---|---|---|---|").unwrap();
+        for f in &st.fields {
+            let default = instance
+                .and_then(|s| s.values.iter().find(|(n, _)| *n == f.name))
+                .map(|(_, v)| code(&text(v)))
+                .unwrap_or_default();
+            // E.g. `[deprecated: use timeout_ms] The timeout in seconds`
+            let attrs = f.attrs.to_string();
+            let doc: Vec<&str> = vec![attrs.trim(), f.doc.as_deref().unwrap_or_default()]
+                .into_iter()
+                .filter(|s| !s.is_empty())
+                .collect();
+            writeln!(
+                out,
+                "| {} | {} | {} | {} |",
+                code(&f.name),
+                code(&f.typ.to_string()),
+                default,
+                escape(&doc.join(" "))
+            )
+            .unwrap();
+        }
+        writeln!(out).unwrap();
+    }
+    out
+}
+
+fn text(value: &MyExpression) -> String {
+    match value {
+        MyExpression::Array(xs) => {
+            let items: Vec<_> = xs.iter().map(text).collect();
+            format!("{{{}}}", items.join(", "))
+        }
+        MyExpression::SparseArray { entries, .. } => {
+            let items: Vec<_> = entries
+                .iter()
+                .map(|(i, x)| format!("[{}] = {}", i, text(x)))
+                .collect();
+            format!("{{{}}}", items.join(", "))
+        }
+        MyExpression::Struct(MyStruct { values, .. }) => {
+            let items: Vec<_> = values
+                .iter()
+                .map(|(n, v)| format!(".{} = {}", n, text(v)))
+                .collect();
+            format!("{{{}}}", items.join(", "))
+        }
+        leaf => tree::text(leaf),
+    }
+}
+
+fn code(s: &str) -> String {
+    format!("`{}`", escape(s))
+}
+
+/// Keep `|` from ending the table cell
+fn escape(s: &str) -> String {
+    s.replace('|', "\\|")
+}
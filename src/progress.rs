@@ -0,0 +1,135 @@
+//! The progress of the scan of many files, as a line on stderr redrawn per file
+//!
+//! The line is only drawn if stderr is a terminal, so the logs of CI runs and the output
+//! redirected to files stay free of it. Hidden, a [`Progress`] still counts the files.
+
+use std::io::{self, IsTerminal, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// The width of the bar in characters
+const WIDTH: usize = 30;
+
+pub struct Progress {
+    total: usize,
+    done: usize,
+    start: Instant,
+    shown: bool,
+}
+
+impl Progress {
+    /// The progress of `total` files, shown if stderr is a terminal and there is more than
+    /// one file
+    pub fn new(total: usize) -> Progress {
+        let mut progress = Progress::hidden(total);
+        progress.shown = total > 1 && io::stderr().is_terminal();
+        progress
+    }
+
+    /// The progress of `total` files that is only counted, e.g. with `--quiet`
+    pub fn hidden(total: usize) -> Progress {
+        Progress {
+            total,
+            done: 0,
+            start: Instant::now(),
+            shown: false,
+        }
+    }
+
+    /// Count `file` as processed and redraw the line
+    pub fn tick(&mut self, file: &Path) {
+        self.draw(&mut io::stderr(), file);
+    }
+
+    /// The number of files counted so far
+    pub fn done(&self) -> usize {
+        self.done
+    }
+
+    /// Count `file` and redraw the line on `out`
+    fn draw(&mut self, out: &mut dyn Write, file: &Path) {
+        self.done += 1;
+        if !self.shown {
+            return;
+        }
+        let filled = (WIDTH * self.done / self.total.max(1)).min(WIDTH);
+        let eta = match self.eta() {
+            Some(eta) => format!(", ETA {}", duration(eta)),
+            None => String::new(),
+        };
+        // Clear the line first, the file names differ in length
+        write!(
+            out,
+            "\r\x1b[2K[{}{}] {}/{} files{} {}",
+            "=".repeat(filled),
+            " ".repeat(WIDTH - filled),
+            self.done,
+            self.total,
+            eta,
+            file.display()
+        )
+        .and_then(|_| out.flush())
+        .ok();
+    }
+
+    /// The time the remaining files will take at the pace of the ones done so far
+    fn eta(&self) -> Option<Duration> {
+        let remaining = self.total.checked_sub(self.done)?;
+        let per_file = self.start.elapsed().checked_div(self.done as u32)?;
+        per_file.checked_mul(remaining as u32)
+    }
+}
+
+/// Remove the line once all files are done, or the scan stopped early
+impl Drop for Progress {
+    fn drop(&mut self) {
+        if self.shown && self.done > 0 {
+            eprint!("\r\x1b[2K");
+            io::stderr().flush().ok();
+        }
+    }
+}
+
+/// `d` in whole seconds like `1m 05s`
+fn duration(d: Duration) -> String {
+    let secs = d.as_secs();
+    match secs {
+        0..=59 => format!("{}s", secs),
+        _ => format!("{}m {:02}s", secs / 60, secs % 60),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hidden_progress_only_counts() {
+        let mut progress = Progress::hidden(3);
+        let mut out = Vec::new();
+        for file in &["a.c", "b.c", "c.c"] {
+            progress.draw(&mut out, Path::new(file));
+        }
+        assert_eq!(progress.done(), 3);
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn shown_progress_draws_the_bar() {
+        let mut progress = Progress::hidden(2);
+        progress.shown = true;
+        let mut out = Vec::new();
+        progress.draw(&mut out, Path::new("a.c"));
+        let line = String::from_utf8(out).unwrap();
+        assert!(line.contains("1/2 files"), "{:?}", line);
+        assert!(line.ends_with("a.c"), "{:?}", line);
+        // Don't clear the line of the test output on drop
+        progress.shown = false;
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(duration(Duration::from_secs(5)), "5s");
+        assert_eq!(duration(Duration::from_secs(65)), "1m 05s");
+    }
+}
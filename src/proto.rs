@@ -0,0 +1,199 @@
+//! Render the struct types as Protocol Buffers messages, e.g. to exchange configuration
+//! structs with programs in other languages
+
+use crate::ctype::{Derived, MyType};
+use crate::{Extraction, MyStructType};
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// The struct and union types of `ex` as `proto3` messages
+///
+/// The fields are numbered in the order they are declared. Anonymous struct types used by the
+/// fields of only one struct type are nested in its message, the others are messages of
+/// their own. Arrays become `repeated` fields, multidimensional ones with all their elements
+/// in one field, plain char arrays and pointers `string` fields. The fields of a union are one
+/// `oneof`, unless there are arrays among them. Enums are `int32`, since the constants of C
+/// enums needn't start at zero. Types without a Protocol Buffers equivalent become `bytes`
+/// with a warning.
+pub fn render(ex: &Extraction) -> String {
+    let mut out = String::from("syntax = \"proto3\";\n");
+    let nested = nested(ex);
+    let mut struct_types: Vec<_> = ex
+        .struct_types
+        .values()
+        .filter(|st| !nested.contains_key(st.name.as_str()))
+        .collect();
+    struct_types.sort_by_key(|s| &s.name);
+    for st in struct_types {
+        writeln!(out).unwrap();
+        message(&mut out, ex, &nested, st, 0);
+    }
+    out
+}
+
+/// The anonymous struct types used by the fields of only one struct type, by their name with
+/// the name of that struct type
+fn nested(ex: &Extraction) -> HashMap<&str, &str> {
+    let mut users: HashMap<&str, Vec<&str>> = HashMap::new();
+    for st in ex.struct_types.values() {
+        for f in &st.fields {
+            let typ = f.typ.resolve(&ex.typedefs);
+            let used = typ
+                .struct_name()
+                .and_then(|s| ex.struct_types.get_key_value(s))
+                .filter(|(_, t)| t.anonymous && t.name != st.name);
+            if let Some((name, _)) = used {
+                let users = users.entry(name.as_str()).or_default();
+                if !users.contains(&st.name.as_str()) {
+                    users.push(&st.name);
+                }
+            }
+        }
+    }
+    users
+        .into_iter()
+        .filter_map(|(name, users)| match users.as_slice() {
+            [user] => Some((name, *user)),
+            _ => None,
+        })
+        .collect()
+}
+
+fn message(
+    out: &mut String,
+    ex: &Extraction,
+    nested: &HashMap<&str, &str>,
+    st: &MyStructType,
+    depth: usize,
+) {
+    let indent = "  ".repeat(depth);
+    writeln!(out, "{}message {} {{", indent, st.name).unwrap();
+    let mut inner: Vec<_> = nested
+        .iter()
+        .filter(|(_, user)| **user == st.name)
+        .filter_map(|(name, _)| ex.struct_types.get(*name))
+        .collect();
+    inner.sort_by_key(|s| &s.name);
+    for n in inner {
+        message(out, ex, nested, n, depth + 1);
+    }
+    let fields: Vec<(String, &str)> = st
+        .fields
+        .iter()
+        .map(|f| {
+            let what = format!("{}.{}", st.name, f.name);
+            (field_type(ex, &f.typ, &what), f.name.as_str())
+        })
+        .collect();
+    let oneof = st.union && !fields.iter().any(|(t, _)| t.starts_with("repeated "));
+    let field_indent = if oneof {
+        // The name of the oneof mustn't be the name of a field
+        let mut name = String::from("value");
+        while fields.iter().any(|(_, n)| *n == name) {
+            name.push('_');
+        }
+        writeln!(out, "{}  oneof {} {{", indent, name).unwrap();
+        format!("{}    ", indent)
+    } else {
+        format!("{}  ", indent)
+    };
+    for (i, ((typ, name), f)) in fields.iter().zip(&st.fields).enumerate() {
+        let width = match &f.bit_width {
+            Some(w) => format!(" // bit-field of {} bits", w),
+            None => String::new(),
+        };
+        writeln!(
+            out,
+            "{}{} {} = {};{}",
+            field_indent,
+            typ,
+            name,
+            i + 1,
+            width
+        )
+        .unwrap();
+    }
+    if oneof {
+        writeln!(out, "{}  }}", indent).unwrap();
+    }
+    writeln!(out, "{}}}", indent).unwrap();
+}
+
+/// The type of a field of type `typ`, with `repeated` for arrays, `what` is the field (for
+/// warnings)
+fn field_type(ex: &Extraction, typ: &MyType, what: &str) -> String {
+    let typ = typ.resolve(&ex.typedefs);
+    let mut element = typ.clone().into_owned();
+    let mut dims = 0;
+    while let Some(Derived::Array(_)) = element.derived.last() {
+        element.derived.pop();
+        dims += 1;
+    }
+    if dims > 0 && element.derived.is_empty() && is_text(&element) {
+        // The last dimension is the string
+        dims -= 1;
+        element.derived.push(Derived::Pointer(Vec::new()));
+    }
+    let scalar = scalar(ex, &element, what);
+    match dims {
+        0 => scalar,
+        1 => format!("repeated {}", scalar),
+        _ => {
+            log::warn!(
+                target: "c_ast::type_map",
+                "The {}-dimensional array {} is one repeated field of all its elements",
+                dims,
+                what
+            );
+            format!("repeated {}", scalar)
+        }
+    }
+}
+
+/// The Protocol Buffers type of `typ` without array dimensions
+fn scalar(ex: &Extraction, typ: &MyType, what: &str) -> String {
+    match typ.derived.as_slice() {
+        [] => (),
+        [Derived::Pointer(_)] if is_text(typ) => return String::from("string"),
+        _ => return unknown(typ, what),
+    }
+    if let Some(s) = typ.struct_name() {
+        if ex.struct_types.contains_key(s) {
+            return String::from(s);
+        }
+        return unknown(typ, what);
+    }
+    let has = |w: &str| typ.specifiers.iter().any(|s| s == w);
+    if has("_Bool") {
+        return String::from("bool");
+    } else if has("float") && !has("_Complex") {
+        return String::from("float");
+    } else if has("double") && !has("long") && !has("_Complex") {
+        return String::from("double");
+    } else if has("double") || has("float") {
+        return unknown(typ, what);
+    }
+    match typ.int_range() {
+        Some((min, max)) if min < 0 && max <= i128::from(i32::MAX) => String::from("int32"),
+        Some((min, _)) if min < 0 => String::from("int64"),
+        Some((_, max)) if max <= i128::from(u32::MAX) => String::from("uint32"),
+        Some(_) => String::from("uint64"),
+        None => unknown(typ, what),
+    }
+}
+
+/// Whether `typ` is plain `char`, `signed char` and `unsigned char` are rather bytes
+fn is_text(typ: &MyType) -> bool {
+    let has = |w: &str| typ.specifiers.iter().any(|s| s == w);
+    has("char") && !has("signed") && !has("unsigned")
+}
+
+fn unknown(typ: &MyType, what: &str) -> String {
+    log::warn!(
+        target: "c_ast::type_map",
+        "No Protocol Buffers type for {} of {}, it is bytes",
+        typ,
+        what
+    );
+    String::from("bytes")
+}
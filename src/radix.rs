@@ -0,0 +1,137 @@
+//! Show the values of flag-like enums in hex, see `--enum-radix`
+
+use crate::{Extraction, MyEnumType};
+use std::collections::HashSet;
+
+/// In which radix the enum constants are shown
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EnumRadix {
+    Dec,
+    Hex,
+    /// Hex for the enums that look like flags, see [`is_flags`]
+    Auto,
+}
+
+/// Whether the constants of `et` are shown in hex with `radix`
+pub fn is_hex(et: &MyEnumType, radix: EnumRadix) -> bool {
+    match radix {
+        EnumRadix::Dec => false,
+        EnumRadix::Hex => true,
+        EnumRadix::Auto => is_flags(et),
+    }
+}
+
+/// Whether `et` looks like a set of flags
+///
+/// That is the case if its name contains `flag` or `mask`, or if its constants are powers of
+/// two or combinations of these, like `READ = 1, WRITE = 2, EXEC = 4, ALL = 7`. At least one
+/// of them has to be 4 or more, to leave out the enums counting `0, 1, 2`.
+pub fn is_flags(et: &MyEnumType) -> bool {
+    let name = et.name.to_lowercase();
+    if name.contains("flag") || name.contains("mask") {
+        return true;
+    }
+    let values: Vec<i128> = et.constants.iter().filter_map(|(_, v)| *v).collect();
+    let bits = values
+        .iter()
+        .filter(|v| **v > 0 && (**v & (**v - 1)) == 0)
+        .fold(0, |bits, v| bits | v);
+    values.len() == et.constants.len()
+        && bits >= 4
+        && values.iter().all(|v| *v >= 0 && v & !bits == 0)
+}
+
+/// The constants of the enum types of `ex` that are shown in hex with `radix`
+pub fn hex_constants(ex: &Extraction, radix: EnumRadix) -> HashSet<String> {
+    ex.enum_types
+        .values()
+        .filter(|et| is_hex(et, radix))
+        .flat_map(|et| et.constants.iter().map(|(n, _)| n.clone()))
+        .collect()
+}
+
+/// Whether the expression written as `raw` refers to one of `constants`, e.g.
+/// `FLAG_A | FLAG_B`
+pub fn refers_to(raw: &str, constants: &HashSet<String>) -> bool {
+    raw.split(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .any(|word| constants.contains(word))
+}
+
+/// `value` in hex like `0x10` or `-0x10`, or else in decimal
+pub fn format(value: i128, hex: bool) -> String {
+    match (hex, value < 0) {
+        (false, _) => value.to_string(),
+        (true, false) => format!("{:#x}", value),
+        (true, true) => format!("-{:#x}", value.unsigned_abs()),
+    }
+}
+
+/// The integer `text` as formatted by [`format()`] with its digits grouped by `_`, in threes
+/// for decimal like `1_000_000` and in fours for hex like `0xff_ffff`
+pub fn group(text: &str) -> String {
+    let (sign, unsigned) = match text.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", text),
+    };
+    let (prefix, digits, size) = match unsigned.strip_prefix("0x") {
+        Some(rest) => ("0x", rest, 4),
+        None => ("", unsigned, 3),
+    };
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / size);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % size == 0 {
+            grouped.push('_');
+        }
+        grouped.push(c);
+    }
+    format!("{}{}{}", sign, prefix, grouped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn enum_type(name: &str, values: &[i128]) -> MyEnumType {
+        MyEnumType {
+            name: String::from(name),
+            anonymous: false,
+            constants: values
+                .iter()
+                .enumerate()
+                .map(|(i, v)| (format!("C{}", i), Some(*v)))
+                .collect(),
+            underlying: None,
+        }
+    }
+
+    #[test]
+    fn flags() {
+        assert!(is_flags(&enum_type("perm", &[1, 2, 4, 7])));
+        assert!(is_flags(&enum_type("irq_mask", &[0, 3])));
+        assert!(!is_flags(&enum_type("weekday", &[0, 1, 2])));
+        assert!(!is_flags(&enum_type("mode", &[0, 4, 5])));
+        assert!(!is_flags(&enum_type("signed", &[-1, 4])));
+    }
+
+    #[test]
+    fn formatted() {
+        assert_eq!(format(16, false), "16");
+        assert_eq!(format(16, true), "0x10");
+        assert_eq!(format(-16, true), "-0x10");
+    }
+
+    #[test]
+    fn grouped() {
+        assert_eq!(group("1000000"), "1_000_000");
+        assert_eq!(group("100"), "100");
+        assert_eq!(group("-2500"), "-2_500");
+        assert_eq!(group("0xffffff"), "0xff_ffff");
+    }
+
+    #[test]
+    fn references() {
+        let constants: HashSet<String> = vec![String::from("FLAG_A")].into_iter().collect();
+        assert!(refers_to("FLAG_A | FLAG_B", &constants));
+        assert!(!refers_to("FLAG_AB", &constants));
+    }
+}
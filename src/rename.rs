@@ -0,0 +1,265 @@
+//! Rename the names shown in the output, e.g. strip prefixes
+
+use crate::{ucn, Extraction, MyExpression, MyStruct, MyValue, ValueKey};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Remove the first matching prefix from the struct, field and variable names of `ex`
+///
+/// Names are only renamed if that doesn't make them collide with another name of the same
+/// kind (struct names, the fields of one struct, the variables of one scope); colliding
+/// names are kept as they are and a warning is logged.
+pub fn strip_prefixes(ex: &mut Extraction, prefixes: &[String]) {
+    if prefixes.is_empty() {
+        return;
+    }
+    let plan = Plan::new(ex, |names, kind| plan(names, prefixes, kind), false);
+    apply(ex, &plan);
+}
+
+/// Turn the placeholders of universal character names in the names of `ex` into the
+/// characters, or into the `raw` UCNs like `\u00e9` of the preprocessed source
+///
+/// See [`crate::parse_file`], the typedef and enum names are renamed as well.
+pub fn decode_identifiers(ex: &mut Extraction, raw: bool) {
+    let decode: fn(&str) -> Option<String> = if raw { ucn::raw } else { ucn::decode };
+    let plan = Plan::new(
+        ex,
+        |names, _| {
+            names
+                .filter_map(|n| Some((n.clone(), decode(n)?)))
+                .collect()
+        },
+        true,
+    );
+    apply(ex, &plan);
+    // The raw form of expressions may name enum constants or other variables
+    for v in ex.values.values_mut() {
+        match v {
+            MyValue::Struct(s) => s.values.iter_mut().for_each(|(_, e)| rewrite(e, decode)),
+            MyValue::Scalar { value, .. } => rewrite(value, decode),
+        }
+    }
+}
+
+fn rewrite(e: &mut MyExpression, decode: fn(&str) -> Option<String>) {
+    match e {
+        MyExpression::Integer { raw, .. } | MyExpression::Other { raw, .. } => {
+            if let Some(r) = decode(raw) {
+                *raw = r;
+            }
+        }
+        MyExpression::Array(items) => items.iter_mut().for_each(|e| rewrite(e, decode)),
+        MyExpression::SparseArray { entries, .. } => {
+            entries.iter_mut().for_each(|(_, e)| rewrite(e, decode))
+        }
+        MyExpression::Struct(s) => s.values.iter_mut().for_each(|(_, e)| rewrite(e, decode)),
+        _ => (),
+    }
+}
+
+type Renames = HashMap<String, String>;
+
+/// The renames of the names of an extraction, by kind of name
+struct Plan {
+    structs: Renames,
+    /// By the original struct name
+    fields: HashMap<String, Renames>,
+    /// By the file and scope of the variables
+    vars: HashMap<(Option<PathBuf>, Vec<String>), Renames>,
+    /// Typedef, enum and enum constant names
+    others: Renames,
+}
+
+impl Plan {
+    /// Plan the renames of a kind of names with `plan`, the typedef and enum names only with
+    /// `others`
+    fn new<'e, F>(ex: &'e Extraction, plan: F, others: bool) -> Plan
+    where
+        F: Fn(&mut dyn Iterator<Item = &'e String>, &str) -> Renames,
+    {
+        let structs = plan(&mut ex.struct_types.keys(), "struct");
+        let fields = ex
+            .struct_types
+            .values()
+            .map(|st| {
+                let mut names = st.fields.iter().map(|f| &f.name);
+                (st.name.clone(), plan(&mut names, "field"))
+            })
+            .collect();
+
+        let mut scopes: HashMap<(_, _), Vec<&String>> = HashMap::new();
+        for k in ex.values.keys() {
+            scopes
+                .entry((k.file.clone(), k.scope.clone()))
+                .or_default()
+                .push(&k.name);
+        }
+        let vars = scopes
+            .into_iter()
+            .map(|(scope, names)| (scope, plan(&mut names.into_iter(), "variable")))
+            .collect();
+
+        let others = if others {
+            let mut names = ex
+                .typedefs
+                .keys()
+                .chain(ex.enum_types.values().flat_map(|et| {
+                    std::iter::once(&et.name).chain(et.constants.iter().map(|(n, _)| n))
+                }))
+                .chain(ex.enum_bases.keys());
+            plan(&mut names, "type")
+        } else {
+            Renames::new()
+        };
+        Plan {
+            structs,
+            fields,
+            vars,
+            others,
+        }
+    }
+}
+
+fn apply(ex: &mut Extraction, plan: &Plan) {
+    ex.struct_types = ex
+        .struct_types
+        .drain()
+        .map(|(name, mut st)| {
+            for f in &mut st.fields {
+                rename(&mut f.name, &plan.fields[&name]);
+                rename_type_refs(&mut f.typ.specifiers, plan);
+            }
+            rename(&mut st.name, &plan.structs);
+            (st.name.clone(), st)
+        })
+        .collect();
+    ex.typedefs = ex
+        .typedefs
+        .drain()
+        .map(|(mut name, mut t)| {
+            rename(&mut name, &plan.others);
+            rename_type_refs(&mut t.specifiers, plan);
+            (name, t)
+        })
+        .collect();
+    if !plan.others.is_empty() {
+        ex.enum_types = ex
+            .enum_types
+            .drain()
+            .map(|(_, mut et)| {
+                rename(&mut et.name, &plan.others);
+                for (n, _) in &mut et.constants {
+                    rename(n, &plan.others);
+                }
+                (et.name.clone(), et)
+            })
+            .collect();
+        ex.enum_bases = ex
+            .enum_bases
+            .drain()
+            .map(|(mut name, base)| {
+                rename(&mut name, &plan.others);
+                (name, base)
+            })
+            .collect();
+    }
+
+    ex.values = ex
+        .values
+        .drain()
+        .map(|(k, mut v)| {
+            let names = &plan.vars[&(k.file.clone(), k.scope.clone())];
+            match &mut v {
+                MyValue::Struct(s) => {
+                    rename(&mut s.name, names);
+                    rename_members(s, &plan.fields, &plan.structs);
+                }
+                MyValue::Scalar { name, .. } => rename(name, names),
+            }
+            let mut name = k.name.clone();
+            rename(&mut name, names);
+            (ValueKey { name, ..k }, v)
+        })
+        .collect();
+}
+
+fn strip<'n>(name: &'n str, prefixes: &[String]) -> &'n str {
+    prefixes
+        .iter()
+        .filter_map(|p| name.strip_prefix(p.as_str()))
+        .find(|s| !s.is_empty())
+        .unwrap_or(name)
+}
+
+/// Map each name that can be stripped without a collision to its stripped form
+fn plan<'n>(
+    names: impl Iterator<Item = &'n String>,
+    prefixes: &[String],
+    kind: &str,
+) -> HashMap<String, String> {
+    let mut by_stripped: HashMap<&str, Vec<&str>> = HashMap::new();
+    for n in names {
+        by_stripped.entry(strip(n, prefixes)).or_default().push(n);
+    }
+    let mut renames = HashMap::new();
+    for (stripped, originals) in by_stripped {
+        if originals.len() > 1 {
+            log::warn!(
+                target: "c_ast::strip_prefix",
+                "Not stripping the prefix of {} names {}, they would all become '{}'",
+                kind,
+                originals.join(", "),
+                stripped
+            );
+        } else if originals[0] != stripped {
+            renames.insert(String::from(originals[0]), String::from(stripped));
+        }
+    }
+    renames
+}
+
+fn rename(name: &mut String, renames: &HashMap<String, String>) {
+    if let Some(n) = renames.get(name) {
+        *name = n.clone();
+    }
+}
+
+/// Rename the members of `s` and of the structs nested in it, and their struct types
+fn rename_members(
+    s: &mut MyStruct,
+    fields: &HashMap<String, HashMap<String, String>>,
+    structs: &HashMap<String, String>,
+) {
+    let renames = fields.get(&s.typ);
+    for (field, value) in &mut s.values {
+        if let Some(renames) = renames {
+            rename(field, renames);
+        }
+        if let MyExpression::Struct(nested) = value {
+            if let Some(renames) = renames {
+                rename(&mut nested.name, renames);
+            }
+            rename_members(nested, fields, structs);
+        }
+    }
+    rename(&mut s.typ, structs);
+}
+
+/// Rename the struct, union, enum and typedef names among the type `specifiers`
+fn rename_type_refs(specifiers: &mut [String], plan: &Plan) {
+    for s in specifiers {
+        let renamed = ["struct ", "union ", "enum "].iter().find_map(|kind| {
+            let n = s.strip_prefix(kind)?;
+            let renames = if *kind == "enum " {
+                &plan.others
+            } else {
+                &plan.structs
+            };
+            Some(format!("{}{}", kind, renames.get(n)?))
+        });
+        if let Some(r) = renamed.or_else(|| plan.others.get(s.as_str()).cloned()) {
+            *s = r;
+        }
+    }
+}
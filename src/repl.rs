@@ -0,0 +1,157 @@
+//! Query an extraction interactively, see the `repl` subcommand
+//!
+//! The commands are dispatched independently of the terminal, one line at a time.
+
+use crate::verify::PathIndex;
+use crate::{sorted_keys, CAstError, Extraction, MyValue};
+use std::cell::OnceCell;
+use std::fmt::Write;
+
+pub const HELP: &str = "\
+get PATH              print a value or a (nested) member like cfg.servers[0].port
+fields STRUCT         print the fields of a struct type
+list WHAT             list the structs, enums, typedefs, functions or values
+filter PATTERN        list the values whose name matches the glob PATTERN
+help                  print this help
+quit                  leave";
+
+type Result<T> = std::result::Result<T, CAstError>;
+
+/// Return a [`CAstError::Query`] with the formatted message
+macro_rules! fail {
+    ($($arg:tt)*) => {
+        return Err(CAstError::Query(format!($($arg)*)))
+    };
+}
+
+/// The result of one command
+#[derive(Debug, PartialEq)]
+pub enum Reply {
+    Output(String),
+    Quit,
+}
+
+/// The queries against one extraction
+pub struct Session<'e> {
+    ex: &'e Extraction,
+    /// Built by the first `get` of a member, for the following ones
+    index: OnceCell<PathIndex<'e>>,
+    /// Between the names of the members in the paths of `get`
+    separator: String,
+}
+
+impl<'e> Session<'e> {
+    pub fn new(ex: &'e Extraction) -> Session<'e> {
+        Session {
+            ex,
+            index: OnceCell::new(),
+            separator: String::from("."),
+        }
+    }
+
+    /// Separate the names of the members in the paths of `get` by `separator` instead of
+    /// `.`, like `cfg/servers[0]/port` for `/`
+    pub fn with_separator(mut self, separator: &str) -> Session<'e> {
+        self.separator = String::from(separator);
+        self
+    }
+
+    /// Run the command `line`
+    pub fn dispatch(&self, line: &str) -> Result<Reply> {
+        let ex = self.ex;
+        let mut words = line.split_whitespace();
+        let cmd = match words.next() {
+            Some(c) => c,
+            None => return Ok(Reply::Output(String::new())),
+        };
+        let arg = words.next();
+        if words.next().is_some() {
+            fail!("Too many arguments for '{}'", cmd);
+        }
+        let mut out = String::new();
+        match (cmd, arg) {
+            ("get", Some(path)) => get(self, path, &mut out)?,
+            ("fields", Some(name)) => match ex.struct_types.get(name) {
+                Some(st) => {
+                    for f in &st.fields {
+                        writeln!(out, "{};", f.typ.declare(&f.name)).unwrap();
+                    }
+                }
+                None => fail!("struct {} not found", name),
+            },
+            ("list", Some(what)) => list(ex, what, &mut out)?,
+            ("filter", Some(pattern)) => {
+                let pattern = glob::Pattern::new(pattern)?;
+                for k in sorted_keys(ex) {
+                    if pattern.matches(&k.name) {
+                        writeln!(out, "{}", k).unwrap();
+                    }
+                }
+            }
+            ("help", None) => writeln!(out, "{}", HELP).unwrap(),
+            ("quit", None) | ("exit", None) => return Ok(Reply::Quit),
+            ("get", None) | ("fields", None) | ("list", None) | ("filter", None) => {
+                fail!("'{}' needs an argument, see 'help'", cmd)
+            }
+            _ => fail!("Unknown command '{}', see 'help'", line.trim()),
+        }
+        Ok(Reply::Output(out))
+    }
+}
+
+fn get(session: &Session, path: &str, out: &mut String) -> Result<()> {
+    let ex = session.ex;
+    if !path.contains(session.separator.as_str()) {
+        let mut found = false;
+        for k in sorted_keys(ex).into_iter().filter(|k| k.name == path) {
+            if !k.scope.is_empty() {
+                writeln!(out, "// in {}", k.scope.join("::")).unwrap();
+            }
+            write!(out, "{}", ex.values[k]).unwrap();
+            found = true;
+        }
+        // The elements of arrays of structs are values of their own, those of the other
+        // arrays are in the index
+        if found {
+            return Ok(());
+        } else if !path.contains('[') {
+            fail!("No value named {}", path);
+        }
+    }
+    let index = session
+        .index
+        .get_or_init(|| PathIndex::with_separator(&ex.values, &session.separator));
+    match index.get(path) {
+        Some(e) => writeln!(out, "{} = {:?}", path, e).unwrap(),
+        None => fail!("No value at {}", path),
+    }
+    Ok(())
+}
+
+fn list(ex: &Extraction, what: &str, out: &mut String) -> Result<()> {
+    let mut names: Vec<String> = match what {
+        "structs" => ex.struct_types.keys().cloned().collect(),
+        "enums" => ex.enum_types.keys().cloned().collect(),
+        "typedefs" => ex.typedefs.keys().cloned().collect(),
+        "functions" => ex.functions.iter().map(|f| f.to_string()).collect(),
+        "values" => {
+            for k in sorted_keys(ex) {
+                let kind = match &ex.values[k] {
+                    MyValue::Struct(s) => format!("struct {}", s.typ),
+                    MyValue::Scalar { .. } => String::from("scalar"),
+                };
+                writeln!(out, "{} ({})", k, kind).unwrap();
+            }
+            return Ok(());
+        }
+        _ => fail!(
+            "Can't list '{}', only structs, enums, typedefs, functions or values",
+            what
+        ),
+    };
+    names.sort_unstable();
+    for n in names {
+        writeln!(out, "{}", n).unwrap();
+    }
+    Ok(())
+}
@@ -0,0 +1,242 @@
+//! Render the collected types as Rust declarations, e.g. for the bindings of a C library
+
+use crate::ctype::{Derived, MyType};
+use crate::radix::{self, EnumRadix};
+use crate::typemap::TypeMap;
+use crate::Extraction;
+use std::fmt::Write;
+
+/// Field names that have to be written as raw identifiers
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in", "let",
+    "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return",
+    "static", "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use",
+    "virtual", "where", "while", "yield",
+];
+
+/// The enum and struct types of `ex` as `#[repr(C)]` Rust types
+///
+/// The C types are looked up in `map` first, types mapped there aren't declared. Otherwise the
+/// built-in types are mapped assuming the LP64 data model, e.g. `unsigned long` to `u64`, and
+/// typedefs are resolved. Types without a Rust equivalent are the unit type `()` with the C
+/// type as comment and a warning. Bit-fields are only kept as comment. The enum constants are
+/// written in hex or decimal as told by `radix`.
+pub fn render(ex: &Extraction, map: &TypeMap, radix: EnumRadix) -> String {
+    let r = Renderer { ex, map };
+    let mut out =
+        String::from("#![allow(non_camel_case_types, non_snake_case, non_upper_case_globals)]\n\n");
+    let mut enum_types: Vec<_> = ex.enum_types.values().collect();
+    enum_types.sort_by_key(|e| &e.name);
+    for et in enum_types {
+        if map.get(&format!("enum {}", et.name)).is_some() {
+            continue;
+        }
+        // GCC makes it an `unsigned int` unless there are negative constants
+        let repr = match &et.underlying {
+            Some(u) => r.underlying(u, &format!("enum {}", et.name)),
+            None if et.constants.iter().any(|(_, v)| v.unwrap_or(0) < 0) => String::from("i32"),
+            None => String::from("u32"),
+        };
+        writeln!(out, "pub type {} = {};", et.name, repr).unwrap();
+        let hex = radix::is_hex(et, radix);
+        for (name, value) in &et.constants {
+            match value {
+                Some(v) => writeln!(
+                    out,
+                    "pub const {}: {} = {};",
+                    name,
+                    et.name,
+                    radix::format(*v, hex)
+                )
+                .unwrap(),
+                None => writeln!(out, "// {}: the value isn't known", name).unwrap(),
+            }
+        }
+        writeln!(out).unwrap();
+    }
+    let mut struct_types: Vec<_> = ex.struct_types.values().collect();
+    struct_types.sort_by_key(|s| &s.name);
+    for st in struct_types {
+        if map.get(&format!("{} {}", st.keyword(), st.name)).is_some() {
+            continue;
+        }
+        writeln!(out, "#[repr(C)]\n#[derive(Clone, Copy)]").unwrap();
+        writeln!(out, "pub {} {} {{", st.keyword(), st.name).unwrap();
+        for f in &st.fields {
+            let what = format!("{}.{}", st.name, f.name);
+            match &f.bit_width {
+                Some(w) => writeln!(
+                    out,
+                    "    // {} : {}, bit-fields can't be declared in Rust",
+                    f.typ.declare(&f.name),
+                    w
+                )
+                .unwrap(),
+                None => writeln!(
+                    out,
+                    "    pub {}: {},",
+                    field_name(&f.name),
+                    r.rust(&f.typ, &what)
+                )
+                .unwrap(),
+            }
+        }
+        writeln!(out, "}}\n").unwrap();
+    }
+    out
+}
+
+struct Renderer<'e> {
+    ex: &'e Extraction,
+    map: &'e TypeMap,
+}
+
+impl Renderer<'_> {
+    /// The Rust type of `typ`, the type of `what` (for warnings)
+    fn rust(&self, typ: &MyType, what: &str) -> String {
+        let mut inner = typ.clone();
+        match inner.derived.pop() {
+            None => self.base(typ, what),
+            Some(Derived::Array(len)) => {
+                let len = match len {
+                    Some(l) if l.parse::<u64>().is_ok() => l,
+                    Some(l) => {
+                        log::warn!(
+                            target: "c_ast::type_map",
+                            "The length {} of {} may not be valid Rust",
+                            l,
+                            what
+                        );
+                        l
+                    }
+                    // A flexible array member
+                    None => String::from("0"),
+                };
+                format!("[{}; {}]", self.rust(&inner, what), len)
+            }
+            Some(Derived::Pointer(_)) => {
+                let konst = match inner.derived.last() {
+                    Some(Derived::Function { params, variadic }) => {
+                        let mut ret = inner.clone();
+                        ret.derived.pop();
+                        return self.function(&ret, params, *variadic, what);
+                    }
+                    Some(Derived::Pointer(qs)) => qs.iter().any(|q| q == "const"),
+                    Some(Derived::Array(_)) => false,
+                    None => inner.qualifiers.iter().any(|q| q == "const"),
+                };
+                let pointee = if is_void(&inner) {
+                    String::from("::std::os::raw::c_void")
+                } else {
+                    self.rust(&inner, what)
+                };
+                format!("*{} {}", if konst { "const" } else { "mut" }, pointee)
+            }
+            Some(Derived::Function { .. }) => self.unknown(typ, what),
+        }
+    }
+
+    /// A pointer to a function returning `ret`
+    fn function(
+        &self,
+        ret: &MyType,
+        params: &[(Option<String>, MyType)],
+        variadic: bool,
+        what: &str,
+    ) -> String {
+        let mut ps: Vec<String> = match params {
+            [(None, t)] if is_void(t) => Vec::new(),
+            _ => params.iter().map(|(_, t)| self.rust(t, what)).collect(),
+        };
+        if variadic {
+            ps.push(String::from("..."));
+        }
+        let ret = if is_void(ret) {
+            String::new()
+        } else {
+            format!(" -> {}", self.rust(ret, what))
+        };
+        format!("Option<unsafe extern \"C\" fn({}){}>", ps.join(", "), ret)
+    }
+
+    /// The Rust type of the base type `typ` without derived declarators
+    fn base(&self, typ: &MyType, what: &str) -> String {
+        let written = typ.specifiers.join(" ");
+        if let Some(t) = self.map.get(&written) {
+            return String::from(t);
+        }
+        if let [name] = typ.specifiers.as_slice() {
+            if let Some(target) = self.ex.typedefs.get(name) {
+                return self.rust(target, what);
+            }
+        }
+        if let Some(s) = typ.struct_name() {
+            if self.ex.struct_types.contains_key(s) {
+                return String::from(s);
+            }
+            return self.unknown(typ, what);
+        }
+        if let [e] = typ.specifiers.as_slice() {
+            if let Some(name) = e.strip_prefix("enum ") {
+                if self.ex.enum_types.contains_key(name) {
+                    return String::from(name);
+                }
+                // Only declared, with the underlying type if it was given
+                return match self.ex.enum_bases.get(name) {
+                    Some(u) => self.underlying(u, what),
+                    None => String::from("u32"),
+                };
+            }
+        }
+        let has = |w: &str| typ.specifiers.iter().any(|s| s == w);
+        if has("_Bool") {
+            return String::from("bool");
+        } else if has("float") && !has("_Complex") {
+            return String::from("f32");
+        } else if has("double") && !has("long") && !has("_Complex") {
+            return String::from("f64");
+        } else if has("double") || has("float") {
+            return self.unknown(typ, what);
+        }
+        match typ.int_range() {
+            Some((min, max)) => {
+                let bits = 128 - max.leading_zeros() + (min < 0) as u32;
+                format!("{}{}", if min < 0 { "i" } else { "u" }, bits)
+            }
+            None => self.unknown(typ, what),
+        }
+    }
+
+    /// The Rust type of the underlying type `base` of an enum like `unsigned char`
+    fn underlying(&self, base: &str, what: &str) -> String {
+        let typ = MyType {
+            specifiers: base.split(' ').map(String::from).collect(),
+            ..MyType::default()
+        };
+        self.rust(&typ, what)
+    }
+
+    /// The unit type as placeholder that still compiles, with the C type as comment
+    fn unknown(&self, typ: &MyType, what: &str) -> String {
+        log::warn!(
+            target: "c_ast::type_map",
+            "No Rust type for {} of {}, see --type-map",
+            typ,
+            what
+        );
+        format!("/* unknown: {} */ ()", typ.to_string().replace("*/", "* /"))
+    }
+}
+
+fn is_void(typ: &MyType) -> bool {
+    typ.derived.is_empty() && typ.specifiers == ["void"]
+}
+
+fn field_name(name: &str) -> String {
+    match name {
+        "self" | "Self" | "super" | "crate" => format!("{}_", name),
+        n if KEYWORDS.contains(&n) => format!("r#{}", n),
+        n => String::from(n),
+    }
+}
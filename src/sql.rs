@@ -0,0 +1,148 @@
+//! Render an extraction as SQL script for SQLite, e.g. `sqlite3 ast.db < extraction.sql`
+//!
+//! The tables are only created if they don't exist, so the scripts of several runs can be
+//! loaded into the same database. The values are stored twice: once per top level value with
+//! its JSON, and once per member with the path of the member, like `servers[0].port`, or
+//! `servers[0]/port` with another separator.
+
+use crate::{json, sorted_keys, tree, Extraction, MyExpression, MyValue};
+use std::fmt::Write;
+
+const SCHEMA: &str = "\
+CREATE TABLE IF NOT EXISTS struct_types (name TEXT, kind TEXT, anonymous INTEGER);
+CREATE TABLE IF NOT EXISTS fields (struct TEXT, position INTEGER, name TEXT, type TEXT, bit_width TEXT, doc TEXT);
+CREATE TABLE IF NOT EXISTS enum_constants (enum TEXT, position INTEGER, name TEXT, value INTEGER);
+CREATE TABLE IF NOT EXISTS typedefs (name TEXT, type TEXT);
+CREATE TABLE IF NOT EXISTS variables (profile TEXT, file TEXT, scope TEXT, name TEXT, type TEXT, json TEXT);
+CREATE TABLE IF NOT EXISTS members (profile TEXT, file TEXT, scope TEXT, variable TEXT, path TEXT, value TEXT, number INTEGER);
+";
+
+/// The statements creating the tables and inserting the types and values of `ex`
+///
+/// The rows of the values have the `profile` they were extracted with, if any. The scope of
+/// a value is joined by `::` like in the human output, empty for file scope values. The
+/// names in the paths of the members are separated by `separator`. The `number` of a member
+/// is only set for integers.
+pub fn dump(ex: &Extraction, profile: Option<&str>, separator: &str) -> String {
+    let mut out = String::from(SCHEMA);
+    out.push_str("BEGIN;\n");
+    let mut struct_types: Vec<_> = ex.struct_types.values().collect();
+    struct_types.sort_by_key(|st| &st.name);
+    for st in struct_types {
+        insert(
+            &mut out,
+            "struct_types",
+            &[text(&st.name), text(st.keyword()), st.anonymous.to_string()],
+        );
+        for (i, f) in st.fields.iter().enumerate() {
+            insert(
+                &mut out,
+                "fields",
+                &[
+                    text(&st.name),
+                    i.to_string(),
+                    text(&f.name),
+                    text(&f.typ.to_string()),
+                    optional(f.bit_width.as_deref()),
+                    optional(f.doc.as_deref()),
+                ],
+            );
+        }
+    }
+    let mut enum_types: Vec<_> = ex.enum_types.values().collect();
+    enum_types.sort_by_key(|et| &et.name);
+    for et in enum_types {
+        for (i, (name, value)) in et.constants.iter().enumerate() {
+            let value = value.map_or_else(|| String::from("NULL"), |v| v.to_string());
+            insert(
+                &mut out,
+                "enum_constants",
+                &[text(&et.name), i.to_string(), text(name), value],
+            );
+        }
+    }
+    let mut typedefs: Vec<_> = ex.typedefs.iter().collect();
+    typedefs.sort_by_key(|(n, _)| n.as_str());
+    for (name, typ) in typedefs {
+        insert(&mut out, "typedefs", &[text(name), text(&typ.to_string())]);
+    }
+    for k in sorted_keys(ex) {
+        let value = &ex.values[k];
+        let row = [
+            optional(profile),
+            optional(k.file.as_ref().map(|f| f.display().to_string()).as_deref()),
+            text(&k.scope.join("::")),
+        ];
+        let typ = match value {
+            MyValue::Struct(s) => text(&s.typ),
+            MyValue::Scalar { .. } => String::from("NULL"),
+        };
+        let json = json::value(k, value)["value"].to_string();
+        let variable = [&row[..], &[text(&k.name), typ, text(&json)]].concat();
+        insert(&mut out, "variables", &variable);
+        let mut members = Vec::new();
+        match value {
+            MyValue::Struct(s) => {
+                for (n, e) in &s.values {
+                    leaves(&mut members, n.clone(), e, separator);
+                }
+            }
+            MyValue::Scalar { value, .. } => leaves(&mut members, String::new(), value, separator),
+        }
+        for (path, e) in members {
+            let number = match e {
+                MyExpression::Integer { value, .. } => value.to_string(),
+                _ => String::from("NULL"),
+            };
+            let member = [
+                &row[..],
+                &[text(&k.name), text(&path), text(&tree::text(e)), number],
+            ]
+            .concat();
+            insert(&mut out, "members", &member);
+        }
+    }
+    out.push_str("COMMIT;\n");
+    out
+}
+
+/// The members of `e` that aren't structs or arrays, with their paths below `path`
+fn leaves<'e>(
+    out: &mut Vec<(String, &'e MyExpression)>,
+    path: String,
+    e: &'e MyExpression,
+    separator: &str,
+) {
+    match e {
+        MyExpression::Struct(s) => {
+            for (n, v) in &s.values {
+                leaves(out, format!("{}{}{}", path, separator, n), v, separator);
+            }
+        }
+        MyExpression::Array(xs) => {
+            for (i, x) in xs.iter().enumerate() {
+                leaves(out, format!("{}[{}]", path, i), x, separator);
+            }
+        }
+        // The elements that aren't set are zero and left out
+        MyExpression::SparseArray { entries, .. } => {
+            for (i, x) in entries {
+                leaves(out, format!("{}[{}]", path, i), x, separator);
+            }
+        }
+        leaf => out.push((path, leaf)),
+    }
+}
+
+fn insert(out: &mut String, table: &str, row: &[String]) {
+    writeln!(out, "INSERT INTO {} VALUES ({});", table, row.join(", ")).unwrap();
+}
+
+/// `s` as SQL string literal
+fn text(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "''"))
+}
+
+fn optional(s: Option<&str>) -> String {
+    s.map_or_else(|| String::from("NULL"), text)
+}
@@ -0,0 +1,72 @@
+//! Statistics over the extracted values, e.g. for documenting a struct type
+
+use crate::{tree, Extraction, MyExpression, MyValue};
+use std::collections::HashMap;
+
+/// The distinct values the member `field` of the struct type `typ` is initialized with and
+/// how often, the most frequent first
+///
+/// All values of type `typ` are counted, also those nested in other structs and in arrays.
+/// Instances that don't initialize `field` aren't counted. Structs and arrays are compared
+/// by their JSON, integers only by their value.
+pub fn field_values(ex: &Extraction, typ: &str, field: &str) -> Vec<(String, usize)> {
+    let mut counts = HashMap::new();
+    let mut count = |values: &[(String, MyExpression)]| {
+        for (n, v) in values {
+            if n == field {
+                *counts.entry(text(v)).or_insert(0) += 1;
+            }
+        }
+    };
+    for value in ex.values.values() {
+        match value {
+            MyValue::Struct(s) => {
+                if s.typ == typ {
+                    count(&s.values);
+                }
+                for (_, v) in &s.values {
+                    nested(v, typ, &mut count);
+                }
+            }
+            MyValue::Scalar { value, .. } => nested(value, typ, &mut count),
+        }
+    }
+    let mut counts: Vec<_> = counts.into_iter().collect();
+    counts.sort_unstable_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts
+}
+
+/// Call `count` with the members of the structs of type `typ` in `e`
+fn nested(e: &MyExpression, typ: &str, count: &mut impl FnMut(&[(String, MyExpression)])) {
+    match e {
+        MyExpression::Struct(s) => {
+            if s.typ == typ {
+                count(&s.values);
+            }
+            for (_, v) in &s.values {
+                nested(v, typ, count);
+            }
+        }
+        MyExpression::Array(xs) => {
+            for x in xs {
+                nested(x, typ, count);
+            }
+        }
+        MyExpression::SparseArray { entries, .. } => {
+            for (_, x) in entries {
+                nested(x, typ, count);
+            }
+        }
+        _ => (),
+    }
+}
+
+fn text(e: &MyExpression) -> String {
+    match e {
+        MyExpression::Integer { value, .. } => value.to_string(),
+        MyExpression::Struct(_) | MyExpression::Array(_) | MyExpression::SparseArray { .. } => {
+            serde_json::to_string(e).unwrap()
+        }
+        leaf => tree::text(leaf),
+    }
+}
@@ -0,0 +1,85 @@
+//! Render the extracted values as a tree, like the `tree` command
+
+use crate::{sorted_keys, Extraction, MyExpression, MyValue};
+use std::fmt::Write;
+
+/// All values of `ex` sorted by their key, nested structs and arrays as branches
+pub fn render(ex: &Extraction) -> String {
+    let mut out = String::new();
+    for k in sorted_keys(ex) {
+        match &ex.values[k] {
+            MyValue::Struct(s) => {
+                writeln!(out, "{} (struct {})", k, s.typ).unwrap();
+                children(&mut out, "", &s.values);
+            }
+            MyValue::Scalar { value, .. } => node(&mut out, "", &k.to_string(), value),
+        }
+    }
+    out
+}
+
+fn children(out: &mut String, prefix: &str, items: &[(String, MyExpression)]) {
+    for (i, (label, value)) in items.iter().enumerate() {
+        let (connector, continued) = if i + 1 == items.len() {
+            ("└── ", "    ")
+        } else {
+            ("├── ", "│   ")
+        };
+        out.push_str(prefix);
+        out.push_str(connector);
+        node(out, &format!("{}{}", prefix, continued), label, value);
+    }
+}
+
+/// Write `label` and either the value of a leaf or the children of a branch
+fn node(out: &mut String, prefix: &str, label: &str, value: &MyExpression) {
+    match value {
+        MyExpression::Struct(s) => {
+            writeln!(out, "{} (struct {})", label, s.typ).unwrap();
+            children(out, prefix, &s.values);
+        }
+        MyExpression::Array(xs) => {
+            writeln!(out, "{}", label).unwrap();
+            let items: Vec<_> = xs
+                .iter()
+                .enumerate()
+                .map(|(i, x)| (format!("[{}]", i), x.clone()))
+                .collect();
+            children(out, prefix, &items);
+        }
+        // Only the set elements
+        MyExpression::SparseArray { len, entries } => {
+            writeln!(out, "{} ({} elements)", label, len).unwrap();
+            let items: Vec<_> = entries
+                .iter()
+                .map(|(i, x)| (format!("[{}]", i), x.clone()))
+                .collect();
+            children(out, prefix, &items);
+        }
+        leaf => writeln!(out, "{} = {}", label, text(leaf)).unwrap(),
+    }
+}
+
+pub(crate) fn text(value: &MyExpression) -> String {
+    match value {
+        MyExpression::Integer { value, raw } if *raw == value.to_string() => raw.clone(),
+        MyExpression::Integer { value, raw } => format!("{} ({})", value, raw),
+        MyExpression::Float(f) => f.clone(),
+        MyExpression::Bool(b) => b.to_string(),
+        MyExpression::String(s) => s.clone(),
+        MyExpression::StringLiteral { raw, .. } | MyExpression::WideString { raw, .. } => {
+            raw.join(" ")
+        }
+        MyExpression::AddressOf(object) => format!("&{}", object),
+        MyExpression::FunctionRef(function) => function.clone(),
+        MyExpression::Null => String::from("NULL"),
+        MyExpression::Call { name, args } => {
+            let args: Vec<_> = args.iter().map(text).collect();
+            format!("{}({})", name, args.join(", "))
+        }
+        MyExpression::Other { category, .. } => format!("<{:?}>", category),
+        MyExpression::Array(_) | MyExpression::SparseArray { .. } | MyExpression::Struct(_) => {
+            String::new()
+        }
+    }
+}
@@ -0,0 +1,67 @@
+//! User defined names for C types in generated code, see [`TypeMap`]
+
+use crate::ctype;
+use crate::verify::{self, Expected};
+use crate::CAstError;
+use std::collections::HashMap;
+
+/// The target type names for C types, e.g. `my_handle_t` to `Handle`
+///
+/// The C types are written as in the declarations, a typedef name like `uint32_t` or the
+/// specifiers like `unsigned int` or `struct point_t`, in any order, see
+/// [`ctype::normalize`]. The renderers look a type up before resolving typedefs, so a mapped
+/// typedef is used as it is.
+#[derive(Debug, Default)]
+pub struct TypeMap {
+    types: HashMap<String, String>,
+}
+
+impl TypeMap {
+    /// Read the `"C type" = "target type"` lines of a TOML file
+    pub fn parse(text: &str) -> Result<TypeMap, CAstError> {
+        let mut types = HashMap::new();
+        for (line, c_type, target) in verify::parse_pairs(text)? {
+            match target {
+                Expected::String(t) => types.insert(ctype::normalize(&c_type), t),
+                v => {
+                    return Err(CAstError::Spec {
+                        line,
+                        message: format!("expected the target type as string, got {}", v),
+                    })
+                }
+            };
+        }
+        Ok(TypeMap { types })
+    }
+
+    pub fn get(&self, c_type: &str) -> Option<&str> {
+        self.types
+            .get(&ctype::normalize(c_type))
+            .map(|t| t.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalized_lookup() {
+        let map =
+            TypeMap::parse("\"unsigned int\" = \"u32\"\n\"struct point_t\" = \"Point\"\n").unwrap();
+        assert_eq!(map.get("int unsigned"), Some("u32"));
+        assert_eq!(map.get("struct point_t"), Some("Point"));
+        assert_eq!(map.get("int"), None);
+    }
+
+    #[test]
+    fn targets_are_strings() {
+        match TypeMap::parse("\n\"int\" = 4\n") {
+            Err(CAstError::Spec { line, message }) => {
+                assert_eq!(line, 2);
+                assert_eq!(message, "expected the target type as string, got 4");
+            }
+            r => panic!("{:?}", r),
+        }
+    }
+}
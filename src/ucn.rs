@@ -0,0 +1,134 @@
+//! Identifiers with universal character names like `caf\u00e9`
+//!
+//! The parser only accepts ASCII identifiers, so the UCNs are replaced by a placeholder like
+//! `caf__ucnu00e9` before parsing, see [`mangle`]. The names of the extraction are turned into
+//! the decoded or the raw form again afterwards, see [`crate::rename::decode_identifiers`].
+
+use std::borrow::Cow;
+use std::char;
+
+/// The start of a placeholder, followed by `u` and 4 or `U` and 8 hex digits
+const MARK: &str = "__ucn";
+
+/// Replace the UCNs outside of string and character literals of the preprocessed `text`
+pub fn mangle(text: &str) -> Cow<'_, str> {
+    if !text.contains("\\u") && !text.contains("\\U") {
+        return Cow::Borrowed(text);
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut quote = None;
+    let mut chars = text.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match (quote, c) {
+            (Some(_), '\\') => {
+                out.push(c);
+                if let Some((_, escaped)) = chars.next() {
+                    out.push(escaped);
+                }
+                continue;
+            }
+            (Some(q), _) if c == q || c == '\n' => quote = None,
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (None, '\\') => {
+                if let Some(len) = ucn_len(&text[i..]) {
+                    out.push_str(MARK);
+                    out.push_str(&text[i + 1..i + len]);
+                    chars.nth(len - 2);
+                    continue;
+                }
+            }
+            _ => (),
+        }
+        out.push(c);
+    }
+    Cow::Owned(out)
+}
+
+/// The identifier `name` with the placeholders decoded to their characters, `None` without
+/// placeholders
+///
+/// Placeholders of invalid characters are kept as raw UCN.
+pub fn decode(name: &str) -> Option<String> {
+    replace(name, |raw| {
+        u32::from_str_radix(&raw[2..], 16)
+            .ok()
+            .and_then(char::from_u32)
+            .map(String::from)
+            .unwrap_or_else(|| String::from(raw))
+    })
+}
+
+/// The identifier `name` with the placeholders turned into the raw UCNs of the preprocessed
+/// source again, `None` without placeholders
+pub fn raw(name: &str) -> Option<String> {
+    replace(name, |raw| raw.to_string())
+}
+
+/// Replace each placeholder of `name` by `f` of its raw UCN like `\u00e9`
+fn replace(name: &str, f: impl Fn(&str) -> String) -> Option<String> {
+    if !name.contains(MARK) {
+        return None;
+    }
+    let mut out = String::new();
+    let mut rest = name;
+    while let Some(at) = rest.find(MARK) {
+        out.push_str(&rest[..at]);
+        let raw = format!("\\{}", &rest[at + MARK.len()..]);
+        match ucn_len(&raw) {
+            Some(len) => {
+                out.push_str(&f(&raw[..len]));
+                rest = &rest[at + MARK.len() + len - 1..];
+            }
+            None => {
+                out.push_str(MARK);
+                rest = &rest[at + MARK.len()..];
+            }
+        }
+    }
+    out.push_str(rest);
+    Some(out)
+}
+
+/// The length of the UCN at the start of `s`, e.g. 6 for `\u00e9`
+fn ucn_len(s: &str) -> Option<usize> {
+    let digits = match s.get(..2)? {
+        "\\u" => 4,
+        "\\U" => 8,
+        _ => return None,
+    };
+    let hex = s.get(2..2 + digits)?;
+    if hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        Some(2 + digits)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mangled_outside_of_literals() {
+        assert_eq!(mangle("int x;"), Cow::Borrowed("int x;"));
+        assert_eq!(
+            mangle("int caf\\u00e9 = '\\u00e9'; char *s = \"\\U0001F600\";"),
+            "int caf__ucnu00e9 = '\\u00e9'; char *s = \"\\U0001F600\";"
+        );
+    }
+
+    #[test]
+    fn decoded() {
+        assert_eq!(decode("caf__ucnu00e9"), Some(String::from("café")));
+        assert_eq!(decode("__ucnU000003c0_x"), Some(String::from("π_x")));
+        assert_eq!(decode("plain"), None);
+        // A surrogate isn't a character
+        assert_eq!(decode("x__ucnud800"), Some(String::from("x\\ud800")));
+    }
+
+    #[test]
+    fn raw_again() {
+        assert_eq!(raw("caf__ucnu00e9"), Some(String::from("caf\\u00e9")));
+        assert_eq!(raw("__ucn_not"), Some(String::from("__ucn_not")));
+    }
+}
@@ -0,0 +1,506 @@
+//! Check extracted values against an expectation file
+//!
+//! The expectation file is a small subset of TOML: one `dotted.path = value` per line,
+//! optionally grouped below `[table]` headers, with `#` comments. Values may be integers,
+//! floats, booleans or basic strings.
+
+use crate::CAstError;
+use crate::{Encoding, MyExpression, MyValue, ValueKey};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::OnceLock;
+
+#[derive(Debug, PartialEq)]
+pub enum Expected {
+    Integer(i128),
+    Float(f64),
+    Bool(bool),
+    String(String),
+}
+
+impl fmt::Display for Expected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expected::Integer(i) => write!(f, "{}", i),
+            Expected::Float(x) => write!(f, "{}", x),
+            Expected::Bool(b) => write!(f, "{}", b),
+            Expected::String(s) => write!(f, "{:?}", s),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Expectation {
+    path: String,
+    expected: Expected,
+}
+
+pub fn parse_spec(text: &str) -> Result<Vec<Expectation>, CAstError> {
+    Ok(parse_pairs(text)?
+        .into_iter()
+        .map(|(_, path, expected)| Expectation { path, expected })
+        .collect())
+}
+
+/// The `key = value` lines of the TOML subset with their line numbers, the keys of a
+/// `[table]` prefixed by its name
+///
+/// Keys may be quoted, e.g. `"unsigned int" = "u32"`.
+pub(crate) fn parse_pairs(text: &str) -> Result<Vec<(usize, String, Expected)>, CAstError> {
+    let mut table = String::new();
+    let mut pairs = Vec::new();
+    for (no, line) in text.lines().enumerate() {
+        let line = strip_comment(line).trim();
+        if line.is_empty() {
+            continue;
+        }
+        if line.starts_with('[') && line.ends_with(']') {
+            table = String::from(line[1..line.len() - 1].trim());
+            continue;
+        }
+        let spec_error = |message| CAstError::Spec {
+            line: no + 1,
+            message,
+        };
+        let (key, value) = match split_pair(line) {
+            Some(kv) => kv,
+            None => return Err(spec_error(String::from("expected `path = value`"))),
+        };
+        let key = if key.starts_with('"') {
+            parse_string(key).map_err(spec_error)?
+        } else {
+            String::from(key)
+        };
+        let expected = parse_value(value).map_err(spec_error)?;
+        let path = if table.is_empty() {
+            key
+        } else {
+            format!("{}.{}", table, key)
+        };
+        pairs.push((no + 1, path, expected));
+    }
+    Ok(pairs)
+}
+
+/// Split `key = value` at the first `=` that isn't part of a quoted key
+fn split_pair(line: &str) -> Option<(&str, &str)> {
+    let start = match line.strip_prefix('"') {
+        Some(rest) => rest.find('"')? + 2,
+        None => 0,
+    };
+    let i = start + line[start..].find('=')?;
+    Some((line[..i].trim(), line[i + 1..].trim()))
+}
+
+fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '\\' if in_string => escaped = !escaped,
+            '"' if !escaped => in_string = !in_string,
+            '#' if !in_string => return &line[..i],
+            _ => escaped = false,
+        }
+    }
+    line
+}
+
+fn parse_value(s: &str) -> Result<Expected, String> {
+    if s.starts_with('"') {
+        return parse_string(s).map(Expected::String);
+    }
+    match s {
+        "true" => return Ok(Expected::Bool(true)),
+        "false" => return Ok(Expected::Bool(false)),
+        _ => (),
+    }
+    let digits = s.replace('_', "");
+    let (neg, unsigned) = match digits.strip_prefix('-') {
+        Some(d) => (true, d),
+        None => (false, digits.strip_prefix('+').unwrap_or(&digits)),
+    };
+    let int = if let Some(h) = unsigned.strip_prefix("0x") {
+        i128::from_str_radix(h, 16).ok()
+    } else if let Some(o) = unsigned.strip_prefix("0o") {
+        i128::from_str_radix(o, 8).ok()
+    } else if let Some(b) = unsigned.strip_prefix("0b") {
+        i128::from_str_radix(b, 2).ok()
+    } else {
+        unsigned.parse::<i128>().ok()
+    };
+    if let Some(i) = int {
+        return Ok(Expected::Integer(if neg { -i } else { i }));
+    }
+    match digits.parse::<f64>() {
+        Ok(x) => Ok(Expected::Float(x)),
+        Err(_) => Err(format!("unsupported value `{}`", s)),
+    }
+}
+
+fn parse_string(s: &str) -> Result<String, String> {
+    let mut out = String::new();
+    let mut chars = s[1..].chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if chars.as_str().trim().is_empty() => return Ok(out),
+            '"' => return Err(format!("trailing characters after string `{}`", s)),
+            '\\' => match chars.next() {
+                Some('n') => out.push('\n'),
+                Some('t') => out.push('\t'),
+                Some('r') => out.push('\r'),
+                Some('"') => out.push('"'),
+                Some('\\') => out.push('\\'),
+                e => {
+                    return Err(format!(
+                        "unsupported escape `\\{}`",
+                        e.map(String::from).unwrap_or_default()
+                    ))
+                }
+            },
+            c => out.push(c),
+        }
+    }
+    Err(format!("unterminated string `{}`", s))
+}
+
+/// Look up `path` (`name` or `name.field`, with nested structs `name.field.member`) in the
+/// file scope values
+pub fn lookup<'v>(values: &'v HashMap<ValueKey, MyValue>, path: &str) -> Option<&'v MyExpression> {
+    let mut segments = path.split('.');
+    let name = segments.next()?;
+    let val = values
+        .iter()
+        .find(|(k, _)| k.scope.is_empty() && k.name == name)
+        .map(|(_, v)| v)?;
+    let mut found = match (val, segments.next()) {
+        (MyValue::Scalar { value, .. }, None) => return Some(value),
+        (MyValue::Struct(s), Some(field)) => s.values.iter().find(|(n, _)| n == field)?,
+        _ => return None,
+    };
+    for field in segments {
+        found = match &found.1 {
+            MyExpression::Struct(s) => s.values.iter().find(|(n, _)| n == field)?,
+            _ => return None,
+        };
+    }
+    Some(&found.1)
+}
+
+/// The file scope values and their members by path, to look up many paths quickly
+///
+/// The paths are those of [`lookup`], `name` for scalars and `name.field.member` for the
+/// members of structs, and also the elements of arrays like `name.servers[0].port`. The
+/// nested structs and arrays are in the index as well as their members and elements, of
+/// sparse arrays only the set elements.
+pub struct PathIndex<'v> {
+    paths: HashMap<String, &'v MyExpression>,
+    /// Between the names of the members, `.` unless told otherwise
+    separator: String,
+}
+
+impl<'v> PathIndex<'v> {
+    pub fn new(values: &'v HashMap<ValueKey, MyValue>) -> PathIndex<'v> {
+        PathIndex::with_separator(values, ".")
+    }
+
+    /// The index with the names of the members separated by `separator` instead of `.`,
+    /// like `name/servers[0]/port` for `/`
+    pub fn with_separator(
+        values: &'v HashMap<ValueKey, MyValue>,
+        separator: &str,
+    ) -> PathIndex<'v> {
+        let mut index = PathIndex {
+            paths: HashMap::new(),
+            separator: String::from(separator),
+        };
+        for (k, v) in values.iter().filter(|(k, _)| k.scope.is_empty()) {
+            match v {
+                MyValue::Scalar { value, .. } => index.add(k.name.clone(), value),
+                MyValue::Struct(s) => index.add_members(&k.name, &s.values),
+            }
+        }
+        index
+    }
+
+    /// The value or member at `path`, like [`lookup`]
+    pub fn get(&self, path: &str) -> Option<&'v MyExpression> {
+        self.paths.get(path).copied()
+    }
+
+    /// All paths of the index, in no particular order
+    pub fn paths(&self) -> impl Iterator<Item = &str> {
+        self.paths.keys().map(String::as_str)
+    }
+
+    /// Add `e` at `path` unless a value of the same name was added before, like [`lookup`]
+    /// takes the first one it finds
+    fn add(&mut self, path: String, e: &'v MyExpression) {
+        if self.paths.contains_key(&path) {
+            return;
+        }
+        match e {
+            MyExpression::Struct(s) => self.add_members(&path, &s.values),
+            MyExpression::Array(xs) => {
+                for (i, x) in xs.iter().enumerate() {
+                    self.add(format!("{}[{}]", path, i), x);
+                }
+            }
+            MyExpression::SparseArray { entries, .. } => {
+                for (i, x) in entries {
+                    self.add(format!("{}[{}]", path, i), x);
+                }
+            }
+            _ => (),
+        }
+        self.paths.insert(path, e);
+    }
+
+    fn add_members(&mut self, path: &str, values: &'v [(String, MyExpression)]) {
+        for (n, e) in values {
+            self.add(format!("{}{}{}", path, self.separator, n), e);
+        }
+    }
+}
+
+/// What a JSON pointer refers to, a whole top level value or a part of it
+#[derive(Debug)]
+pub enum Found<'v> {
+    Value(&'v MyValue),
+    Expression(&'v MyExpression),
+}
+
+/// Look up the RFC 6901 JSON pointer `pointer` like `/config/servers/0/port` in the file
+/// scope values
+///
+/// Unlike with [`lookup`] the segments may contain dots, `~1` stands for `/` and `~0` for `~`.
+/// Array elements are addressed by their index.
+pub fn lookup_pointer<'v>(
+    values: &'v HashMap<ValueKey, MyValue>,
+    pointer: &str,
+) -> Result<Found<'v>, CAstError> {
+    let not_found = |why: String| CAstError::Query(format!("{} not found, {}", pointer, why));
+    let mut segments = match pointer.strip_prefix('/') {
+        Some(rest) => rest
+            .split('/')
+            .map(|s| s.replace("~1", "/").replace("~0", "~")),
+        None => return Err(not_found(String::from("a pointer starts with '/'"))),
+    };
+    let name = segments.next().unwrap_or_default();
+    let mut found = match values
+        .iter()
+        .find(|(k, _)| k.scope.is_empty() && k.name == name)
+    {
+        Some((_, MyValue::Scalar { value, .. })) => Found::Expression(value),
+        Some((_, v)) => Found::Value(v),
+        None => return Err(not_found(format!("no value named '{}'", name))),
+    };
+    let mut path = format!("/{}", name);
+    for segment in segments {
+        let next = match found {
+            Found::Value(MyValue::Struct(s)) | Found::Expression(MyExpression::Struct(s)) => s
+                .values
+                .iter()
+                .find(|(n, _)| *n == segment)
+                .map(|(_, e)| e)
+                .ok_or_else(|| format!("{} has no member '{}'", path, segment)),
+            Found::Expression(MyExpression::Array(items)) => match index(&segment) {
+                Some(i) => items.get(i).ok_or_else(|| {
+                    format!("{} has {} elements, no index {}", path, items.len(), i)
+                }),
+                None => Err(format!(
+                    "'{}' isn't an index into the array {}",
+                    segment, path
+                )),
+            },
+            Found::Expression(MyExpression::SparseArray { len, entries }) => {
+                match index(&segment) {
+                    Some(i) if i < *len => Ok(entries
+                        .iter()
+                        .find(|(at, _)| *at == i)
+                        .map_or_else(|| zero(), |(_, e)| e)),
+                    Some(i) => Err(format!("{} has {} elements, no index {}", path, len, i)),
+                    None => Err(format!(
+                        "'{}' isn't an index into the array {}",
+                        segment, path
+                    )),
+                }
+            }
+            _ => Err(format!("{} has no members or elements", path)),
+        };
+        found = Found::Expression(next.map_err(not_found)?);
+        path = format!("{}/{}", path, segment);
+    }
+    Ok(found)
+}
+
+/// The value of the elements of a [`MyExpression::SparseArray`] that aren't set
+fn zero() -> &'static MyExpression {
+    static ZERO: OnceLock<MyExpression> = OnceLock::new();
+    ZERO.get_or_init(|| MyExpression::Integer {
+        value: 0,
+        raw: String::from("0"),
+    })
+}
+
+/// An array index of a JSON pointer, only digits and without leading zeros
+fn index(segment: &str) -> Option<usize> {
+    match segment.as_bytes() {
+        [b'0'] => Some(0),
+        [b'1'..=b'9', rest @ ..] if rest.iter().all(u8::is_ascii_digit) => segment.parse().ok(),
+        _ => None,
+    }
+}
+
+fn matches(found: &MyExpression, expected: &Expected) -> bool {
+    match (found, expected) {
+        (MyExpression::Integer { value, .. }, Expected::Integer(i)) => value == i,
+        (MyExpression::Integer { value, .. }, Expected::Bool(b)) => (*value != 0) == *b,
+        (MyExpression::Integer { value, .. }, Expected::Float(x)) => *value as f64 == *x,
+        (MyExpression::Bool(found), Expected::Bool(b)) => found == b,
+        (MyExpression::Bool(found), Expected::Integer(i)) => i128::from(*found) == *i,
+        (MyExpression::Float(f), Expected::Float(x)) => parse_float(f) == Some(*x),
+        (MyExpression::Float(f), Expected::Integer(i)) => parse_float(f) == Some(*i as f64),
+        (MyExpression::StringLiteral { bytes: Some(b), .. }, Expected::String(s)) => {
+            b == s.as_bytes()
+        }
+        (MyExpression::StringLiteral { raw, .. }, Expected::String(s)) => {
+            let joined: String = raw.iter().map(|p| unquote(p, "\"")).collect();
+            joined == *s
+        }
+        (
+            MyExpression::WideString {
+                encoding,
+                units: Some(units),
+                ..
+            },
+            Expected::String(s),
+        ) => match encoding {
+            Encoding::Utf16 => s.encode_utf16().map(u32::from).eq(units.iter().copied()),
+            Encoding::Wide | Encoding::Utf32 => s.chars().map(u32::from).eq(units.iter().copied()),
+        },
+        (MyExpression::String(c), Expected::String(s)) => unquote(c, "'") == s,
+        _ => false,
+    }
+}
+
+fn parse_float(f: &str) -> Option<f64> {
+    f.trim_end_matches(['f', 'F', 'l', 'L']).parse().ok()
+}
+
+fn unquote<'s>(s: &'s str, quote: &str) -> &'s str {
+    s.strip_prefix(quote)
+        .and_then(|s| s.strip_suffix(quote))
+        .unwrap_or(s)
+}
+
+/// Check all expectations and return a description of every one that isn't met
+pub fn verify(values: &HashMap<ValueKey, MyValue>, spec: &[Expectation]) -> Vec<String> {
+    let index = PathIndex::new(values);
+    let mut failures = Vec::new();
+    for exp in spec {
+        match index.get(&exp.path) {
+            Some(found) if matches(found, &exp.expected) => (),
+            Some(found) => failures.push(format!(
+                "{}: expected {}, found {:?}",
+                exp.path, exp.expected, found
+            )),
+            None => failures.push(format!("{}: not found", exp.path)),
+        }
+    }
+    failures
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Extraction, MyVisitor};
+    use lang_c::driver::{parse_preprocessed, Config};
+    use lang_c::visit::Visit;
+    use std::collections::BTreeSet;
+
+    /// The values of the C `source`
+    fn extracted(source: &str) -> Extraction {
+        let parsed = parse_preprocessed(&Config::default(), String::from(source)).unwrap();
+        let mut ex = Extraction::default();
+        MyVisitor::new(None, &mut ex).visit_translation_unit(&parsed.unit);
+        ex
+    }
+
+    #[test]
+    fn indexed_paths() {
+        let ex = extracted(
+            "struct net { int port; };
+             struct server { int port; };
+             struct cfg { struct net net; struct server servers[2]; };
+             struct cfg cfg = { .net = { .port = 80 }, .servers = { { 1 }, { 2 } } };
+             int lut[256] = { [3] = 1, [200] = 2 };",
+        );
+        let paths = |separator| -> BTreeSet<String> {
+            let index = PathIndex::with_separator(&ex.values, separator);
+            index.paths().map(String::from).collect()
+        };
+        let expected = |paths: &[&str]| paths.iter().map(|p| String::from(*p)).collect();
+        assert_eq!(
+            paths("."),
+            expected(&[
+                "cfg.net",
+                "cfg.net.port",
+                "cfg.servers",
+                "cfg.servers[0]",
+                "cfg.servers[0].port",
+                "cfg.servers[1]",
+                "cfg.servers[1].port",
+                "lut",
+                "lut[200]",
+                "lut[3]",
+            ])
+        );
+        assert!(paths("/").contains("cfg/servers[1]/port"));
+        let index = PathIndex::new(&ex.values);
+        assert!(matches!(
+            index.get("cfg.servers[1].port"),
+            Some(MyExpression::Integer { value: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn pairs() {
+        let spec = "# limits\nmax = 0x10 # hex\n[net]\nport = 8_080\nname = \"a#b\\n\"\nup = true\nratio = -1.5\n";
+        let pairs = parse_pairs(spec).unwrap();
+        assert_eq!(
+            pairs,
+            vec![
+                (2, String::from("max"), Expected::Integer(16)),
+                (4, String::from("net.port"), Expected::Integer(8080)),
+                (
+                    5,
+                    String::from("net.name"),
+                    Expected::String(String::from("a#b\n"))
+                ),
+                (6, String::from("net.up"), Expected::Bool(true)),
+                (7, String::from("net.ratio"), Expected::Float(-1.5)),
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_lines() {
+        let line_of = |text| match parse_spec(text) {
+            Err(CAstError::Spec { line, message }) => (line, message),
+            r => panic!("{:?}", r),
+        };
+        assert_eq!(
+            line_of("a = 1\nb"),
+            (2, String::from("expected `path = value`"))
+        );
+        assert_eq!(
+            line_of("a = \"open"),
+            (1, String::from("unterminated string `\"open`"))
+        );
+        assert_eq!(
+            line_of("a = nope"),
+            (1, String::from("unsupported value `nope`"))
+        );
+    }
+}
@@ -0,0 +1,242 @@
+//! Find the input files in a directory tree
+
+use crate::CAstError;
+use glob::Pattern;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use walkdir::{DirEntry, WalkDir};
+
+/// All files below `dir` whose path relative to `dir` matches `pattern`, sorted
+///
+/// With `respect_gitignore` the `.gitignore` files found on the way are honored and `.git`
+/// directories are skipped. With `since` only the files modified at or after it are found.
+pub fn find(
+    dir: &Path,
+    pattern: &Pattern,
+    respect_gitignore: bool,
+    since: Option<SystemTime>,
+) -> Result<Vec<PathBuf>, CAstError> {
+    let ignores = RefCell::new(HashMap::new());
+    let mut files = Vec::new();
+    let walker = WalkDir::new(dir)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|e| {
+            !respect_gitignore
+                || e.depth() == 0
+                || !(e.file_name() == ".git" || ignored(dir, e, &mut ignores.borrow_mut()))
+        });
+    for entry in walker {
+        let entry = entry?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let rel = entry
+            .path()
+            .strip_prefix(dir)
+            .unwrap_or_else(|_| entry.path());
+        if !pattern.matches_path(rel) {
+            log::debug!("{} doesn't match {}", rel.display(), pattern);
+        } else if since.is_some_and(|t| modified(&entry).is_some_and(|m| m < t)) {
+            log::debug!("{} was modified before --since", rel.display());
+        } else {
+            files.push(entry.into_path());
+        }
+    }
+    Ok(files)
+}
+
+fn modified(entry: &DirEntry) -> Option<SystemTime> {
+    entry.metadata().ok()?.modified().ok()
+}
+
+/// The point in time `spec` stands for, a time ago like `2h`, `3d` or `1h30m` (with the
+/// units `s`, `m`, `h`, `d` and `w`), a UTC date like `2024-05-01` or `2024-05-01T12:30`,
+/// or seconds since the Unix epoch like `@1714566600`
+pub fn since(spec: &str, now: SystemTime) -> Option<SystemTime> {
+    if let Some(secs) = spec.strip_prefix('@') {
+        return SystemTime::UNIX_EPOCH.checked_add(Duration::from_secs(secs.parse().ok()?));
+    }
+    if spec.starts_with(|c: char| c.is_ascii_digit()) && spec.contains('-') {
+        return timestamp(spec);
+    }
+    let mut ago = 0u64;
+    let mut rest = spec;
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let n: u64 = rest[..digits].parse().ok()?;
+        let unit = rest[digits..].chars().next()?;
+        let secs = match unit {
+            's' => 1,
+            'm' => 60,
+            'h' => 60 * 60,
+            'd' => 24 * 60 * 60,
+            'w' => 7 * 24 * 60 * 60,
+            _ => return None,
+        };
+        ago = ago.checked_add(n.checked_mul(secs)?)?;
+        rest = &rest[digits + 1..];
+    }
+    now.checked_sub(Duration::from_secs(ago))
+}
+
+/// `YYYY-MM-DD` with an optional time `THH:MM[:SS]` (or after a space), in UTC
+fn timestamp(spec: &str) -> Option<SystemTime> {
+    let (date, time) = match spec.split_once(['T', ' ']) {
+        Some((date, time)) => (date, Some(time)),
+        None => (spec, None),
+    };
+    let mut parts = date.splitn(3, '-').map(|p| p.parse::<i64>().ok());
+    let (year, month, day) = (parts.next()??, parts.next()??, parts.next()??);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    let mut secs = days_from_civil(year, month, day) * 24 * 60 * 60;
+    if let Some(time) = time {
+        let mut parts = time.splitn(3, ':').map(|p| p.parse::<i64>().ok());
+        let (hour, minute) = (parts.next()??, parts.next()??);
+        let second = parts.next().unwrap_or(Some(0))?;
+        if !(0..24).contains(&hour) || !(0..60).contains(&minute) || !(0..61).contains(&second) {
+            return None;
+        }
+        secs += hour * 60 * 60 + minute * 60 + second;
+    }
+    SystemTime::UNIX_EPOCH.checked_add(Duration::from_secs(u64::try_from(secs).ok()?))
+}
+
+/// The days since 1970-01-01 of a date of the proleptic Gregorian calendar
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    // The years counted from March, so the leap day is the last day of a year
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let year_of_era = y - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// One line of a `.gitignore`
+struct Rule {
+    pattern: Pattern,
+    negated: bool,
+    dir_only: bool,
+    /// Matched against the path relative to the `.gitignore`, otherwise against the name
+    anchored: bool,
+}
+
+fn load(dir: &Path) -> Vec<Rule> {
+    let text = match fs::read_to_string(dir.join(".gitignore")) {
+        Ok(t) => t,
+        Err(_) => return Vec::new(),
+    };
+    text.lines()
+        .filter_map(|l| {
+            let l = l.trim_end();
+            if l.is_empty() || l.starts_with('#') {
+                return None;
+            }
+            let (negated, l) = match l.strip_prefix('!') {
+                Some(l) => (true, l),
+                None => (false, l),
+            };
+            let (dir_only, l) = match l.strip_suffix('/') {
+                Some(l) => (true, l),
+                None => (false, l),
+            };
+            let anchored = l.contains('/');
+            let l = l.strip_prefix('/').unwrap_or(l);
+            match Pattern::new(l) {
+                Ok(pattern) => Some(Rule {
+                    pattern,
+                    negated,
+                    dir_only,
+                    anchored,
+                }),
+                Err(e) => {
+                    log::warn!(
+                        target: "c_ast::gitignore",
+                        "Ignoring invalid pattern '{}' in {}: {}",
+                        l,
+                        dir.display(),
+                        e
+                    );
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// Whether `entry` is ignored by the `.gitignore` files between `root` and the entry
+///
+/// As with git, the last matching rule decides and deeper files override shallower ones.
+fn ignored(root: &Path, entry: &DirEntry, cache: &mut HashMap<PathBuf, Vec<Rule>>) -> bool {
+    let path = entry.path();
+    let is_dir = entry.file_type().is_dir();
+    let mut result = false;
+    for base in path.ancestors().skip(1) {
+        if !base.starts_with(root) {
+            break;
+        }
+        let rules = cache
+            .entry(base.to_path_buf())
+            .or_insert_with(|| load(base));
+        let rel = match path.strip_prefix(base) {
+            Ok(rel) => rel,
+            Err(_) => continue,
+        };
+        let matched = rules.iter().rev().find(|r| {
+            (is_dir || !r.dir_only)
+                && if r.anchored {
+                    r.pattern.matches_path(rel)
+                } else {
+                    r.pattern.matches(&entry.file_name().to_string_lossy())
+                }
+        });
+        if let Some(r) = matched {
+            result = !r.negated;
+            // The innermost .gitignore with a matching rule wins
+            break;
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(secs: u64) -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn ago() {
+        let now = at(1_000_000);
+        assert_eq!(since("2h", now), Some(at(1_000_000 - 7200)));
+        assert_eq!(since("1h30m", now), Some(at(1_000_000 - 5400)));
+        assert_eq!(since("1w", now), Some(at(1_000_000 - 604_800)));
+        assert_eq!(since("2x", now), None);
+        assert_eq!(since("h", now), None);
+        assert_eq!(since("12", now), None);
+    }
+
+    #[test]
+    fn dates() {
+        let now = at(0);
+        assert_eq!(since("@1714566600", now), Some(at(1_714_566_600)));
+        assert_eq!(since("2024-05-01", now), Some(at(1_714_521_600)));
+        assert_eq!(since("2024-05-01T12:30", now), Some(at(1_714_566_600)));
+        assert_eq!(since("2024-05-01 12:30:15", now), Some(at(1_714_566_615)));
+        assert_eq!(since("2024-02-29", now), Some(at(1_709_164_800)));
+        assert_eq!(since("2024-13-01", now), None);
+        assert_eq!(since("2024-05-01T24:00", now), None);
+        assert_eq!(since("1969-12-31", now), None);
+    }
+}
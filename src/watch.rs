@@ -0,0 +1,188 @@
+//! Wait for the input files to change, by polling their modification times
+
+use std::fs;
+use std::path::PathBuf;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// How often the modification times are checked
+const POLL: Duration = Duration::from_millis(500);
+/// How long the files have to stay unchanged after a change, e.g. while an editor saves
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+pub struct Watcher {
+    files: Vec<PathBuf>,
+    stamps: Vec<Option<SystemTime>>,
+    poll: Duration,
+    debounce: Duration,
+    /// How long to wait for a change before giving up, forever if not given
+    timeout: Option<Duration>,
+}
+
+impl Watcher {
+    /// Watch `files` for changes after their current state
+    pub fn new(files: &[PathBuf]) -> Watcher {
+        let mut w = Watcher {
+            files: files.to_vec(),
+            stamps: Vec::new(),
+            poll: POLL,
+            debounce: DEBOUNCE,
+            timeout: None,
+        };
+        w.changed();
+        w
+    }
+
+    /// Check the files every `poll` instead of every 500 ms, and wait for them to stay
+    /// unchanged for `debounce` instead of 200 ms after a change
+    pub fn with_intervals(mut self, poll: Duration, debounce: Duration) -> Watcher {
+        self.poll = poll;
+        self.debounce = debounce;
+        self
+    }
+
+    /// Stop waiting if nothing changed for `timeout`
+    pub fn with_timeout(mut self, timeout: Duration) -> Watcher {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Whether a file was modified, created or removed since the last call
+    pub fn changed(&mut self) -> bool {
+        let stamps: Vec<_> = self
+            .files
+            .iter()
+            .map(|f| fs::metadata(f).and_then(|m| m.modified()).ok())
+            .collect();
+        let changed = stamps != self.stamps;
+        self.stamps = stamps;
+        changed
+    }
+
+    /// Block until a file changed and then didn't change for a moment, `false` if nothing
+    /// changed before the timeout
+    pub fn wait(&mut self) -> bool {
+        let start = Instant::now();
+        while !self.changed() {
+            if self.timeout.is_some_and(|t| start.elapsed() >= t) {
+                return false;
+            }
+            thread::sleep(self.poll);
+        }
+        loop {
+            thread::sleep(self.debounce);
+            if !self.changed() {
+                return true;
+            }
+        }
+    }
+}
+
+/// Call `run` once per change of the files of `watcher`, until it stops waiting
+///
+/// The changes following each other within the debounce interval are one change.
+pub fn rerun(watcher: &mut Watcher, mut run: impl FnMut()) {
+    while watcher.wait() {
+        run();
+    }
+}
+
+/// The time of day of `t` in UTC as `HH:MM:SS`
+pub fn clock(t: SystemTime) -> String {
+    let secs = t.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    format!(
+        "{:02}:{:02}:{:02}",
+        secs / 3600 % 24,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+
+    /// A new file `name` in the temporary directory
+    fn file(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("c-ast-watch-{}.c", name));
+        fs::write(&path, "int x;\n").unwrap();
+        path
+    }
+
+    /// Set the modification time of `path` `secs` seconds ahead, so each touch is a change
+    /// regardless of the resolution of the file system
+    fn touch(path: &PathBuf, secs: u64) {
+        let t = SystemTime::now() + Duration::from_secs(secs);
+        File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(t)
+            .unwrap();
+    }
+
+    fn watcher(path: &PathBuf) -> Watcher {
+        Watcher::new(std::slice::from_ref(path))
+            .with_intervals(Duration::from_millis(10), Duration::from_millis(200))
+            .with_timeout(Duration::from_millis(800))
+    }
+
+    #[test]
+    fn changes() {
+        let path = file("changes");
+        let mut w = watcher(&path);
+        assert!(!w.changed());
+        touch(&path, 1);
+        assert!(w.changed());
+        assert!(!w.changed());
+        fs::remove_file(&path).unwrap();
+        assert!(w.changed());
+        assert!(!w.wait());
+    }
+
+    #[test]
+    fn one_run_per_change() {
+        let path = file("one_run");
+        let mut w = watcher(&path);
+        let toucher = {
+            let path = path.clone();
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(50));
+                touch(&path, 1);
+            })
+        };
+        let mut runs = 0;
+        rerun(&mut w, || runs += 1);
+        toucher.join().unwrap();
+        assert_eq!(runs, 1);
+    }
+
+    #[test]
+    fn changes_within_the_debounce_interval_are_one() {
+        let path = file("debounce");
+        let mut w = watcher(&path);
+        let toucher = {
+            let path = path.clone();
+            thread::spawn(move || {
+                for i in 1..=3 {
+                    thread::sleep(Duration::from_millis(20));
+                    touch(&path, i);
+                }
+                // Then another change after the debounce interval
+                thread::sleep(Duration::from_millis(500));
+                touch(&path, 4);
+            })
+        };
+        let mut runs = 0;
+        rerun(&mut w, || runs += 1);
+        toucher.join().unwrap();
+        assert_eq!(runs, 2);
+    }
+
+    #[test]
+    fn clock_in_utc() {
+        let t = UNIX_EPOCH + Duration::from_secs(86_400 + 3_723);
+        assert_eq!(clock(t), "01:02:03");
+    }
+}
@@ -0,0 +1,658 @@
+//! The command line options of c-ast
+
+mod common;
+
+use common::{at, fixtures, has, int, json, logged, names, run, source, stderr, stdout, warnings};
+use serde_json::{json, Value};
+use std::fs::{self, File};
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+fn lines(text: &str) -> Vec<Value> {
+    text.lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect()
+}
+
+fn jsonl(args: &[&str]) -> Vec<Value> {
+    let mut all = vec!["-q", "--format", "jsonl"];
+    all.extend_from_slice(args);
+    lines(&stdout(&all))
+}
+
+fn failure(args: &[&str]) -> String {
+    let out = run(args);
+    assert!(!out.status.success(), "c-ast {:?} succeeded", args);
+    String::from_utf8(out.stderr).unwrap()
+}
+
+#[test]
+fn several_files() {
+    let values = jsonl(&["keyed/first.c", "keyed/second.c"]);
+    let files: Vec<_> = values.iter().map(|v| v["file"].as_str().unwrap()).collect();
+    assert_eq!(files, ["keyed/first.c", "keyed/second.c"]);
+}
+
+#[test]
+fn format_from_the_environment() {
+    let out = std::process::Command::new(env!("CARGO_BIN_EXE_c-ast"))
+        .current_dir(fixtures())
+        .args(["-q", "keyed/second.c"])
+        .env("C_AST_FORMAT", "jsonl")
+        .output()
+        .unwrap();
+    let values = lines(&String::from_utf8(out.stdout).unwrap());
+    assert_eq!(values[0]["name"], "answer");
+    // The flag wins
+    let out = std::process::Command::new(env!("CARGO_BIN_EXE_c-ast"))
+        .current_dir(fixtures())
+        .args(["-q", "--format", "human", "keyed/second.c"])
+        .env("C_AST_FORMAT", "jsonl")
+        .output()
+        .unwrap();
+    assert!(String::from_utf8(out.stdout)
+        .unwrap()
+        .contains("answer = Integer { value: 42, raw: \"42\" }"));
+}
+
+#[test]
+fn profiles() {
+    let values = jsonl(&["--profile", "a:BOARD_A", "--profile", "b:", "test.c"]);
+    let leds: Vec<_> = values
+        .iter()
+        .filter(|v| v["name"] == "board_leds")
+        .map(|v| {
+            (
+                v["profile"].clone(),
+                v["value"]["Scalar"]["value"]["Integer"]["value"].clone(),
+            )
+        })
+        .collect();
+    assert_eq!(leds, [(json!("a"), json!(4)), (json!("b"), json!(2))]);
+}
+
+#[test]
+fn require_c_extension() {
+    let err = failure(&["--require-c-extension", "types.toml"]);
+    assert!(err.contains("types.toml doesn't have one of the extensions c, h, i"));
+    assert!(run(&["-q", "--require-c-extension", "keyed/second.c"])
+        .status
+        .success());
+}
+
+#[test]
+fn dir_and_glob() {
+    let mut values: Vec<_> = jsonl(&["--dir", "dir"])
+        .into_iter()
+        .map(|v| v["name"].as_str().unwrap().to_string())
+        .collect();
+    values.sort();
+    assert_eq!(values, ["generated", "nested", "scratch", "top"]);
+    let headers = jsonl(&["--dir", "dir", "--glob", "**/*.h"]);
+    assert_eq!(headers.len(), 1);
+    assert_eq!(headers[0]["file"], "dir/sub/nested.h");
+}
+
+#[test]
+fn dir_human_output_is_sorted() {
+    let out = stdout(&["-q", "--dir", "dir"]);
+    let files: Vec<_> = out.lines().filter(|l| l.starts_with("// in ")).collect();
+    assert_eq!(
+        files,
+        [
+            "// in dir/sub/generated/gen.c",
+            "// in dir/sub/nested.h",
+            "// in dir/sub/scratch.c",
+            "// in dir/top.c",
+        ]
+    );
+    let first = stdout(&["-q", "test.c"]);
+    for _ in 0..3 {
+        assert_eq!(stdout(&["-q", "test.c"]), first);
+    }
+}
+
+#[test]
+fn respect_gitignore() {
+    let mut values: Vec<_> = jsonl(&["--dir", "dir", "--respect-gitignore"])
+        .into_iter()
+        .map(|v| v["name"].as_str().unwrap().to_string())
+        .collect();
+    values.sort();
+    assert_eq!(values, ["nested", "top"]);
+}
+
+#[test]
+fn since() {
+    // git doesn't keep the modification times, so they are set on a copy
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("since");
+    fs::create_dir_all(&dir).unwrap();
+    let now = SystemTime::now();
+    for (name, age) in [("fresh.c", 0), ("stale.c", 10)] {
+        let path = dir.join(name);
+        fs::copy(fixtures().join("since").join(name), &path).unwrap();
+        let modified = now - Duration::from_secs(age * 24 * 60 * 60);
+        File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+    }
+    let dir = dir.display().to_string();
+    let names: Vec<_> = jsonl(&["--dir", &dir, "--since", "3d"])
+        .into_iter()
+        .map(|v| v["name"].clone())
+        .collect();
+    assert_eq!(names, [json!("since_fresh")]);
+    assert_eq!(jsonl(&["--dir", &dir, "--since", "30d"]).len(), 2);
+    let err = failure(&["--since", "3d", "keyed/second.c"]);
+    assert!(err.contains("--dir"), "{}", err);
+}
+
+#[test]
+fn summary_json() {
+    let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join("summary.json");
+    let path = path.display().to_string();
+    stdout(&["-q", "--summary-json", &path, "test.c"]);
+    let summary: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(summary["files"], json!(["test.c"]));
+    let doc = json(&["-q", "test.c"]);
+    assert_eq!(
+        summary["struct_types"],
+        doc["struct_types"].as_object().unwrap().len()
+    );
+    assert_eq!(summary["warnings"]["c_ast::range"], 5);
+    assert_eq!(summary["warnings"]["c_ast::shift"], 1);
+    assert!(summary["parse_ms"].as_f64().unwrap() > 0.0);
+}
+
+#[test]
+fn timing() {
+    let err = stderr(&["-q", "--timing", "keyed/second.c"]);
+    let rows: Vec<_> = err.lines().map(|l| l.split('\t').next().unwrap()).collect();
+    assert_eq!(rows, ["file", "keyed/second.c", "total"]);
+}
+
+#[test]
+fn preprocessed() {
+    let doc = json(&["-q", "--preprocessed", "preprocessed.i"]);
+    assert_eq!(int(&doc, "unix"), 1);
+    assert_eq!(int(&doc, "board.revision"), 3);
+}
+
+#[test]
+fn preprocessor() {
+    let doc = json(&["-q", "--preprocessor", "grep -v ^[#/]", "preprocessor.c"]);
+    assert_eq!(int(&doc, "preprocessed_by_stub"), 1);
+    // gcc can't find the header
+    let err = failure(&["-q", "preprocessor.c"]);
+    assert!(err.contains("not_there.h"), "{}", err);
+    let err = failure(&["-q", "--preprocessor", "false", "keyed/second.c"]);
+    assert!(err.contains("false exited with"), "{}", err);
+}
+
+#[test]
+fn strip_prefix() {
+    let doc = json(&["-q", "--strip-prefix", "g_cfg_", "test.c"]);
+    assert!(has(&doc, "net"));
+    // port would clash with the other field named port
+    assert_eq!(int(&doc, "net.g_cfg_port"), 80);
+    assert_eq!(int(&doc, "net.timeout"), 30);
+    assert!(doc["struct_types"]["net"].is_object());
+    let log = logged(&["--strip-prefix", "g_cfg_", "test.c"]);
+    let clashes = warnings(&log, "strip_prefix");
+    assert!(
+        clashes
+            .iter()
+            .any(|w| w.contains("they would all become 'port'")),
+        "{}",
+        log
+    );
+}
+
+#[test]
+fn limit() {
+    let out = run(&["-q", "--format", "jsonl", "--limit", "2", "test.c"]);
+    let values = lines(&String::from_utf8(out.stdout).unwrap());
+    let names: Vec<_> = values.iter().map(|v| v["name"].clone()).collect();
+    assert_eq!(names, [json!("BASE"), json!("EARLY")]);
+    let total = json(&["-q", "test.c"])["values"].as_array().unwrap().len();
+    assert!(String::from_utf8(out.stderr)
+        .unwrap()
+        .contains(&format!("Showing 2 of {} values", total)));
+}
+
+#[test]
+fn error_on() {
+    let err = failure(&["-q", "--error-on", "range", "test.c"]);
+    assert!(
+        err.contains("Warnings treated as errors: 5 range"),
+        "{}",
+        err
+    );
+    assert!(run(&["-q", "--error-on", "octal", "test.c"])
+        .status
+        .success());
+    let err = failure(&["--error-on", "bogus", "test.c"]);
+    assert!(err.contains("isn't a valid value"), "{}", err);
+}
+
+#[test]
+fn dry_run() {
+    let err = failure(&["--dry-run", "test.c", "syntax_error.c"]);
+    assert!(err.contains("syntax_error.c: syntax error"), "{}", err);
+    let log = logged(&["--dry-run", "test.c", "syntax_error.c"]);
+    assert!(log.contains("1 parsed, 1 failed"), "{}", log);
+    assert!(run(&["-q", "--dry-run", "test.c"]).status.success());
+}
+
+#[test]
+fn continue_on_error() {
+    failure(&["-q", "keyed/second.c", "syntax_error.c"]);
+    let values = jsonl(&["--continue-on-error", "syntax_error.c", "keyed/second.c"]);
+    assert_eq!(values.len(), 1);
+    let log = logged(&["--continue-on-error", "syntax_error.c", "keyed/second.c"]);
+    assert_eq!(warnings(&log, "skipped").len(), 1, "{}", log);
+}
+
+#[test]
+fn max_file_size() {
+    let err = failure(&["--max-file-size", "10", "test.c"]);
+    assert!(err.contains("more than the maximum of 10"), "{}", err);
+    let log = logged(&[
+        "--max-file-size",
+        "100",
+        "--continue-on-error",
+        "test.c",
+        "keyed/second.c",
+    ]);
+    assert_eq!(warnings(&log, "skipped").len(), 1, "{}", log);
+}
+
+#[test]
+fn merge() {
+    let doc = json(&["-q", "--merge", "merge/a.c", "merge/b.c", "merge/c.c"]);
+    assert_eq!(int(&doc, "TOTAL_SLOTS"), 64);
+    assert_eq!(int(&doc, "HALF_SLOTS"), 32);
+    assert_eq!(
+        at(&doc, "NOT_SHARED")["Other"]["category"],
+        "UnknownIdentifier"
+    );
+}
+
+#[test]
+fn merge_conflicts() {
+    let log = logged(&["--merge", "conflict/a.c", "conflict/b.c"]);
+    let conflicts = warnings(&log, "conflict");
+    assert_eq!(conflicts.len(), 1, "{}", log);
+    assert!(conflicts[0].contains("BUFFER_SIZE = 512 in conflict/a.c conflicts with"));
+    let values = jsonl(&["--merge", "conflict/a.c", "conflict/b.c"]);
+    let retries = values.iter().filter(|v| v["name"] == "RETRIES").count();
+    assert_eq!(retries, 1);
+}
+
+#[test]
+fn verify() {
+    let out = stdout(&["-q", "test.c", "verify", "verify.toml"]);
+    assert_eq!(out, "All 14 expectations met\n");
+    let spec = source("wrong.toml", "anton.foo = 2\nmissing = 1\n");
+    let out = run(&["-q", "test.c", "verify", &spec]);
+    assert!(!out.status.success());
+    let report = String::from_utf8(out.stdout).unwrap();
+    assert!(
+        report.contains("anton.foo: expected 2, found"),
+        "{}",
+        report
+    );
+    assert!(report.contains("missing"), "{}", report);
+}
+
+#[test]
+fn get_ptr() {
+    let out = stdout(&["-q", "--get-ptr", "/service/servers/1/port", "test.c"]);
+    assert_eq!(
+        out,
+        "/service/servers/1/port = Integer { value: 443, raw: \"443\" }\n"
+    );
+    let err = failure(&["-q", "--get-ptr", "/nope", "test.c"]);
+    assert!(err.contains("/nope not found, no value named 'nope'"));
+}
+
+#[test]
+fn fingerprint() {
+    let a = stdout(&["-q", "--fingerprint", "test.c"]);
+    assert_eq!(a, stdout(&["-q", "--fingerprint", "test.c"]));
+    assert_eq!(a.trim().len(), 16);
+    assert_ne!(a, stdout(&["-q", "--fingerprint", "test.c", "-DBOARD_A"]));
+}
+
+#[test]
+fn section() {
+    let values = jsonl(&["--section", ".config", "test.c"]);
+    let names: Vec<_> = values.iter().map(|v| v["name"].as_str().unwrap()).collect();
+    assert_eq!(names, ["boot_config", "config_version"]);
+}
+
+#[test]
+fn relative_paths() {
+    let values = jsonl(&["--relative-paths=..", "keyed/second.c"]);
+    assert_eq!(values[0]["file"], "fixtures/keyed/second.c");
+    let absolute = fixtures().join("keyed/second.c").display().to_string();
+    let values = jsonl(&["--relative-paths", &absolute]);
+    assert_eq!(values[0]["file"], "keyed/second.c");
+}
+
+#[test]
+fn field_values() {
+    let out = stdout(&["-q", "--field-values", "baud.parity", "field_values.c"]);
+    assert_eq!(out, "     2 0\n     1 1\n");
+}
+
+#[test]
+fn first_only() {
+    let out = stdout(&[
+        "-q",
+        "--fields-only",
+        "point_t",
+        "--first-only",
+        "keyed/first.c",
+        "keyed/second.c",
+    ]);
+    assert_eq!(out, "int x;\nint y;\n");
+    let err = failure(&["--first-only", "keyed/first.c"]);
+    assert!(err.contains("--fields-only <STRUCT>"), "{}", err);
+}
+
+#[test]
+fn first_only_skips_the_later_declarations() {
+    let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join("first_only.json");
+    let path = path.display().to_string();
+    let summary = |args: &[&str]| -> (String, Value) {
+        let log = logged(&[&["--fields-only", "point_t", "--summary-json", &path], args].concat());
+        let summary = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        (log, summary)
+    };
+    let (log, all) = summary(&["first_only.c"]);
+    assert_eq!(all["skipped_declarations"], 0);
+    assert_eq!(warnings(&log, "redefinition").len(), 1, "{}", log);
+    let (log, first) = summary(&["--first-only", "first_only.c"]);
+    assert_eq!(first["skipped_declarations"], 1);
+    assert!(warnings(&log, "redefinition").is_empty(), "{}", log);
+    assert!(log.starts_with("int x;\nint y;\n"), "{}", log);
+}
+
+#[test]
+fn annotations() {
+    let out = stdout(&["-q", "--annotations", "github", "test.c"]);
+    assert!(out.contains(
+        "::warning file=test.c,line=164,title=range::uint8_t red = 300 is out of the range 0..=255 of the type"
+    ));
+    assert!(out.contains("::warning file=test.c,line=124,title=unresolved::"));
+}
+
+#[test]
+fn annotations_with_relative_paths() {
+    let test_c = fixtures().join("test.c").display().to_string();
+    let absolute = stdout(&["-q", "--annotations", "github", "--warn-octal", &test_c]);
+    assert!(absolute.contains(&format!("::warning file={},line=164,", test_c)));
+    let relative = stdout(&[
+        "-q",
+        "--annotations",
+        "github",
+        "--warn-octal",
+        "--relative-paths",
+        &test_c,
+    ]);
+    assert!(relative.contains("::warning file=test.c,line=164,title=range::"));
+    assert!(relative.contains("::warning file=test.c,line=124,title=unresolved::"));
+    assert!(relative.contains(",title=octal::"));
+    assert!(!relative.contains(&test_c), "{}", relative);
+}
+
+#[test]
+fn pragmas() {
+    let out = stdout(&["-q", "--pragmas", "pragma.c"]);
+    assert!(out.contains("#pragma pack(push, 1) // pragma.h:1\n"));
+    assert!(out.contains("#pragma config WDTE = OFF // pragma.c:4\n"));
+    let doc = json(&["-q", "pragma.c"]);
+    assert!(doc.get("pragmas").is_none() || doc["pragmas"] == json!([]));
+}
+
+#[test]
+fn pragmas_with_relative_paths() {
+    let pragma_c = fixtures().join("pragma.c").display().to_string();
+    let absolute = stdout(&["-q", "--pragmas", &pragma_c]);
+    assert!(absolute.contains(&format!("#pragma config WDTE = OFF // {}:4\n", pragma_c)));
+    for merge in &[None, Some("--merge")] {
+        let mut args = vec!["-q", "--pragmas", "--relative-paths", &pragma_c];
+        args.extend(merge);
+        let relative = stdout(&args);
+        assert!(
+            relative.contains("#pragma pack(push, 1) // pragma.h:1\n"),
+            "{}",
+            relative
+        );
+        assert!(relative.contains("#pragma config WDTE = OFF // pragma.c:4\n"));
+    }
+}
+
+#[test]
+fn warn_octal() {
+    let log = logged(&["--warn-octal", "test.c"]);
+    let octal = warnings(&log, "octal");
+    assert_eq!(octal.len(), 2, "{}", log);
+    assert!(octal[0].contains("010 is octal, i.e. 8 and not 10"));
+    assert!(warnings(&logged(&["test.c"]), "octal").is_empty());
+}
+
+#[test]
+fn warn_shadowing() {
+    let log = logged(&["--warn-shadowing", "shadowing.c"]);
+    let shadowing = warnings(&log, "shadowing");
+    assert_eq!(shadowing.len(), 2, "{}", log);
+    assert!(shadowing[0].contains(".timeout designated in uplink"));
+    assert!(shadowing[1].contains(".retries designated in uplink"));
+}
+
+/// The output of the repl of c-ast with `args` given the commands `input`
+fn repl_session(args: &[&str], input: &str) -> String {
+    let mut child = std::process::Command::new(env!("CARGO_BIN_EXE_c-ast"))
+        .current_dir(fixtures())
+        .args(args)
+        .arg("repl")
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+    use std::io::Write;
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    String::from_utf8(child.wait_with_output().unwrap().stdout).unwrap()
+}
+
+#[test]
+fn repl() {
+    let out = repl_session(
+        &["-q", "test.c"],
+        "get anton.foo\nget table[1].bar\nlist anton\nquit\n",
+    );
+    assert!(out.contains("> anton.foo = Integer { value: 1, raw: \"1\" }\n"));
+    assert!(out.contains("> table[1].bar = Integer { value: 13, raw: \"13\" }\n"));
+    assert!(out.contains("> error: Can't list 'anton'"));
+}
+
+#[test]
+fn repl_path_separator() {
+    let out = repl_session(
+        &["-q", "--path-separator", "/", "separator.c"],
+        "get link/peers[1]/port\nget link/id\nget link.peers[1].port\nquit\n",
+    );
+    assert!(out.contains("> link/peers[1]/port = Integer { value: 8080, raw: \"8080\" }\n"));
+    assert!(out.contains("> link/id = Integer { value: 1, raw: \"1\" }\n"));
+    assert!(
+        out.contains("> error: No value at link.peers[1].port"),
+        "{}",
+        out
+    );
+}
+
+/// The rows `sqlite3` prints for `query` on the database `db`, after loading the `scripts`
+fn sqlite3(db: &Path, scripts: &[&str], query: &str) -> String {
+    let _ = fs::remove_file(db);
+    for script in scripts {
+        let out = std::process::Command::new("sqlite3")
+            .arg(db)
+            .stdin(File::open(script).unwrap())
+            .output()
+            .expect("sqlite3 runs");
+        assert!(
+            out.status.success(),
+            "{}",
+            String::from_utf8_lossy(&out.stderr)
+        );
+    }
+    let out = std::process::Command::new("sqlite3")
+        .arg(db)
+        .arg(query)
+        .output()
+        .expect("sqlite3 runs");
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    String::from_utf8(out.stdout).unwrap()
+}
+
+#[test]
+fn sql_script() {
+    let tmp = Path::new(env!("CARGO_TARGET_TMPDIR"));
+    let path = tmp.join("separator.sql").display().to_string();
+    let args = ["-q", "--sql-script", &path, "--path-separator", "/"];
+    stdout(&[&args[..], &["separator.c"]].concat());
+    let script = fs::read_to_string(&path).unwrap();
+    assert!(script.starts_with("CREATE TABLE IF NOT EXISTS struct_types"));
+    assert!(script.contains(
+        "INSERT INTO members VALUES (NULL, 'separator.c', '', 'link', 'peers[1]/port', '8080', 8080);"
+    ));
+    assert!(script.trim_end().ends_with("COMMIT;"));
+    let first = tmp.join("first.sql").display().to_string();
+    stdout(&["-q", "--sql-script", &first, "keyed/first.c"]);
+    let db = tmp.join("extraction.db");
+    let rows = sqlite3(
+        &db,
+        &[&path, &first],
+        "SELECT number FROM members WHERE variable = 'link' AND path = 'peers[1]/port';",
+    );
+    assert_eq!(rows, "8080\n");
+    let rows = sqlite3(
+        &db,
+        &[&path, &first],
+        "SELECT file, name FROM variables ORDER BY file, name;",
+    );
+    assert_eq!(rows, "keyed/first.c|origin\nseparator.c|link\n");
+}
+
+#[test]
+fn display_options() {
+    let out = stdout(&["-q", "--indent", "tab", "test.c"]);
+    assert!(out.contains("struct a_t anton\n\t.foo = Integer { value: 1, raw: \"1\" }\n"));
+    let out = stdout(&["-q", "--flatten-arrays", "compact", "test.c"]);
+    assert!(out.contains("\nprimes = [2, 3, 5, 7]\n"));
+    let out = stdout(&["-q", "--flatten-arrays", "indexed", "test.c"]);
+    assert!(out.contains("\nlevels[2] = Integer { value: 16, raw: \"0x10\" } [deprecated]\n"));
+    let out = stdout(&["-q", "--group-digits", "digits.c"]);
+    assert!(out.contains("million = Integer { value: 1_000_000, raw: \"1'000'000\" }"));
+}
+
+#[test]
+fn enum_radix() {
+    let out = stdout(&["-q", "--enum-radix", "auto", "flags.c"]);
+    assert!(out.contains("  PERM_ALL = 0x7,\n"));
+    assert!(out.contains("  IRQ_TIMER = 0x3,\n"));
+    assert!(out.contains("  TUESDAY = 1,\n"));
+    assert!(out.contains("default_perm = Integer { value: 0x3, raw: \"PERM_READ | PERM_WRITE\" }"));
+    let out = stdout(&["-q", "--enum-radix", "hex", "flags.c"]);
+    assert!(out.contains("  TUESDAY = 0x1,\n"));
+}
+
+#[test]
+fn raw_identifiers() {
+    let out = stdout(&["-q", "--raw-identifiers", "ucn.c"]);
+    assert!(out.contains("\\U000003c0_x100 = Integer { value: 314, raw: \"314\" }"));
+    // The UCNs of the preprocessor, for all kinds of names
+    assert!(out.contains("struct caf\\U000000e9_t {\n  int na\\U000000efve;"));
+    assert!(out.contains("  PR\\U000000c4FIX_A = 1,\n"));
+    let doc = json(&["-q", "ucn.c"]);
+    assert!(names(&doc).contains(&"π_x100"));
+}
+
+#[test]
+fn no_system_headers() {
+    let doc = json(&["-q", "--no-system-headers", "system.c"]);
+    assert_eq!(names(&doc), ["max_jobs", "nightly"]);
+    let structs: Vec<_> = doc["struct_types"].as_object().unwrap().keys().collect();
+    assert_eq!(structs, ["job_t"]);
+    // The typedefs and the names of the anonymous types of the system headers don't leak
+    assert_eq!(doc["typedefs"], json!({"length_t": "size_t"}));
+    let enums: Vec<_> = doc["enum_types"].as_object().unwrap().keys().collect();
+    assert_eq!(enums, ["anonymous_1"]);
+    let all = json(&["-q", "system.c"]);
+    assert!(all["struct_types"].as_object().unwrap().len() > 1);
+    assert!(all["typedefs"]["size_t"].is_string());
+}
+
+#[test]
+fn no_progress_without_a_terminal() {
+    let err = stderr(&["-q", "keyed/first.c", "keyed/second.c"]);
+    assert!(!err.contains("files"), "{}", err);
+}
+
+#[test]
+fn stream() {
+    let streamed = jsonl(&["--stream", "test.c"]);
+    let names: Vec<_> = streamed
+        .iter()
+        .map(|v| v["name"].as_str().unwrap())
+        .collect();
+    // The elements come as they are extracted, before the other values
+    assert_eq!(names[..3], ["table[0]", "table[1]", "table[2]"]);
+    assert_eq!(names.iter().filter(|n| **n == "table[0]").count(), 1);
+    assert_eq!(streamed.len(), jsonl(&["test.c"]).len());
+    let human = stdout(&["-q", "--stream", "test.c"]);
+    assert!(human.starts_with("struct a_t table[0]\n"));
+    assert_eq!(human.matches("struct a_t table[0]\n").count(), 1);
+    for format in [
+        "json",
+        "tree",
+        "markdown",
+        "c-header",
+        "rust",
+        "protobuf-schema",
+    ] {
+        let err = failure(&["--stream", "--format", format, "test.c"]);
+        assert!(
+            err.contains("--stream only works with --format human or jsonl"),
+            "{}",
+            err
+        );
+    }
+    assert!(failure(&["--stream", "--dot", "test.c"]).contains("cannot be used with"));
+}
+
+#[test]
+fn stream_several_files() {
+    let human = stdout(&["-q", "--stream", "test.c", "keyed/first.c"]);
+    assert!(human.starts_with("test.c:struct a_t table[0]\n"));
+    assert!(human.contains("test.c:struct command_t command_table[1]\n"));
+    let streamed = jsonl(&["--stream", "test.c", "keyed/first.c"]);
+    assert_eq!(streamed[0]["file"], "test.c");
+    assert!(streamed.iter().any(|v| v["file"] == "keyed/first.c"));
+    let profiles = stdout(&["-q", "--stream", "--profile", "a:BOARD_A", "test.c"]);
+    assert!(profiles.starts_with("a:test.c:struct a_t table[0]\n"));
+}
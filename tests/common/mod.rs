@@ -0,0 +1,164 @@
+//! Helpers of the integration tests, which run the c-ast binary on the files in
+//! `tests/fixtures`
+#![allow(dead_code)]
+
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// The directory of the fixtures, the working directory of the binary
+pub fn fixtures() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures")
+}
+
+/// Run c-ast with `args` in the directory of the fixtures
+pub fn run(args: &[&str]) -> Output {
+    run_in(&fixtures(), args)
+}
+
+/// Run c-ast with `args` in `dir`
+pub fn run_in(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_c-ast"))
+        .current_dir(dir)
+        .args(args)
+        .env_remove("C_AST_FORMAT")
+        .output()
+        .expect("c-ast runs")
+}
+
+/// The stdout of a successful run of c-ast with `args`
+pub fn stdout(args: &[&str]) -> String {
+    let out = run(args);
+    assert!(
+        out.status.success(),
+        "c-ast {:?} failed: {}",
+        args,
+        String::from_utf8_lossy(&out.stderr)
+    );
+    String::from_utf8(out.stdout).unwrap()
+}
+
+/// The stderr of a run of c-ast with `args`, whether it succeeded or not
+pub fn stderr(args: &[&str]) -> String {
+    String::from_utf8(run(args).stderr).unwrap()
+}
+
+/// The log of a run of c-ast with `args`, the logger writes to stdout
+pub fn logged(args: &[&str]) -> String {
+    let out = run(args);
+    String::from_utf8(out.stdout).unwrap() + &String::from_utf8(out.stderr).unwrap()
+}
+
+/// The JSON document of `args` with `--format json`
+pub fn json(args: &[&str]) -> Value {
+    let mut all = vec!["--format", "json"];
+    all.extend_from_slice(args);
+    serde_json::from_str(&stdout(&all)).unwrap()
+}
+
+/// The top level value `name` of the JSON document `doc`
+pub fn top<'v>(doc: &'v Value, name: &str) -> &'v Value {
+    doc["values"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|v| v["name"] == name && v["scope"].as_array().is_some_and(|s| s.is_empty()))
+        .unwrap_or_else(|| panic!("no value {}", name))
+}
+
+/// The expression at `path` like `name.field[1].member` in the JSON document `doc`, of a
+/// top level struct value the `Struct`
+///
+/// The elements of top level arrays of structs are values of their own named like
+/// `table[1]`, the longest name that `path` starts with is taken.
+pub fn at<'v>(doc: &'v Value, path: &str) -> &'v Value {
+    let name_len = doc["values"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|v| v["name"].as_str())
+        .filter(|n| {
+            path.strip_prefix(n)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with(['.', '[']))
+        })
+        .map(str::len)
+        .max()
+        .unwrap_or_else(|| panic!("no value at {}", path));
+    let value = &top(doc, &path[..name_len])["value"];
+    let mut found = match value.get("Scalar") {
+        Some(s) => &s["value"],
+        None => value,
+    };
+    let mut rest = &path[name_len..];
+    while !rest.is_empty() {
+        if let Some(index) = rest.strip_prefix('[') {
+            let end = index.find(']').unwrap();
+            let i: u64 = index[..end].parse().unwrap();
+            found = match (found.get("Array"), found.get("SparseArray")) {
+                (Some(items), _) => &items[i as usize],
+                (_, Some(sparse)) => {
+                    let entries = sparse["entries"].as_array().unwrap();
+                    let entry = entries.iter().find(|e| e[0] == i);
+                    &entry.unwrap_or_else(|| panic!("{} isn't set", path))[1]
+                }
+                _ => panic!("{} isn't an array at {}", path, rest),
+            };
+            rest = &index[end + 1..];
+        } else {
+            let member = &rest[1..];
+            let len = member.find(['.', '[']).unwrap_or(member.len());
+            let values = found["Struct"]["values"].as_array();
+            found = values
+                .and_then(|vs| vs.iter().find(|v| v[0] == member[..len]))
+                .map(|v| &v[1])
+                .unwrap_or_else(|| panic!("no member {} in {}", &member[..len], path));
+            rest = &member[len..];
+        }
+    }
+    found
+}
+
+/// The integer at `path` in `doc`
+pub fn int(doc: &Value, path: &str) -> i128 {
+    let found = at(doc, path);
+    match found["Integer"]["value"].as_i64() {
+        Some(i) => i128::from(i),
+        None => found["Integer"]["value"]
+            .as_u64()
+            .map(i128::from)
+            .unwrap_or_else(|| panic!("{} is no integer but {}", path, found)),
+    }
+}
+
+/// Whether the top level value `name` is in `doc`
+pub fn has(doc: &Value, name: &str) -> bool {
+    doc["values"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|v| v["name"] == name)
+}
+
+/// The names of the top level values of `doc` in the order of the document
+pub fn names(doc: &Value) -> Vec<&str> {
+    doc["values"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|v| v["name"].as_str().unwrap())
+        .collect()
+}
+
+/// The warnings of the category `category` in the `log`
+pub fn warnings<'s>(log: &'s str, category: &str) -> Vec<&'s str> {
+    let tag = format!("[c_ast::{}]", category);
+    log.lines().filter(|l| l.contains(&tag)).collect()
+}
+
+/// Write the C source `text` to a file `name` in the temporary directory of the tests,
+/// for the cases too small for a fixture of their own
+pub fn source(name: &str, text: &str) -> String {
+    let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join(name);
+    std::fs::write(&path, text).unwrap();
+    path.display().to_string()
+}
@@ -0,0 +1,22 @@
+/* Alignment specifiers (C11) on fields and variables */
+#include <stddef.h>
+
+struct buffer_t {
+    char tag;
+    _Alignas(16) char buf[64];
+    _Alignas(double) char small;
+    int count __attribute__((aligned(8)));
+};
+
+union cell_t {
+    char c;
+    _Alignas(long double) char wide;
+};
+
+_Alignas(32) const struct buffer_t rx = { 'r', "data", 1, 2 };
+_Alignas(int) const char flag = 1;
+
+const size_t buf_offset = offsetof(struct buffer_t, buf);
+const size_t small_offset = offsetof(struct buffer_t, small);
+const size_t count_offset = offsetof(struct buffer_t, count);
+const char *text = "_Alignas(8)";
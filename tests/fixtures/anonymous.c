@@ -0,0 +1,22 @@
+/* Anonymous types get synthesized names, --no-anonymous leaves them out of the listed types */
+typedef struct {
+  int x;
+  int y;
+} point_t;
+
+struct sprite {
+  point_t origin;
+  struct {
+    unsigned short w;
+    unsigned short h;
+  } size;
+  enum { SPRITE_HIDDEN, SPRITE_SHOWN = 4 } state;
+};
+
+/* The fields of the anonymous types still resolve */
+struct sprite player = { { 10, 20 }, { .h = 32, .w = 16 }, SPRITE_SHOWN };
+
+/* The enum is listed as `enum level_t`, its constants resolve in the initializers */
+typedef enum { LEVEL_LOW, LEVEL_HIGH } level_t;
+const level_t alarm_level = LEVEL_HIGH;
+const int level_count = LEVEL_HIGH + 1;
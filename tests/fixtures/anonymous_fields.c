@@ -0,0 +1,48 @@
+/* Anonymous members: --include-anonymous-fields flatten promotes their members, nest
+   keeps them as anonymous_1, anonymous_2 */
+struct value {
+  int kind;
+  union {
+    int i;
+    float f;
+  };
+  struct {
+    short line;
+    short column;
+  };
+};
+
+const struct value positional = { 1, { 42 }, { 3, 7 } };
+const struct value designated = { .kind = 2, .f = 1.5f, .column = 9 };
+const struct value zero = {0};
+
+/* Three levels deep, the designators of the same anonymous members are merged */
+struct reg_t {
+  int id;
+  union {
+    unsigned int raw;
+    struct {
+      unsigned short low;
+      union {
+        unsigned short high;
+        struct {
+          unsigned char hb0;
+          unsigned char hb1;
+        };
+      };
+    };
+  };
+  int tail;
+};
+
+const struct reg_t reg_raw = { .id = 1, .raw = 0x12345678, .tail = 9 };
+const struct reg_t reg_low = { .id = 2, .low = 5, .hb1 = 7, .tail = 3 };
+const struct reg_t reg_positional = { 3, { 0x1 }, 4 };
+const struct reg_t reg_bytes = { .hb0 = 1, .hb1 = 2 };
+
+/* Without the braces of the anonymous members, the positional items after .low and .high
+   continue in the anonymous members around them */
+const struct value elided = { 1, 42, 3, 7 };
+const struct reg_t reg_elided = { 3, 0x1, 4 };
+const struct reg_t reg_continued = { 2, .low = 5, 6, 7 };
+const struct reg_t reg_bytes_continued = { 2, .high = 5, 6 };
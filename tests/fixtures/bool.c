@@ -0,0 +1,23 @@
+/* Integers and macros assigned to a _Bool are booleans */
+#include <stdbool.h>
+
+#define FEATURE_X 1
+#define FEATURE_Y 0
+
+typedef bool flag_t;
+
+struct features_t {
+  bool logging;
+  _Bool tracing;
+  flag_t metrics;
+  int level;
+};
+
+const bool on = FEATURE_X;
+const _Bool off = FEATURE_Y;
+const flag_t yes = true;
+const bool nonzero = 42;
+const int next_level = on + 1;
+const struct features_t features = { .logging = FEATURE_X, .tracing = FEATURE_Y, .metrics = 1, .level = 3 };
+const struct features_t no_features = { 0 };
+const bool toggles[] = { FEATURE_X, FEATURE_Y, true };
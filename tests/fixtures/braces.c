@@ -0,0 +1,25 @@
+/* Initializers leaving out the braces of the aggregates in aggregates */
+
+struct pair { int a[2]; int b; };
+struct pair flat = {1, 2, 3};
+
+struct outer { struct pair p; int c; } nested = {1, 2, 3, 4};
+
+int grid[2][3] = {1, 2, 3, 4};
+
+struct point { int x; int y; };
+struct point points[] = {1, 2, 3, 4, 5};
+
+struct named { char name[8]; const char *fmt[2]; int n; } labels = { "abc", "x", "y", 3 };
+
+struct tagged { int kind; union { int i; float f; } u; int after; } tagged = {1, 2, 3};
+
+/* The excess element 4 is ignored */
+struct pair excess = {1, 2, 3, 4};
+
+/* Positional items after designators */
+int designated_grid[2][3] = { [1] = 4, 5 };
+struct wrap { struct point in; int z; } continued = { .in.x = 1, 2, 3 };
+struct pair after_element = { .a[0] = 5, 6, 7 };
+struct point moved[3] = { [1].x = 1, 2 };
+int after_range[6] = { [1 ... 2] = 9, 3 };
@@ -0,0 +1,3 @@
+#include "config.h"
+
+const int BUFFER_SIZE = 512;
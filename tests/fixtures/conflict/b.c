@@ -0,0 +1,4 @@
+#include "config.h"
+
+/* Conflicts with a.c, reported with a warning by --merge */
+const int BUFFER_SIZE = 1024;
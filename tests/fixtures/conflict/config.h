@@ -0,0 +1,2 @@
+/* Included by both files, reported once with --merge */
+static const int RETRIES = 3;
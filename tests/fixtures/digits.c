@@ -0,0 +1,10 @@
+/* C23 digit separators, the raw forms keep them, see also --group-digits */
+
+const long million = 1'000'000;
+const unsigned mask = 0xFF'FF'00'00u;
+const int plain = 1234567;
+const int negative = -2'500;
+const char quote = '\'';
+const double ratio = 1.5e+3;
+const int sizes[] = { 1'024, 4'096, 65'536 };
+const char *text = "1'000";
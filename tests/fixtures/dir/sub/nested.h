@@ -0,0 +1 @@
+static const int nested = 2;
@@ -0,0 +1 @@
+int scratch = 3;
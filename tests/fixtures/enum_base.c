@@ -0,0 +1,28 @@
+/* Enums with a fixed underlying type (C23, accepted by GCC in GNU mode) */
+
+enum color : unsigned char;
+
+struct pixel_t {
+    enum color c;
+    enum { FLAT, RAISED } style : 2;
+    unsigned int alpha;
+};
+
+enum color : unsigned char { RED, GREEN, BLUE = 200 };
+
+/* The forward declaration doesn't replace the definition */
+enum color : unsigned char;
+
+enum level : short { LOW = -1, HIGH = 1 };
+
+typedef enum : unsigned long long { BIG = 0x100000000 } big_t;
+
+const struct pixel_t background = { GREEN, RAISED, 255 };
+const enum level default_level = HIGH;
+const char *separator = "enum x : int;";
+
+/* Only declared, the Rust type is the one of the underlying type */
+enum shade : unsigned short;
+struct tint_t {
+    enum shade s;
+};
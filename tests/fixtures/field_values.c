@@ -0,0 +1,9 @@
+/* With --field-values baud.parity: 2 times 0 and once 1 */
+struct baud {
+  unsigned rate;
+  int parity;
+};
+
+const struct baud console = { 115200, 0 };
+const struct baud modem = { 9600, 1 };
+const struct baud rates[] = { { .rate = 57600, .parity = 0 }, { .rate = 300 } };
@@ -0,0 +1,11 @@
+/* --fields-only point_t --first-only stops at the first of the two definitions */
+struct point_t {
+  int x;
+  int y;
+};
+
+struct point_t {
+  int x;
+  int y;
+  int z;
+};
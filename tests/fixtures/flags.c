@@ -0,0 +1,17 @@
+/* --enum-radix: flags enums in hex, with auto only the flag-like ones */
+enum perm {
+  PERM_READ = 1 << 0,
+  PERM_WRITE = 1 << 1,
+  PERM_EXEC = 1 << 2,
+  PERM_ALL = PERM_READ | PERM_WRITE | PERM_EXEC,
+};
+
+/* A plain counting enum, decimal with auto */
+enum weekday { MONDAY, TUESDAY, WEDNESDAY };
+
+/* Flag-like by its name */
+enum irq_mask { IRQ_NONE, IRQ_TIMER = 3 };
+
+const int default_perm = PERM_READ | PERM_WRITE;
+const int first_day = TUESDAY;
+const int plain = 16;
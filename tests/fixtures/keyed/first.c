@@ -0,0 +1,7 @@
+/* With --format json each file has its own document: keyed/first.c and
+   keyed/second.c */
+struct point_t {
+  int x;
+  int y;
+};
+const struct point_t origin = { 0, 0 };
@@ -0,0 +1 @@
+const int answer = 42;
@@ -0,0 +1,6 @@
+#include "limits.h"
+
+const int MAX_USERS = 16;
+
+/* Only visible in this file, b.c can't refer to it */
+static const int SCRATCH = 99;
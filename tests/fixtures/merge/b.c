@@ -0,0 +1,7 @@
+#include "limits.h"
+
+extern const int TOTAL_SLOTS;
+
+const int SLOTS_PER_USER = 4;
+const int TOTAL_SLOTS = MAX_USERS * SLOTS_PER_USER;
+int slots[MAX_USERS];
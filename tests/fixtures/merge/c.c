@@ -0,0 +1,8 @@
+#include "limits.h"
+
+extern const int TOTAL_SLOTS;
+extern const int SCRATCH;
+
+/* Resolved in the third pass, TOTAL_SLOTS needs MAX_USERS of a.c */
+const int HALF_SLOTS = TOTAL_SLOTS / 2;
+const int NOT_SHARED = SCRATCH;
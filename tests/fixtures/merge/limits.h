@@ -0,0 +1,3 @@
+/* Shared by a.c and b.c, see --merge */
+extern const int MAX_USERS;
+extern const int SLOTS_PER_USER;
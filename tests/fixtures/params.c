@@ -0,0 +1,8 @@
+/* The qualifiers and static of the array parameters of prototypes are kept, see
+   --functions */
+
+void fill(int buf[static 4], int value);
+void copy(char *restrict dst, const char *restrict src, unsigned long n);
+int sum(const int values[const 8]);
+int total(int n, const int table[static const n], int grid[*][4]);
+void clear(volatile unsigned char regs[volatile]);
@@ -0,0 +1,8 @@
+/* Custom pragmas of a microcontroller toolchain, listed with --pragmas */
+#include "pragma.h"
+
+#pragma config WDTE = OFF
+#pragma config FOSC = INTRC_NOCLKOUT
+_Pragma("vendor section(\".boot\")")
+
+const int CLOCK_HZ = 8000000;
@@ -0,0 +1,6 @@
+#pragma pack(push, 1)
+struct frame_t {
+  unsigned char kind;
+  unsigned int length;
+};
+#pragma pack(pop)
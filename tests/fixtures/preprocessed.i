@@ -0,0 +1,7 @@
+struct board_t {
+  int revision;
+  int unix;
+};
+
+struct board_t board = {3, 1};
+int unix = 1;
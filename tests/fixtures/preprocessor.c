@@ -0,0 +1,4 @@
+/* Extracted with --preprocessor "grep -v ^[#/]", which drops the include of a missing header */
+#include "not_there.h"
+
+const int preprocessed_by_stub = 1;
@@ -0,0 +1,39 @@
+/* --format protobuf-schema: sensor_t becomes
+     message sensor_t {
+       message sensor_t_anonymous_1 {
+         oneof value {
+           int32 raw = 1;
+           float scaled = 2;
+         }
+       }
+       string name = 1;
+       uint32 channel = 2;
+       bool enabled = 3;
+       repeated uint32 history = 4;
+       limits_t limits = 5;
+       sensor_t_anonymous_1 anonymous_1 = 6;
+       bytes on_read = 7;
+     }
+   with a warning for on_read */
+#include <stdbool.h>
+#include <stdint.h>
+
+struct limits_t {
+  int16_t low;
+  int16_t high;
+};
+
+typedef struct limits_t limits_t;
+
+struct sensor_t {
+  const char *name;
+  uint8_t channel;
+  bool enabled;
+  unsigned char history[8];
+  limits_t limits;
+  union {
+    int raw;
+    float scaled;
+  };
+  void (*on_read)(int);
+};
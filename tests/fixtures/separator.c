@@ -0,0 +1,14 @@
+/* --path-separator /: the members are at paths like link/peers[1]/port, e.g. in the repl
+   `get link/peers[1]/port` and in the members table of --sql-script */
+
+struct peer_t {
+  const char *host;
+  unsigned short port;
+};
+
+struct link_cfg_t {
+  int id;
+  struct peer_t peers[2];
+};
+
+const struct link_cfg_t link = { 1, { { "a", 80 }, { "b", 8080 } } };
@@ -0,0 +1,15 @@
+/* --warn-shadowing: the designated members named like a global variable declared before */
+
+const int timeout = 30;
+int retries;
+
+struct link_t {
+  int timeout;
+  int retries;
+  int speed;
+};
+
+/* Warns about .timeout and .retries */
+const struct link_t uplink = { .timeout = 5, .retries = 3, .speed = 100 };
+/* Positional members aren't designated */
+const struct link_t downlink = { 10, 2, 50 };
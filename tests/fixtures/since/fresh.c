@@ -0,0 +1,2 @@
+/* Modified now, see the --since test of tests/cli.rs */
+const int since_fresh = 1;
@@ -0,0 +1,2 @@
+/* Modified 10 days ago, see the --since test of tests/cli.rs */
+const int since_stale = 2;
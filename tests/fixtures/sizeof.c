@@ -0,0 +1,21 @@
+/* Array lengths given with sizeof are evaluated with the layouts of the types */
+
+struct header_t {
+  unsigned short kind;
+  unsigned int length;
+};
+
+struct packet_t {
+  struct header_t header;
+  unsigned char payload[sizeof(struct header_t) + 4];
+  char aligned[_Alignof(double) * 2];
+  long words[sizeof(long) / sizeof(int)];
+  /* Unknown layouts stay as written */
+  char unknown[sizeof(struct missing_t)];
+};
+
+typedef unsigned char raw_header_t[sizeof(struct header_t)];
+
+const unsigned char scratch[sizeof(struct header_t)] = { 1, 2 };
+const struct packet_t packet = { .payload = { 0xaa, 0xbb } };
+const raw_header_t raw_header = { 7 };
@@ -0,0 +1,6 @@
+/* Doesn't parse, for --dry-run */
+
+struct broken_t {
+  int a
+  int b;
+};
@@ -0,0 +1,13 @@
+#include <stdio.h>
+#include <time.h>
+
+struct job_t {
+  const char *name;
+  int priority;
+};
+
+struct job_t nightly = {"backup", 3};
+
+typedef size_t length_t;
+enum { JOB_LOW, JOB_HIGH };
+const length_t max_jobs = 8;
@@ -0,0 +1,424 @@
+int x = 3;
+static int y = 5;
+
+struct a_t {
+  int foo;
+  int bar;
+};
+
+struct b_t {
+  char *wer;
+  char *wo;
+};
+
+struct a_t anton = {
+  .foo = 1,
+  .bar = 2
+};
+
+struct a_t berta = {3, 4};
+
+struct a_t caesar = {3+2, 6};
+
+struct b_t dora = {"Dora", "Musterstadt"};
+
+int z = x + 1;
+int w = get_w();
+
+int main(void)
+{
+  int x = 7;
+  {
+    static int x = 8;
+  }
+  return x;
+}
+
+struct a_t table[] = {
+  {10, 11},
+  {12, 13},
+  {14, 15},
+};
+
+struct c_t {
+  struct a_t a;
+  int n;
+};
+
+struct c_t emil = {{1, 2}, 3};
+
+typedef int (*callback_t)(void);
+typedef char buffer_t[256];
+typedef buffer_t named_buffers_t[4];
+
+struct d_t {
+  callback_t cb;
+  buffer_t name;
+  const char *fmt[2];
+  int (*handler)(int, const char *, ...);
+};
+
+struct g_cfg_net {
+  int g_cfg_port;
+  int port;
+  int g_cfg_timeout;
+};
+
+struct g_cfg_net g_cfg_net = {80, 8080, 30};
+int g_cfg_x = 1;
+
+int fourcc = 'ABCD';
+char letter = 'A';
+
+struct node {
+  int value;
+  struct node *next;
+};
+
+struct tree_a;
+struct tree_b {
+  struct tree_a *a;
+};
+struct tree_a {
+  struct tree_b *b;
+  struct node head;
+};
+
+int primes[] = {2, 3, 5, 7};
+int sparse[10] = { [3] = 5, [7] = 9 };
+int ranged[6] = { [1 ... 3] = -1, 4 };
+int grid[2][3] = { {1, 2, 3}, [1] = {4} };
+
+#ifdef BOARD_A
+int board_leds = 4;
+#else
+int board_leds = 2;
+#endif
+
+char escapes[] = "\101\t\x42" "\xff";
+
+__typeof__(x) same_as_x = 9;
+typedef __typeof__(escapes) escapes_t;
+typedef typeof(int *) int_ptr_t;
+int (*handler_ptr) = &x;
+
+int (*on_event)(int) = 0;
+typedef struct { int a; int b; } anon_t;
+anon_t anon = {1, .b = 2};
+
+enum mode { MODE_OFF, MODE_ON = 4, MODE_AUTO };
+
+struct wave {
+  double _Complex amp;
+  int n;
+};
+double _Complex phasor = 1.0 + 2.0 * (__extension__ 1.0iF);
+_Complex float unit_i = 1.0if;
+struct wave carrier = {0.5 * 1.0i, 2};
+
+const int BASE = 10;
+const int OFFSET = BASE + 5;
+const int LIMIT = OFFSET * 2;
+const double SCALE = 1.5;
+const double SCALE_COPY = SCALE;
+const int EARLY = LATE + 1;
+const int LATE = 2;
+int offsets[OFFSET - BASE] = {[BASE - 9] = LIMIT};
+
+void shadow(void)
+{
+  int BASE = 3;
+  const int NOT_FOLDED = BASE + 1;
+}
+
+struct inner_t {
+  int lo;
+  int hi;
+};
+struct outer_t {
+  struct inner_t range;
+  int limits[2];
+  int flags;
+};
+struct outer_t nested = {{1, 2}, {3, 4}, 5};
+
+struct settings_t {
+  int timeout_ms;
+  int timeout __attribute__((deprecated("use timeout_ms")));
+};
+struct settings_t settings = {1000, 1};
+__attribute__((deprecated)) int legacy_mode = 1;
+int old_limit __attribute__((__deprecated__("use LIMIT"))) = 7;
+
+int log_message(int level, const char *fmt, ...);
+void reset(void);
+const char *name_of(struct node *n, unsigned idx);
+
+typedef __UINT8_TYPE__ uint8_t;
+typedef __INT8_TYPE__ int8_t;
+struct pixel_t {
+  uint8_t red;
+  int8_t bias;
+};
+struct pixel_t pixel_ok = {255, -128};
+struct pixel_t pixel_bad = {300, -129};
+uint8_t level_ok = 200;
+uint8_t level_bad = 256;
+int8_t offset_ok = 127;
+int8_t offset_bad = 128;
+
+static inline int square(int x) { return x * x; }
+_Noreturn void die(const char *reason);
+
+#define FLAG_READY (1 << 4 | 1 << 2)
+#define STATUS_MASK (0xff00 & 0x0ff0)
+const int flags = FLAG_READY;
+const int status = STATUS_MASK;
+const int inverted = ~FLAG_READY ^ 0x3;
+const long high = 0x100000000 >> 4;
+const int overshift = 1 << 32;
+const unsigned long wide_bit = 1UL << 40;
+const long long sign_bit = 1LL << 63;
+const unsigned all_ones = ~0u;
+
+#include <stddef.h>
+struct packet_t {
+  char kind;
+  int length;
+  short ports[3];
+  struct pixel_t color;
+  double stamp;
+};
+const size_t length_offset = offsetof(struct packet_t, length);
+const size_t port_offset = offsetof(struct packet_t, ports[2]);
+const size_t bias_offset = offsetof(struct packet_t, color.bias);
+const size_t stamp_offset = offsetof(struct packet_t, stamp);
+const size_t unknown_offset = offsetof(struct missing_t, field);
+
+enum permission { PERM_READ = 1, PERM_WRITE = 2, PERM_EXEC = 4 };
+enum { OPT_VERBOSE = 1 << 0, OPT_FORCE = 1 << 1 };
+int permissions = PERM_READ | PERM_WRITE;
+int options = OPT_VERBOSE | OPT_FORCE | PERM_EXEC;
+
+/** Settings of the serial console */
+struct console_t {
+  /** The speed in baud */
+  unsigned baud;
+  int echo; // echo the input back
+  // The line ending,
+  // "\n" or "\r\n"
+  const char *eol;
+  /* Not set by the initializer */
+  char prompt[8];
+};
+struct console_t console = {115200, 1, "\r\n"};
+
+const int retries = 010;
+const int file_mode = 0644;
+const int seven = 07;
+
+#define KIND(x) _Generic((x), int: 1, unsigned: 2, double: 3, default: 0)
+const int kind_int = KIND(42);
+const int kind_unsigned = KIND(42u);
+const int kind_double = KIND(4.2);
+const int kind_char = KIND((char)'a');
+const char *kind_name = _Generic(1.0f, float: "float", default: "other");
+const int kind_sum = KIND(1L) + KIND((unsigned int)7);
+const int kind_unknown = _Generic(retries, int: 1, default: 0);
+
+int bounded[4] = { [1] = 1, [5] = 5, [2 ... 6] = 2 };
+int overfull[2] = { 1, 2, 3 };
+
+struct precise_t {
+  long double pi;
+  float ratio;
+};
+struct precise_t precise = {3.14159265358979323846264338327950288L, 0.5f};
+const long double tau = 6.28318530717958647692528676655900577L;
+
+/* GNU style initializers like in the Linux kernel */
+struct file_ops_t {
+  int open;
+  int read;
+  int write;
+  int flags[4];
+};
+struct file_ops_t legacy_ops = { read: 2, open: 1 };
+struct file_ops_t modern_ops = { .write = 3, .open = 1, 2, .flags = { [1 ... 3] = 9 } };
+int gnu_ranges[8] = { [0 ... 3] = 1, [6 ... 7] = 2 };
+
+union reg_t {
+  unsigned raw;
+  struct {
+    unsigned enable : 1;
+    unsigned mode : 3;
+  } bits;
+};
+struct device_t {
+  union reg_t ctrl;
+  unsigned char id[2][4];
+  unsigned ready : 1;
+  unsigned : 7;
+  int (*probe)(struct device_t *dev);
+};
+
+const char * const MESSAGE = "hi";
+char const *const volatile (GREETING) = "hello";
+static const char *const *const MESSAGES_PTR = 0;
+
+struct cmd_t {
+  const char *name;
+  int id;
+};
+const struct cmd_t cmd_a = { "a", 1 }, cmd_b = { "b", 2 };
+const struct cmd_t *commands[] = { &cmd_a, &cmd_b, NULL };
+
+const size_t builtin_length_offset = __builtin_offsetof(struct packet_t, length);
+const int folded_constant_p = __builtin_constant_p(BASE + 1);
+const int expected_value = __builtin_expect(BASE, 0);
+const unsigned swapped_magic = __builtin_bswap16(0x1234);
+
+struct big_t {
+  int count;
+  double ratio;
+  char label[16];
+  const char *owner;
+  struct pixel_t tint;
+  short history[3];
+  void (*on_change)(void);
+};
+struct big_t big_zero = {0};
+struct big_t big_empty = {};
+
+struct server_t {
+  const char *host;
+  unsigned short port;
+};
+struct service_t {
+  struct server_t primary;
+  struct server_t servers[2];
+};
+struct service_t service = {
+  .primary = { "localhost", 8080 },
+  .servers = { { "a.example", 80 }, { "b.example", 443 } },
+};
+
+int checksum(const unsigned char *data, int len) {
+  static const unsigned seed = 0x1d;
+  register int acc = seed;
+  auto int step = 1;
+  for (int i = 0; i < len; i += step) {
+    acc ^= data[i];
+  }
+  return acc;
+}
+
+unsigned make_color(unsigned char red, unsigned char green);
+const unsigned orange = make_color(255, BASE * 16);
+
+int boot_counter __asm__("real_boot_counter") = 5;
+const struct pixel_t asm_pixel __asm__("asm_pixel_v2") = { 1, 2 };
+
+/* Rendered differently with --flatten-arrays none, indexed and compact */
+__attribute__((deprecated)) const short levels[3] = {-1, 0, 0x10};
+
+/* The members of const structs declared before are resolved */
+struct limits_t {
+  int low;
+  struct a_t range;
+};
+const struct limits_t limits = { 5, { 10, 20 } };
+const struct a_t ranges[] = { { 1, 2 }, { 3, 4 } };
+int low_limit = limits.low;
+int range_span = limits.range.bar - limits.range.foo;
+int second_bar = ranges[1].bar;
+int not_const = anton.foo;
+int no_member = limits.high;
+
+/* A trailing comma adds no element, empty braces (C23) initialize everything to zero */
+const int trailing[] = { 1, 2, 3, };
+const struct limits_t empty_limits = {};
+const struct a_t empty_ranges[2] = { { 1, }, {}, };
+const int braced = { 7 };
+const int empty_scalar = {};
+
+/* The types are rendered the same however the specifiers are ordered */
+struct spelling_t {
+  const int leading;
+  int const trailing;
+  int unsigned long mixed;
+  char const *const name;
+};
+
+/* Wide literals keep their code units, the arrays have one element more for the NUL */
+const wchar_t wide_greeting[] = L"hello";
+const unsigned short utf16_name[] = u"caf\xe9";
+const wchar_t wide_cut[3] = L"four";
+
+/* Placed in linker sections, --section .config keeps only the first two */
+__attribute__((section(".config"))) const struct a_t boot_config = { 1, 2 };
+const int config_version __attribute__((section(".config"))) = 3;
+__attribute__((__section__(".calib"))) const short calib_gain = 100;
+
+/* Chained designators set the members of nested structs, the others stay unset */
+struct window_t {
+  struct a_t origin;
+  struct limits_t limits;
+  int depth;
+};
+const struct window_t window = {
+  .limits.range.bar = 9, .origin.bar = 4, .depth = 1, .origin.foo = 3, .limits.low = 2
+};
+
+/* Only the set elements of large arrays initialized sparsely are kept, see --dense-arrays */
+const unsigned char lut[256] = { [0] = 1, ['A'] = 2, [255] = 3 };
+struct sparse_t {
+  int id;
+  short table[100];
+};
+const struct sparse_t sparse_table = { .id = 4, .table = { [50] = 5 } };
+const struct sparse_t sparse_zero = { 0 };
+
+/* Index designators place the structs of an array, nested designators set their members */
+struct row_t {
+  int a;
+  int b;
+};
+const struct row_t rows[2] = { [1] = { .b = 2, .a = 1 }, [0] = { .a = 3 } };
+const struct row_t more_rows[] = { [2] = { .a = 1 }, [0].b = 5, { 7 } };
+struct row_grid_t {
+  int n;
+  struct row_t rows[3];
+};
+const struct row_grid_t row_grid = { .rows = { [2] = { .b = 9 } }, .rows[1] = { .a = 4 }, .rows[1].b = 6 };
+
+/* Dispatch tables of function pointers refer to the functions, also through a typedef */
+typedef int (*handler_t)(int);
+int handle_get(int arg);
+int handle_put(int arg);
+static const handler_t handlers[] = { handle_get, &handle_put, 0 };
+struct command_t {
+  const char *name;
+  handler_t run;
+};
+static const struct command_t command_table[] = { { "get", handle_get }, { "put", &handle_put } };
+
+/* The floating constants are kept as written, arithmetic with them isn't folded */
+const float HALF = 0.5f;
+const double SCALED = SCALE * 2;
+const float NEGATED = -HALF;
+const int WHOLE = SCALE;
+
+/* Index chains through const arrays and structs, with constant indices */
+const int cells[2][3] = { { 1, 2, 3 }, { 4, 5, 6 } };
+const int row = 1;
+struct holder_t {
+  int arr[3];
+};
+const struct holder_t holder = { { 7, 8, 9 } };
+int cell = cells[1][2];
+int indexed_cell = cells[row][row + 1];
+int held = holder.arr[row];
+int range_bar = ranges[row].bar;
+int outside = cells[2][0];
+int assigned = (retries = 2);
@@ -0,0 +1,10 @@
+struct café_t {
+  int naïve;
+  const char *menü;
+};
+
+typedef enum { PRÄFIX_A = 1 } präfix_t;
+
+struct café_t café = { 2, "é" };
+int π_x100 = 314;
+präfix_t präfix = PRÄFIX_A;
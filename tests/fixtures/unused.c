@@ -0,0 +1,17 @@
+struct point_t {
+  int x;
+  int y;
+};
+
+struct list_t {
+  struct list_t *next;
+  struct point_t at;
+};
+
+struct legacy_t {
+  int old;
+};
+
+typedef struct list_t list_t;
+
+list_t *head;
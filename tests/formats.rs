@@ -0,0 +1,208 @@
+//! The output formats besides the JSON of the values
+
+mod common;
+
+use common::{logged, stdout, warnings};
+use serde_json::Value;
+use std::path::Path;
+use std::process::Command;
+
+#[test]
+fn json_of_several_files() {
+    let keyed: Value = serde_json::from_str(&stdout(&[
+        "-q",
+        "--format",
+        "json",
+        "keyed/first.c",
+        "keyed/second.c",
+    ]))
+    .unwrap();
+    let files: Vec<_> = keyed.as_object().unwrap().keys().collect();
+    assert_eq!(files, ["keyed/first.c", "keyed/second.c"]);
+    assert_eq!(keyed["keyed/second.c"]["values"][0]["name"], "answer");
+
+    let stream = stdout(&[
+        "-q",
+        "--format",
+        "json",
+        "--merge-format",
+        "stream",
+        "keyed/first.c",
+        "keyed/second.c",
+    ]);
+    let docs: Vec<Value> = stream
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    assert_eq!(docs.len(), 2);
+    assert_eq!(docs[1]["file"], "keyed/second.c");
+    assert!(docs[0]["struct_types"]["point_t"].is_object());
+
+    let combined: Value = serde_json::from_str(&stdout(&[
+        "-q",
+        "--format",
+        "json",
+        "--merge-format",
+        "combined",
+        "keyed/first.c",
+        "keyed/second.c",
+    ]))
+    .unwrap();
+    assert_eq!(combined["values"].as_array().unwrap().len(), 2);
+}
+
+#[test]
+fn jsonl() {
+    let out = stdout(&["-q", "--format", "jsonl", "keyed/first.c", "keyed/second.c"]);
+    let values: Vec<Value> = out
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    assert_eq!(values.len(), 2);
+    assert_eq!(values[0]["name"], "origin");
+    assert_eq!(values[0]["value"]["Struct"]["typ"], "point_t");
+    assert_eq!(
+        values[1]["value"]["Scalar"]["value"]["Integer"]["value"],
+        42
+    );
+}
+
+#[test]
+fn tree() {
+    let out = stdout(&["-q", "--format", "tree", "anonymous.c"]);
+    assert!(out.contains(
+        "anonymous.c:player (struct sprite)\n\
+         ├── origin (struct point_t)\n\
+         │   ├── x = 10\n\
+         │   └── y = 20\n"
+    ));
+    assert!(out.contains("└── state = 4 (SPRITE_SHOWN)\n"));
+    assert!(out.contains("anonymous.c:level_count = 2 (LEVEL_HIGH + 1)\n"));
+}
+
+#[test]
+fn markdown() {
+    let out = stdout(&["-q", "--format", "markdown", "test.c"]);
+    assert!(out.contains(
+        "## console_t\n\n\
+         Defaults from `console`.\n\n\
+         | Field | Type | Default | Description |\n\
+         |---|---|---|---|\n\
+         | `baud` | `unsigned` | `115200` | The speed in baud |\n\
+         | `echo` | `int` | `1` | echo the input back |\n"
+    ));
+    assert!(out.contains(
+        "| `eol` | `const char *` | `\"\\r\\n\"` | The line ending, \"\\n\" or \"\\r\\n\" |"
+    ));
+}
+
+/// Whether gcc accepts the C header `text`
+fn compiles_as_c(name: &str, text: &str) -> Result<(), String> {
+    let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join(name);
+    std::fs::write(&path, text).unwrap();
+    let out = Command::new("gcc")
+        .args(["-fsyntax-only", "-x", "c"])
+        .arg(&path)
+        .output()
+        .unwrap();
+    match out.status.success() {
+        true => Ok(()),
+        false => Err(String::from_utf8_lossy(&out.stderr).into_owned()),
+    }
+}
+
+#[test]
+fn c_header() {
+    let out = stdout(&["-q", "--format", "c-header", "anonymous.c"]);
+    assert!(out.contains("struct sprite {\n  struct point_t origin;\n  struct sprite_size size;\n  enum sprite_state state;\n};\n"));
+    for file in ["test.c", "anonymous.c", "alignas.c", "proto.c"] {
+        let header = stdout(&["-q", "--format", "c-header", file]);
+        let name = format!("{}.h", file.trim_end_matches(".c"));
+        compiles_as_c(&name, &header).unwrap_or_else(|e| panic!("{}: {}", file, e));
+    }
+}
+
+/// Whether rustc accepts the Rust module `text`
+fn compiles_as_rust(name: &str, text: &str) -> Result<(), String> {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR"));
+    let path = dir.join(name);
+    std::fs::write(&path, text).unwrap();
+    let out = Command::new(std::env::var("RUSTC").unwrap_or_else(|_| String::from("rustc")))
+        .args([
+            "--edition",
+            "2018",
+            "--crate-type",
+            "lib",
+            "--emit",
+            "metadata",
+            "--out-dir",
+        ])
+        .arg(dir)
+        .arg(&path)
+        .output()
+        .unwrap();
+    match out.status.success() {
+        true => Ok(()),
+        false => Err(String::from_utf8_lossy(&out.stderr).into_owned()),
+    }
+}
+
+#[test]
+fn rust() {
+    let out = stdout(&["-q", "--format", "rust", "keyed/first.c", "enum_base.c"]);
+    assert!(out.contains("pub type color = u8;\npub const RED: color = 0;\n"));
+    assert!(out.contains("pub type level = i16;\n"));
+    assert!(
+        out.contains("    // enum pixel_t_style style : 2, bit-fields can't be declared in Rust\n")
+    );
+    assert!(out.contains(
+        "#[repr(C)]\n#[derive(Clone, Copy)]\npub struct point_t {\n    pub x: i32,\n    pub y: i32,\n}\n"
+    ));
+    assert!(out.contains("pub struct tint_t {\n    pub s: u16,\n}"));
+    compiles_as_rust("enum_base.rs", &out).unwrap();
+    // The types without a Rust equivalent are placeholders, not C
+    let out = stdout(&["-q", "--format", "rust", "test.c"]);
+    assert!(out.contains("    pub pi: /* unknown: long double */ (),\n"));
+    compiles_as_rust("test.rs", &out).unwrap();
+}
+
+#[test]
+fn rust_type_map() {
+    let out = stdout(&[
+        "-q",
+        "--format",
+        "rust",
+        "--type-map",
+        "types.toml",
+        "test.c",
+    ]);
+    assert!(out.contains("pub struct d_t {\n    pub cb: Callback,\n"));
+    assert!(out.contains("pub struct precise_t {\n    pub pi: f128,\n    pub ratio: f32,\n}"));
+    assert!(out.contains("    pub color: Pixel,\n"));
+}
+
+#[test]
+fn protobuf_schema() {
+    let text = stdout(&["-q", "--format", "protobuf-schema", "proto.c"]);
+    assert!(text.starts_with("syntax = \"proto3\";\n"));
+    assert!(text.contains(
+        "message sensor_t {\n\
+         \x20 message sensor_t_anonymous_1 {\n\
+         \x20   oneof value {\n\
+         \x20     int32 raw = 1;\n\
+         \x20     float scaled = 2;\n\
+         \x20   }\n\
+         \x20 }\n\
+         \x20 string name = 1;\n\
+         \x20 uint32 channel = 2;\n\
+         \x20 bool enabled = 3;\n\
+         \x20 repeated uint32 history = 4;\n\
+         \x20 limits_t limits = 5;\n\
+         \x20 sensor_t_anonymous_1 anonymous_1 = 6;\n\
+         \x20 bytes on_read = 7;\n\
+         }\n"
+    ));
+    let log = logged(&["--format", "protobuf-schema", "proto.c"]);
+    let type_map = warnings(&log, "type_map");
+    assert!(type_map.iter().any(|w| w.contains("on_read")), "{}", log);
+}
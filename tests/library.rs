@@ -0,0 +1,107 @@
+//! The functions of the library, used without the binary
+
+mod common;
+
+use c_ast::{
+    parse_file, CAstError, ExpressionTransformer, Extraction, MyExpression, MyValue, MyVisitor,
+};
+use common::fixtures;
+use lang_c::ast::{CallExpression, Constant, Expression};
+use lang_c::driver::{parse_preprocessed, Config};
+use lang_c::visit::Visit;
+
+#[test]
+fn syntax_errors_are_parse_errors() {
+    let path = fixtures().join("syntax_error.c");
+    match parse_file(&Config::default(), &path, false) {
+        Err(CAstError::Parse { path: at, .. }) => assert_eq!(at, path),
+        Err(e) => panic!("not a parse error: {}", e),
+        Ok(_) => panic!("{} parsed", path.display()),
+    }
+}
+
+#[test]
+fn missing_files_are_io_errors() {
+    let path = fixtures().join("missing.i");
+    assert!(matches!(
+        parse_file(&Config::default(), &path, true),
+        Err(CAstError::Io { .. })
+    ));
+}
+
+/// The calls of the macro `RGB(r, g, b)` as colors like `#ff8000`
+struct Rgb;
+
+impl ExpressionTransformer for Rgb {
+    fn try_transform(&self, expr: &Expression) -> Option<MyExpression> {
+        let Expression::Call(call) = expr else {
+            return None;
+        };
+        let CallExpression { callee, arguments } = &call.node;
+        match &callee.node {
+            Expression::Identifier(id) if id.node.name == "RGB" => (),
+            _ => return None,
+        }
+        let mut color = String::from("#");
+        for arg in arguments {
+            match &arg.node {
+                Expression::Constant(c) => match &c.node {
+                    Constant::Integer(i) => {
+                        color.push_str(&format!("{:02x}", i.number.parse::<u8>().ok()?))
+                    }
+                    _ => return None,
+                },
+                _ => return None,
+            }
+        }
+        Some(MyExpression::String(color))
+    }
+}
+
+/// Every expression as the string `shadowed`, registered after `Rgb`
+struct Anything;
+
+impl ExpressionTransformer for Anything {
+    fn try_transform(&self, _: &Expression) -> Option<MyExpression> {
+        Some(MyExpression::String(String::from("shadowed")))
+    }
+}
+
+/// The value of the variable `name` extracted from `source` with `Rgb`, and `Anything` after
+/// it if `anything`
+fn transformed(source: &str, name: &str, anything: bool) -> MyExpression {
+    let parsed = parse_preprocessed(&Config::default(), String::from(source)).unwrap();
+    let mut ex = Extraction::default();
+    let mut visitor = MyVisitor::new(None, &mut ex).with_transformer(Rgb);
+    if anything {
+        visitor = visitor.with_transformer(Anything);
+    }
+    visitor.visit_translation_unit(&parsed.unit);
+    drop(visitor);
+    let value = ex.values.iter().find(|(k, _)| k.name == name).unwrap().1;
+    match value {
+        MyValue::Scalar { value, .. } => value.clone(),
+        MyValue::Struct(s) => panic!("{} is a struct", s.name),
+    }
+}
+
+#[test]
+fn transformers_run_before_the_built_in_transform() {
+    let source = "const int orange = RGB(255, 128, 0);\nconst int call = CALL(1);\n";
+    assert!(matches!(
+        transformed(source, "orange", false),
+        MyExpression::String(s) if s == "#ff8000"
+    ));
+    assert!(matches!(
+        transformed(source, "call", false),
+        MyExpression::Call { name, .. } if name == "CALL"
+    ));
+    assert!(matches!(
+        transformed(source, "orange", true),
+        MyExpression::String(s) if s == "#ff8000"
+    ));
+    assert!(matches!(
+        transformed(source, "call", true),
+        MyExpression::String(s) if s == "shadowed"
+    ));
+}
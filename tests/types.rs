@@ -0,0 +1,269 @@
+//! The struct, enum and function types and the typedefs collected from the fixtures
+
+mod common;
+
+use common::{json, logged, source, stdout, warnings};
+use serde_json::{json, Value};
+
+fn fields(doc: &Value, name: &str) -> Vec<(String, String)> {
+    doc["struct_types"][name]["fields"]
+        .as_array()
+        .unwrap_or_else(|| panic!("no struct {}", name))
+        .iter()
+        .map(|f| {
+            (
+                f["name"].as_str().unwrap().to_string(),
+                f["type"].as_str().unwrap().to_string(),
+            )
+        })
+        .collect()
+}
+
+fn pairs(items: &[(&str, &str)]) -> Vec<(String, String)> {
+    items
+        .iter()
+        .map(|(a, b)| (a.to_string(), b.to_string()))
+        .collect()
+}
+
+#[test]
+fn structured_types() {
+    let doc = json(&["-q", "test.c"]);
+    assert_eq!(
+        fields(&doc, "d_t"),
+        pairs(&[
+            ("cb", "callback_t"),
+            ("name", "buffer_t"),
+            ("fmt", "const char *[2]"),
+            ("handler", "int (*)(int, const char *, ...)"),
+        ])
+    );
+    assert_eq!(doc["typedefs"]["callback_t"], "int (*)(void)");
+    assert_eq!(doc["typedefs"]["named_buffers_t"], "buffer_t [4]");
+    assert_eq!(
+        fields(&doc, "node"),
+        pairs(&[("value", "int"), ("next", "struct node *")])
+    );
+    let human = stdout(&["-q", "test.c"]);
+    assert!(human.contains("typedef buffer_t named_buffers_t[4]; // char named_buffers_t[4][256]"));
+}
+
+#[test]
+fn canonical_specifier_order() {
+    let doc = json(&["-q", "test.c"]);
+    assert_eq!(
+        fields(&doc, "spelling_t"),
+        pairs(&[
+            ("leading", "const int"),
+            ("trailing", "const int"),
+            ("mixed", "unsigned long int"),
+            ("name", "const char *const"),
+        ])
+    );
+}
+
+#[test]
+fn unions_and_bit_fields() {
+    let doc = json(&["-q", "test.c"]);
+    assert_eq!(doc["struct_types"]["reg_t"]["union"], true);
+    assert_eq!(
+        doc["struct_types"]["reg_t_bits"]["fields"][1]["bit_width"],
+        "3"
+    );
+    let device = &doc["struct_types"]["device_t"]["fields"];
+    assert_eq!(device[1]["type"], "unsigned char [2][4]");
+    assert_eq!(device[2]["bit_width"], "1");
+    // The unnamed bit-field isn't a field
+    assert_eq!(device[3]["name"], "probe");
+}
+
+#[test]
+fn known_structs_are_collected_once() {
+    let ex = source(
+        "known_twice.c",
+        "struct p { int a; int b; };\nstruct p;\nstruct p first = { 1, 2 };\n",
+    );
+    let doc = json(&["-q", &ex]);
+    assert_eq!(fields(&doc, "p"), pairs(&[("a", "int"), ("b", "int")]));
+}
+
+#[test]
+fn redefined_structs() {
+    let first = source("redefined_a.c", "struct p { int a; int b; };\n");
+    let same = source("redefined_b.c", "struct p { int a; int b; };\n");
+    let other = source(
+        "redefined_c.c",
+        "struct p { int a; long b : 4; };\nunion p *q;\n",
+    );
+    let log = logged(&[&first, &same]);
+    assert!(warnings(&log, "redefinition").is_empty(), "{}", log);
+    let log = logged(&[&first, &same, &other]);
+    let redefined = warnings(&log, "redefinition");
+    assert_eq!(redefined.len(), 1, "{}", log);
+    assert!(redefined[0]
+        .contains("struct p is redefined as `int a; long b : 4;`, keeping `int a; int b;`"));
+    let doc = json(&["-q", "--merge-format", "combined", &first, &other]);
+    assert_eq!(fields(&doc, "p"), pairs(&[("a", "int"), ("b", "int")]));
+}
+
+#[test]
+fn struct_graph() {
+    let dot = stdout(&["-q", "--dot", "test.c"]);
+    assert!(dot.starts_with("digraph structs {"));
+    assert!(dot.contains("\"c_t\" -> \"a_t\" [label=\"a\"];"));
+    // The edges closing a cycle are dashed
+    assert!(dot.contains("\"node\" -> \"node\" [label=\"*next\", style=dashed, constraint=false];"));
+    assert!(
+        dot.contains("\"tree_b\" -> \"tree_a\" [label=\"*a\", style=dashed, constraint=false];")
+    );
+    assert!(dot.contains("\"tree_a\" -> \"tree_b\" [label=\"*b\"];"));
+}
+
+#[test]
+fn unknown_structs_and_unnamed_declarators() {
+    let ex = source(
+        "unknown_struct.c",
+        "struct missing m = { 2 };\nint;\nconst int after = 1;\n",
+    );
+    let log = logged(&[&ex]);
+    assert_eq!(warnings(&log, "unknown_struct").len(), 1, "{}", log);
+    let doc = json(&["-q", &ex]);
+    assert_eq!(common::int(&doc, "after"), 1);
+}
+
+#[test]
+fn functions() {
+    let doc = json(&["-q", "--functions", "test.c"]);
+    let functions = doc["functions"].as_array().unwrap();
+    let log_message = functions
+        .iter()
+        .find(|f| f["name"] == "log_message")
+        .unwrap();
+    assert_eq!(
+        log_message,
+        &json!({
+            "name": "log_message",
+            "params": [["level", "int"], ["fmt", "const char *"]],
+            "return_type": "int",
+            "specifiers": [],
+            "variadic": true,
+        })
+    );
+    let human = stdout(&["-q", "--functions", "test.c"]);
+    assert!(human.contains("static inline int square(int x);"));
+    assert!(human.contains("_Noreturn void die(const char *reason);"));
+    assert!(human.contains("const char *name_of(struct node *n, unsigned idx);"));
+    assert!(json(&["-q", "test.c"])["functions"] == json!([]));
+}
+
+#[test]
+fn array_parameters() {
+    let human = stdout(&["-q", "--functions", "params.c"]);
+    for f in [
+        "void fill(int buf[static 4], int value);",
+        "void copy(char *restrict dst, const char *restrict src, unsigned long n);",
+        "int sum(const int values[const 8]);",
+        "int total(int n, const int table[static const n], int grid[*][4]);",
+        "void clear(volatile unsigned char regs[volatile]);",
+    ] {
+        assert!(human.contains(f), "{} in {}", f, human);
+    }
+}
+
+#[test]
+fn unused_structs() {
+    let log = logged(&["--report-unused", "unused.c"]);
+    let unused = warnings(&log, "unused");
+    assert_eq!(unused.len(), 1, "{}", log);
+    assert!(unused[0].contains("struct legacy_t is never used"));
+}
+
+#[test]
+fn fields_only() {
+    let out = stdout(&["-q", "--fields-only", "d_t", "test.c"]);
+    assert_eq!(
+        out,
+        "callback_t cb;\nbuffer_t name;\nconst char *fmt[2];\nint (*handler)(int, const char *, ...);\n"
+    );
+}
+
+#[test]
+fn anonymous_type_names() {
+    let doc = json(&["-q", "anonymous.c"]);
+    let mut structs: Vec<_> = doc["struct_types"].as_object().unwrap().keys().collect();
+    structs.sort();
+    assert_eq!(structs, ["point_t", "sprite", "sprite_size"]);
+    assert_eq!(doc["struct_types"]["sprite_size"]["anonymous"], true);
+    assert_eq!(doc["enum_types"]["sprite_state"]["anonymous"], true);
+    assert_eq!(doc["enum_types"]["level_t"]["anonymous"], true);
+    assert_eq!(doc["typedefs"]["point_t"], "struct point_t");
+    let named = json(&["-q", "--no-anonymous", "anonymous.c"]);
+    assert_eq!(named["struct_types"].as_object().unwrap().len(), 1);
+    assert_eq!(named["enum_types"], json!({}));
+    assert!(common::has(&named, "player"));
+}
+
+#[test]
+fn anonymous_names_avoid_tags() {
+    let ex = source(
+        "anonymous_tags.c",
+        "struct { int a; int b; } config = { 1, 2 };\n\
+         struct config { int port; };\n\
+         struct config defaults = { 80 };\n\
+         struct { int on; } anonymous_1 = { 1 };\n\
+         struct anonymous_1 { int x; } flag;\n",
+    );
+    let doc = json(&["-q", &ex]);
+    assert_eq!(fields(&doc, "config"), pairs(&[("port", "int")]));
+    assert_eq!(doc["struct_types"]["config"]["anonymous"], false);
+    assert_eq!(
+        fields(&doc, "config_2"),
+        pairs(&[("a", "int"), ("b", "int")])
+    );
+    assert_eq!(doc["struct_types"]["config_2"]["anonymous"], true);
+    assert_eq!(
+        common::top(&doc, "config")["value"]["Struct"]["typ"],
+        "config_2"
+    );
+    assert_eq!(
+        common::top(&doc, "defaults")["value"]["Struct"]["typ"],
+        "config"
+    );
+    assert_eq!(common::int(&doc, "defaults.port"), 80);
+    assert_eq!(fields(&doc, "anonymous_1_2"), pairs(&[("on", "int")]));
+    assert_eq!(fields(&doc, "anonymous_1"), pairs(&[("x", "int")]));
+}
+
+#[test]
+fn layouts_of_aligned_fields() {
+    let human = stdout(&["-q", "alignas.c"]);
+    assert!(human.contains("  char buf[64]; [align: 16]"));
+    assert!(human.contains("  char small; [align: 8]"));
+    assert!(human.contains("  char wide; [align: 16]"));
+    assert!(human.contains("struct buffer_t rx [align: 32]"));
+}
+
+#[test]
+fn sizeof_lengths() {
+    let doc = json(&["-q", "sizeof.c"]);
+    assert_eq!(
+        fields(&doc, "packet_t"),
+        pairs(&[
+            ("header", "struct header_t"),
+            ("payload", "unsigned char [12]"),
+            ("aligned", "char [16]"),
+            ("words", "long [2]"),
+            ("unknown", "char [sizeof(struct missing_t)]"),
+        ])
+    );
+}
+
+#[test]
+fn enum_underlying_types() {
+    let human = stdout(&["-q", "enum_base.c"]);
+    assert!(
+        human.contains("enum color : unsigned char {\n  RED = 0,\n  GREEN = 1,\n  BLUE = 200,\n}")
+    );
+    assert!(human.contains("enum big_t : unsigned long long {\n  BIG = 4294967296,\n}"));
+    assert!(human.contains("enum pixel_t_style {"));
+}
@@ -0,0 +1,634 @@
+//! The values extracted from the initializers of the fixtures
+
+mod common;
+
+use common::{at, has, int, json, logged, source, top, warnings};
+use serde_json::{json, Value};
+
+fn test_c() -> Value {
+    json(&["-q", "test.c"])
+}
+
+fn ints(v: &Value) -> Vec<i128> {
+    v["Array"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| i128::from(e["Integer"]["value"].as_i64().unwrap()))
+        .collect()
+}
+
+#[test]
+fn scalars_and_structs() {
+    let doc = test_c();
+    assert_eq!(int(&doc, "x"), 3);
+    assert_eq!(int(&doc, "anton.foo"), 1);
+    assert_eq!(int(&doc, "berta.bar"), 4);
+    assert_eq!(int(&doc, "caesar.foo"), 5);
+    assert_eq!(at(&doc, "caesar.foo")["Integer"]["raw"], "3 + 2");
+    assert_eq!(int(&doc, "emil.a.bar"), 2);
+    assert_eq!(int(&doc, "table[2].foo"), 14);
+}
+
+#[test]
+fn other_expressions_are_categorized() {
+    let doc = test_c();
+    assert_eq!(at(&doc, "kind_unknown")["Other"]["category"], "Unsupported");
+    assert_eq!(
+        at(&doc, "no_member")["Other"]["category"],
+        "UnknownIdentifier"
+    );
+    assert_eq!(at(&doc, "not_const")["Other"]["raw"], "anton.foo");
+}
+
+#[test]
+fn values_are_keyed_by_scope() {
+    let doc = test_c();
+    let scoped = |scope: Value| {
+        doc["values"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|v| v["name"] == "x" && v["scope"] == scope)
+            .map(|v| v["value"]["Scalar"]["value"]["Integer"]["value"].clone())
+    };
+    assert_eq!(scoped(json!([])), Some(json!(3)));
+    assert_eq!(scoped(json!(["main", "{0}"])), Some(json!(8)));
+    // Only static locals by default
+    assert_eq!(scoped(json!(["main"])), None);
+    assert_eq!(top_in(&doc, "seed", &["checksum"]), json!(29));
+}
+
+fn top_in(doc: &Value, name: &str, scope: &[&str]) -> Value {
+    doc["values"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|v| v["name"] == name && v["scope"] == json!(scope))
+        .map(|v| v["value"]["Scalar"]["value"]["Integer"]["value"].clone())
+        .unwrap_or_else(|| panic!("no {} in {:?}", name, scope))
+}
+
+#[test]
+fn locals_all_collects_automatic_variables() {
+    let doc = json(&["-q", "--locals", "all", "test.c"]);
+    assert_eq!(top_in(&doc, "x", &["main"]), json!(7));
+    assert_eq!(top_in(&doc, "acc", &["checksum"]), json!(29));
+    let none = json(&["-q", "--locals", "none", "test.c"]);
+    assert!(none["values"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|v| v["scope"] == json!([])));
+}
+
+#[test]
+fn multi_character_constants() {
+    let doc = test_c();
+    assert_eq!(int(&doc, "fourcc"), 0x4142_4344);
+    assert_eq!(at(&doc, "letter")["String"], "'A'");
+}
+
+#[test]
+fn designated_array_elements() {
+    let doc = test_c();
+    assert_eq!(ints(at(&doc, "sparse")), [0, 0, 0, 5, 0, 0, 0, 9, 0, 0]);
+    assert_eq!(ints(at(&doc, "ranged")), [0, -1, -1, -1, 4, 0]);
+    assert_eq!(ints(at(&doc, "grid[0]")), [1, 2, 3]);
+    assert_eq!(ints(at(&doc, "grid[1]")), [4, 0, 0]);
+    assert_eq!(ints(at(&doc, "gnu_ranges")), [1, 1, 1, 1, 0, 0, 2, 2]);
+}
+
+#[test]
+fn string_literal_bytes() {
+    let doc = test_c();
+    assert_eq!(
+        at(&doc, "escapes")["StringLiteral"]["bytes"],
+        json!([65, 9, 66, 255])
+    );
+    assert_eq!(
+        at(&doc, "GREETING")["StringLiteral"]["bytes"],
+        json!(b"hello".to_vec())
+    );
+    assert_eq!(
+        at(&doc, "MESSAGE")["StringLiteral"]["raw"],
+        json!(["\"hi\""])
+    );
+    assert_eq!(
+        at(&doc, "console.eol")["StringLiteral"]["bytes"],
+        json!([13, 10])
+    );
+}
+
+#[test]
+fn wide_strings() {
+    let doc = test_c();
+    let wide = &at(&doc, "wide_greeting")["WideString"];
+    assert_eq!(wide["encoding"], "Wide");
+    assert_eq!(wide["units"], json!([104, 101, 108, 108, 111]));
+    let utf16 = &at(&doc, "utf16_name")["WideString"];
+    assert_eq!(utf16["encoding"], "Utf16");
+    assert_eq!(utf16["units"], json!([99, 97, 102, 233]));
+    let log = logged(&["test.c"]);
+    let range = warnings(&log, "range");
+    assert!(range.iter().any(|w| w.contains("wide_cut")), "{}", log);
+}
+
+#[test]
+fn typeof_and_parenthesized_declarators() {
+    let doc = test_c();
+    assert_eq!(int(&doc, "same_as_x"), 9);
+    assert_eq!(at(&doc, "handler_ptr"), &json!({"AddressOf": "x"}));
+    assert_eq!(doc["typedefs"]["escapes_t"], "typeof(escapes)");
+    assert_eq!(doc["typedefs"]["int_ptr_t"], "typeof(int *)");
+}
+
+#[test]
+fn complex_initializers() {
+    let doc = test_c();
+    assert_eq!(at(&doc, "phasor")["Other"]["raw"], "1.0 + (2.0 * 1.0if)");
+    assert_eq!(at(&doc, "unit_i")["Other"]["raw"], "1.0if");
+    assert_eq!(at(&doc, "carrier.amp")["Other"]["raw"], "0.5 * 1.0i");
+}
+
+#[test]
+fn const_references_are_folded() {
+    let doc = test_c();
+    assert_eq!(int(&doc, "OFFSET"), 15);
+    assert_eq!(int(&doc, "LIMIT"), 30);
+    assert_eq!(at(&doc, "LIMIT")["Integer"]["raw"], "OFFSET * 2");
+    assert_eq!(at(&doc, "SCALE_COPY")["Float"], "1.5");
+    assert_eq!(ints(at(&doc, "offsets")), [0, 30, 0, 0, 0]);
+    // Declared after the use
+    assert_eq!(at(&doc, "EARLY")["Other"]["category"], "UnknownIdentifier");
+    let log = logged(&["test.c"]);
+    assert!(warnings(&log, "unresolved")
+        .iter()
+        .any(|w| w.contains("EARLY")));
+}
+
+#[test]
+fn floating_const_references() {
+    let doc = test_c();
+    assert_eq!(at(&doc, "SCALED")["Other"]["raw"], "SCALE * 2");
+    assert_eq!(at(&doc, "NEGATED")["Other"]["raw"], "-HALF");
+    assert_eq!(int(&doc, "WHOLE"), 1);
+    let log = logged(&["test.c"]);
+    let unresolved = warnings(&log, "unresolved");
+    for name in ["SCALED", "NEGATED"] {
+        assert!(
+            unresolved.iter().any(|w| w.contains(&format!(
+                "{} = {} isn't folded",
+                name,
+                at(&doc, name)["Other"]["raw"].as_str().unwrap()
+            ))),
+            "{}",
+            log
+        );
+    }
+}
+
+#[test]
+fn integer_ranges() {
+    let doc = test_c();
+    assert_eq!(int(&doc, "pixel_bad.red"), 300);
+    let log = logged(&["test.c"]);
+    let range: Vec<_> = warnings(&log, "range");
+    for name in [
+        "red = 300",
+        "bias = -129",
+        "level_bad = 256",
+        "offset_bad = 128",
+    ] {
+        assert!(range.iter().any(|w| w.contains(name)), "{}", name);
+    }
+    for name in ["level_ok", "offset_ok", "red = 255"] {
+        assert!(!range.iter().any(|w| w.contains(name)), "{}", name);
+    }
+}
+
+#[test]
+fn bitwise_operators() {
+    let doc = test_c();
+    assert_eq!(int(&doc, "flags"), 20);
+    assert_eq!(int(&doc, "status"), 0x0f00);
+    assert_eq!(int(&doc, "inverted"), -24);
+    assert_eq!(int(&doc, "high"), 0x1000_0000);
+    assert_eq!(at(&doc, "overshift")["Other"]["category"], "Unsupported");
+    assert_eq!(int(&doc, "wide_bit"), 1 << 40);
+    assert_eq!(int(&doc, "sign_bit"), i128::from(i64::MIN));
+    assert_eq!(int(&doc, "all_ones"), 4_294_967_295);
+    let log = logged(&["test.c"]);
+    let shift = warnings(&log, "shift");
+    assert_eq!(shift.len(), 1, "{}", log);
+    assert!(shift[0].contains("Shifting 1 by 32 bits"));
+}
+
+#[test]
+fn offsetof_from_the_layouts() {
+    let doc = test_c();
+    assert_eq!(int(&doc, "length_offset"), 4);
+    assert_eq!(int(&doc, "port_offset"), 12);
+    assert_eq!(int(&doc, "bias_offset"), 15);
+    assert_eq!(int(&doc, "stamp_offset"), 16);
+    assert_eq!(int(&doc, "builtin_length_offset"), 4);
+    assert_eq!(
+        at(&doc, "unknown_offset")["Other"]["category"],
+        "Unsupported"
+    );
+}
+
+#[test]
+fn enum_constants() {
+    let doc = test_c();
+    assert_eq!(int(&doc, "permissions"), 3);
+    assert_eq!(int(&doc, "options"), 7);
+    let mode = &doc["enum_types"]["mode"]["constants"];
+    assert_eq!(mode[2], json!({"name": "MODE_AUTO", "value": 5}));
+}
+
+#[test]
+fn generic_selections() {
+    let doc = test_c();
+    assert_eq!(int(&doc, "kind_int"), 1);
+    assert_eq!(int(&doc, "kind_unsigned"), 2);
+    assert_eq!(int(&doc, "kind_double"), 3);
+    assert_eq!(int(&doc, "kind_char"), 0);
+    assert_eq!(int(&doc, "kind_sum"), 2);
+    assert_eq!(
+        at(&doc, "kind_name")["StringLiteral"]["bytes"],
+        json!(b"float".to_vec())
+    );
+}
+
+#[test]
+fn elements_beyond_the_length_are_dropped() {
+    let doc = test_c();
+    assert_eq!(ints(at(&doc, "bounded")), [0, 1, 2, 2]);
+    assert_eq!(ints(at(&doc, "overfull")), [1, 2]);
+    let log = logged(&["test.c"]);
+    let designator = warnings(&log, "designator");
+    assert!(designator.iter().any(|w| w.contains("bounded[4]")));
+    assert!(designator.iter().any(|w| w.contains("overfull[2]")));
+}
+
+#[test]
+fn float_suffixes() {
+    let doc = test_c();
+    assert_eq!(
+        at(&doc, "precise.pi")["Float"],
+        "3.14159265358979323846264338327950288L"
+    );
+    assert_eq!(at(&doc, "precise.ratio")["Float"], "0.5f");
+    assert_eq!(at(&doc, "SCALE")["Float"], "1.5");
+}
+
+#[test]
+fn member_designators() {
+    let doc = test_c();
+    assert_eq!(int(&doc, "legacy_ops.open"), 1);
+    assert_eq!(int(&doc, "legacy_ops.read"), 2);
+    assert_eq!(int(&doc, "modern_ops.read"), 2);
+    assert_eq!(int(&doc, "modern_ops.write"), 3);
+    assert_eq!(ints(at(&doc, "modern_ops.flags")), [0, 9, 9, 9]);
+    assert_eq!(int(&doc, "anon.b"), 2);
+}
+
+#[test]
+fn pointer_tables() {
+    let doc = test_c();
+    assert_eq!(
+        at(&doc, "commands"),
+        &json!({"Array": [{"AddressOf": "cmd_a"}, {"AddressOf": "cmd_b"}, "Null"]})
+    );
+    assert_eq!(int(&doc, "cmd_b.id"), 2);
+}
+
+#[test]
+fn gnu_builtins() {
+    let doc = test_c();
+    assert_eq!(int(&doc, "swapped_magic"), 0x3412);
+    assert_eq!(int(&doc, "expected_value"), 10);
+    assert_eq!(int(&doc, "folded_constant_p"), 1);
+}
+
+#[test]
+fn zero_initialization() {
+    let doc = test_c();
+    for name in ["big_zero", "big_empty"] {
+        assert_eq!(int(&doc, &format!("{}.count", name)), 0);
+        assert_eq!(at(&doc, &format!("{}.ratio", name))["Float"], "0.0");
+        assert_eq!(at(&doc, &format!("{}.owner", name)), &json!("Null"));
+        assert_eq!(int(&doc, &format!("{}.tint.red", name)), 0);
+        assert_eq!(ints(at(&doc, &format!("{}.history", name))), [0, 0, 0]);
+    }
+    assert_eq!(int(&doc, "empty_scalar"), 0);
+    assert_eq!(int(&doc, "braced"), 7);
+    assert_eq!(int(&doc, "empty_limits.range.bar"), 0);
+    assert_eq!(int(&doc, "empty_ranges[1].foo"), 0);
+    assert_eq!(ints(at(&doc, "trailing")), [1, 2, 3]);
+}
+
+#[test]
+fn calls() {
+    let doc = test_c();
+    let orange = &at(&doc, "orange")["Call"];
+    assert_eq!(orange["name"], "make_color");
+    assert_eq!(orange["args"][1]["Integer"]["value"], 160);
+    assert_eq!(
+        at(&doc, "w"),
+        &json!({"Call": {"name": "get_w", "args": []}})
+    );
+}
+
+#[test]
+fn attributes() {
+    let doc = test_c();
+    let attrs = |name| &top(&doc, name)["value"]["Scalar"]["attrs"];
+    assert_eq!(attrs("legacy_mode")["deprecated"], true);
+    assert_eq!(attrs("old_limit")["deprecated_message"], "use LIMIT");
+    assert_eq!(attrs("boot_counter")["asm_label"], "real_boot_counter");
+    assert_eq!(attrs("config_version")["section"], ".config");
+    let timeout = &doc["struct_types"]["settings_t"]["fields"][1]["attrs"];
+    assert_eq!(timeout["deprecated_message"], "use timeout_ms");
+}
+
+#[test]
+fn members_of_const_structs() {
+    let doc = test_c();
+    assert_eq!(int(&doc, "low_limit"), 5);
+    assert_eq!(int(&doc, "range_span"), 10);
+    assert_eq!(int(&doc, "second_bar"), 4);
+}
+
+#[test]
+fn index_chains() {
+    let doc = test_c();
+    assert_eq!(int(&doc, "cell"), 6);
+    assert_eq!(int(&doc, "indexed_cell"), 6);
+    assert_eq!(
+        at(&doc, "indexed_cell")["Integer"]["raw"],
+        "cells[row][row + 1]"
+    );
+    assert_eq!(int(&doc, "held"), 8);
+    assert_eq!(int(&doc, "range_bar"), 4);
+    assert_eq!(at(&doc, "outside")["Other"]["raw"], "cells[2][0]");
+    // Rendered as C, not as the syntax tree
+    assert_eq!(at(&doc, "assigned")["Other"]["raw"], "retries = 2");
+    let log = logged(&["test.c"]);
+    let unresolved = warnings(&log, "unresolved");
+    assert!(
+        unresolved
+            .iter()
+            .any(|w| w.contains("outside = cells[2][0] isn't resolved")),
+        "{}",
+        log
+    );
+}
+
+#[test]
+fn chained_designators() {
+    let doc = test_c();
+    assert_eq!(int(&doc, "window.origin.foo"), 3);
+    assert_eq!(int(&doc, "window.origin.bar"), 4);
+    assert_eq!(int(&doc, "window.limits.low"), 2);
+    assert_eq!(int(&doc, "window.limits.range.bar"), 9);
+    assert_eq!(int(&doc, "window.depth"), 1);
+}
+
+#[test]
+fn sparse_arrays() {
+    let doc = test_c();
+    let lut = &at(&doc, "lut")["SparseArray"];
+    assert_eq!(lut["len"], 256);
+    assert_eq!(lut["entries"].as_array().unwrap().len(), 3);
+    assert_eq!(int(&doc, "lut[65]"), 2);
+    assert_eq!(int(&doc, "sparse_table.table[50]"), 5);
+    let dense = json(&["-q", "--dense-arrays", "test.c"]);
+    assert_eq!(at(&dense, "lut")["Array"].as_array().unwrap().len(), 256);
+}
+
+#[test]
+fn huge_sparse_arrays() {
+    let big = source("big.c", "int big[] = { [100000000] = 1, [7] = 2 };\n");
+    let doc = json(&["-q", &big]);
+    let big = &at(&doc, "big")["SparseArray"];
+    assert_eq!(big["len"], 100000001);
+    assert_eq!(big["entries"].as_array().unwrap().len(), 2);
+    assert_eq!(int(&doc, "big[100000000]"), 1);
+    assert_eq!(int(&doc, "big[7]"), 2);
+}
+
+#[test]
+fn designated_structs_of_arrays() {
+    let doc = test_c();
+    assert_eq!(int(&doc, "rows[0].a"), 3);
+    assert_eq!(int(&doc, "rows[1].b"), 2);
+    assert_eq!(int(&doc, "more_rows[0].b"), 5);
+    assert_eq!(int(&doc, "more_rows[1].a"), 7);
+    assert_eq!(int(&doc, "more_rows[2].a"), 1);
+    assert_eq!(int(&doc, "row_grid.rows[1].a"), 4);
+    assert_eq!(int(&doc, "row_grid.rows[1].b"), 6);
+    assert_eq!(int(&doc, "row_grid.rows[2].b"), 9);
+}
+
+#[test]
+fn function_references() {
+    let doc = test_c();
+    assert_eq!(
+        at(&doc, "handlers[0]"),
+        &json!({"FunctionRef": "handle_get"})
+    );
+    assert_eq!(
+        at(&doc, "handlers[1]"),
+        &json!({"FunctionRef": "handle_put"})
+    );
+    assert_eq!(
+        at(&doc, "command_table[1].run"),
+        &json!({"FunctionRef": "handle_put"})
+    );
+}
+
+#[test]
+fn booleans() {
+    let doc = json(&["-q", "bool.c"]);
+    assert_eq!(at(&doc, "on"), &json!({"Bool": true}));
+    assert_eq!(at(&doc, "off"), &json!({"Bool": false}));
+    assert_eq!(at(&doc, "nonzero"), &json!({"Bool": true}));
+    assert_eq!(at(&doc, "features.metrics"), &json!({"Bool": true}));
+    assert_eq!(at(&doc, "no_features.tracing"), &json!({"Bool": false}));
+    assert_eq!(int(&doc, "features.level"), 3);
+    assert_eq!(int(&doc, "next_level"), 2);
+}
+
+#[test]
+fn digit_separators() {
+    let doc = json(&["-q", "digits.c"]);
+    assert_eq!(int(&doc, "million"), 1_000_000);
+    assert_eq!(at(&doc, "million")["Integer"]["raw"], "1'000'000");
+    assert_eq!(int(&doc, "mask"), 0xffff_0000);
+    assert_eq!(int(&doc, "negative"), -2500);
+    assert_eq!(ints(at(&doc, "sizes")), [1024, 4096, 65536]);
+    assert_eq!(
+        at(&doc, "text")["StringLiteral"]["bytes"],
+        json!(b"1'000".to_vec())
+    );
+}
+
+#[test]
+fn anonymous_members() {
+    let doc = json(&["-q", "anonymous_fields.c"]);
+    assert_eq!(int(&doc, "positional.i"), 42);
+    assert_eq!(int(&doc, "positional.column"), 7);
+    assert_eq!(at(&doc, "designated.f")["Float"], "1.5f");
+    assert_eq!(int(&doc, "designated.column"), 9);
+    assert_eq!(int(&doc, "reg_raw.raw"), 0x1234_5678);
+    assert_eq!(int(&doc, "reg_low.low"), 5);
+    assert_eq!(int(&doc, "reg_low.hb1"), 7);
+    assert_eq!(int(&doc, "reg_low.tail"), 3);
+    assert_eq!(int(&doc, "reg_bytes.hb0"), 1);
+    let nested = json(&[
+        "-q",
+        "--include-anonymous-fields",
+        "nest",
+        "anonymous_fields.c",
+    ]);
+    assert_eq!(int(&nested, "positional.anonymous_1.i"), 42);
+    assert_eq!(int(&nested, "positional.anonymous_2.line"), 3);
+}
+
+#[test]
+fn elided_braces_into_anonymous_members() {
+    let doc = json(&["-q", "anonymous_fields.c"]);
+    assert_eq!(int(&doc, "elided.i"), 42);
+    assert_eq!(int(&doc, "elided.line"), 3);
+    assert_eq!(int(&doc, "elided.column"), 7);
+    assert_eq!(int(&doc, "reg_elided.raw"), 1);
+    assert_eq!(int(&doc, "reg_elided.tail"), 4);
+    assert_eq!(int(&doc, "reg_continued.low"), 5);
+    assert_eq!(int(&doc, "reg_continued.high"), 6);
+    assert_eq!(int(&doc, "reg_continued.tail"), 7);
+    assert_eq!(int(&doc, "reg_bytes_continued.high"), 5);
+    assert_eq!(int(&doc, "reg_bytes_continued.tail"), 6);
+    let nested = json(&[
+        "-q",
+        "--include-anonymous-fields",
+        "nest",
+        "anonymous_fields.c",
+    ]);
+    assert_eq!(int(&nested, "elided.anonymous_1.i"), 42);
+    assert_eq!(int(&nested, "elided.anonymous_2.column"), 7);
+    assert_eq!(
+        int(
+            &nested,
+            "reg_continued.anonymous_1.anonymous_1.anonymous_1.high"
+        ),
+        6
+    );
+    let log = common::logged(&["anonymous_fields.c"]);
+    assert!(common::warnings(&log, "designator").is_empty(), "{}", log);
+}
+
+#[test]
+fn alignment() {
+    let doc = json(&["-q", "alignas.c"]);
+    assert_eq!(int(&doc, "buf_offset"), 16);
+    assert_eq!(int(&doc, "small_offset"), 80);
+    assert_eq!(int(&doc, "count_offset"), 88);
+    assert_eq!(
+        at(&doc, "text")["StringLiteral"]["bytes"],
+        json!(b"_Alignas(8)".to_vec())
+    );
+}
+
+#[test]
+fn sizeof_array_lengths() {
+    let doc = json(&["-q", "sizeof.c"]);
+    assert_eq!(at(&doc, "scratch")["Array"].as_array().unwrap().len(), 8);
+    assert_eq!(
+        at(&doc, "packet.payload")["Array"]
+            .as_array()
+            .unwrap()
+            .len(),
+        12
+    );
+    assert_eq!(doc["typedefs"]["raw_header_t"], "unsigned char [8]");
+}
+
+#[test]
+fn universal_character_names() {
+    let doc = json(&["-q", "ucn.c"]);
+    assert_eq!(int(&doc, "café.naïve"), 2);
+    assert_eq!(int(&doc, "π_x100"), 314);
+    assert_eq!(int(&doc, "präfix"), 1);
+    assert!(doc["struct_types"]["café_t"].is_object());
+}
+
+#[test]
+fn enum_bases() {
+    let doc = json(&["-q", "enum_base.c"]);
+    assert_eq!(int(&doc, "background.c"), 1);
+    assert_eq!(int(&doc, "background.style"), 1);
+    assert_eq!(int(&doc, "default_level"), 1);
+    assert_eq!(doc["enum_types"]["color"]["underlying"], "unsigned char");
+    assert_eq!(doc["enum_types"]["level"]["underlying"], "short");
+    assert_eq!(
+        doc["enum_types"]["color"]["constants"][2],
+        json!({"name": "BLUE", "value": 200})
+    );
+}
+
+#[test]
+fn anonymous_types() {
+    let doc = json(&["-q", "anonymous.c"]);
+    assert_eq!(int(&doc, "player.size.h"), 32);
+    assert_eq!(int(&doc, "player.state"), 4);
+    assert_eq!(int(&doc, "level_count"), 2);
+    assert!(has(&doc, "alarm_level"));
+}
+
+#[test]
+fn brace_elision() {
+    let doc = json(&["-q", "braces.c"]);
+    assert_eq!(ints(at(&doc, "flat.a")), [1, 2]);
+    assert_eq!(int(&doc, "flat.b"), 3);
+    assert_eq!(ints(at(&doc, "nested.p.a")), [1, 2]);
+    assert_eq!(int(&doc, "nested.p.b"), 3);
+    assert_eq!(int(&doc, "nested.c"), 4);
+    assert_eq!(ints(at(&doc, "grid[0]")), [1, 2, 3]);
+    assert_eq!(ints(at(&doc, "grid[1]")), [4, 0, 0]);
+    assert_eq!(int(&doc, "points[1].x"), 3);
+    assert_eq!(int(&doc, "points[1].y"), 4);
+    assert_eq!(int(&doc, "points[2].x"), 5);
+    // A string literal sets a whole char array, but only an element of an array of pointers
+    assert_eq!(
+        at(&doc, "labels.name")["StringLiteral"]["raw"],
+        json!(["\"abc\""])
+    );
+    assert_eq!(
+        at(&doc, "labels.fmt[1]")["StringLiteral"]["raw"],
+        json!(["\"y\""])
+    );
+    assert_eq!(int(&doc, "labels.n"), 3);
+    assert_eq!(int(&doc, "tagged.u.i"), 2);
+    assert_eq!(int(&doc, "tagged.after"), 3);
+    let log = logged(&["braces.c"]);
+    let designator = warnings(&log, "designator");
+    assert_eq!(designator.len(), 1, "{}", log);
+    assert!(designator[0].contains("Ignoring the excess element 4 of excess"));
+}
+
+#[test]
+fn positional_items_after_designators() {
+    let doc = json(&["-q", "braces.c"]);
+    assert_eq!(ints(at(&doc, "designated_grid[0]")), [0, 0, 0]);
+    assert_eq!(ints(at(&doc, "designated_grid[1]")), [4, 5, 0]);
+    assert_eq!(int(&doc, "continued.in.x"), 1);
+    assert_eq!(int(&doc, "continued.in.y"), 2);
+    assert_eq!(int(&doc, "continued.z"), 3);
+    assert_eq!(ints(at(&doc, "after_element.a")), [5, 6]);
+    assert_eq!(int(&doc, "after_element.b"), 7);
+    assert_eq!(int(&doc, "moved[1].x"), 1);
+    assert_eq!(int(&doc, "moved[1].y"), 2);
+    assert_eq!(ints(at(&doc, "after_range")), [0, 9, 9, 3, 0, 0]);
+}